@@ -1,3 +1,69 @@
 fn main() {
-    tauri_build::build()
+    // App commands listed here get `allow-*`/`deny-*` permissions generated,
+    // and become unreachable from any window whose capability omits them.
+    tauri_build::try_build(tauri_build::Attributes::new().app_manifest(
+        tauri_build::AppManifest::new().commands(&[
+            "list_todos",
+            "todos_by_date",
+            "first_todo",
+            "add_todo",
+            "quick_add",
+            "parse_quick_add",
+            "toggle_todo",
+            "update_todo",
+            "todo_tree",
+            "move_todo",
+            "set_todo_due",
+            "set_todo_reminder",
+            "snooze_reminder",
+            "set_todo_priority",
+            "set_todo_recurrence",
+            "set_todo_notes",
+            "render_notes",
+            "open_link",
+            "list_attachments",
+            "attach_dropped_files",
+            "open_attachment",
+            "remove_attachment",
+            "overdue_todos",
+            "search_todos",
+            "export_json",
+            "import_json",
+            "export_todo_txt",
+            "import_todo_txt",
+            "export_ical",
+            "import_ical",
+            "report",
+            "delete_todo",
+            "clear_completed",
+            "reorder_todos",
+            "today",
+            "sort_mode",
+            "set_sort_mode",
+            "historical_dates",
+            "future_dates",
+            "take_pending_date",
+            "list_tags",
+            "tag_todo",
+            "untag_todo",
+            "rename_tag",
+            "merge_tags",
+            "set_tag_color",
+            "delete_tag",
+            "todos_by_tags",
+            "list_projects",
+            "create_project",
+            "rename_project",
+            "set_project_style",
+            "archive_project",
+            "reorder_projects",
+            "delete_project",
+            "project_todos",
+            "reorder_project_todos",
+            "move_todos_to_project",
+            "quick_add_shortcut",
+            "set_quick_add_shortcut",
+        ]),
+    ))
+    .expect("failed to run tauri build script");
 }
@@ -0,0 +1,134 @@
+//! Launch requests carried out by the running app: the `--add`, `--compact`
+//! and `--date` flags and `rtodo://` deep links, whether given to this
+//! process or forwarded from a second invocation by the single-instance
+//! plugin.
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, Runtime, State};
+use tracing::{error, info, instrument, warn};
+
+use crate::cli::LaunchArgs;
+use crate::deep_link::DeepLink;
+use crate::store::{self, Store, Todo};
+use crate::{events, windows};
+
+/// Day requested at first launch, held until the main window has loaded far
+/// enough to ask for it.
+#[derive(Default)]
+pub struct PendingDate(Mutex<Option<String>>);
+
+#[tauri::command]
+pub fn take_pending_date(pending: State<'_, PendingDate>) -> Option<String> {
+    pending.0.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Handles the argv of a second `rtodo` invocation.
+pub fn forward<R: Runtime>(app: &AppHandle<R>, argv: Vec<String>) {
+    match crate::cli::parse_launch(argv) {
+        Ok(args) => apply(app, args, false),
+        Err(e) => {
+            warn!(error = %e, "ignoring unparsable arguments from second instance");
+            focus_main(app);
+        }
+    }
+}
+
+#[instrument(skip(app))]
+pub fn apply<R: Runtime>(app: &AppHandle<R>, args: LaunchArgs, first_launch: bool) {
+    if let Some(url) = &args.url {
+        return open_link(app, url, first_launch);
+    }
+
+    if let Some(date) = &args.date {
+        if let Err(e) = store::parse_date(date) {
+            warn!(error = %e, "ignoring --date");
+            return focus_main(app);
+        }
+    }
+
+    if let Some(text) = &args.add {
+        add(app, text, args.date.as_deref());
+    }
+
+    if args.compact {
+        if let Err(e) = windows::show_compact(app) {
+            warn!(error = %e, "failed to open compact window");
+        }
+    } else if args.add.is_none() {
+        navigate(app, args.date, first_launch);
+    }
+}
+
+/// Carries out an `rtodo://` link. Invalid links are logged and dropped.
+#[instrument(skip(app))]
+pub fn open_link<R: Runtime>(app: &AppHandle<R>, url: &str, first_launch: bool) {
+    let link = match DeepLink::parse(url) {
+        Ok(link) => link,
+        Err(e) => {
+            error!(error = %e, "rejected deep link");
+            return;
+        }
+    };
+
+    match link {
+        DeepLink::Add { text, date } => {
+            let added = add(app, &text, date.as_deref());
+            let day = added.map(|todo| todo.day().to_owned()).or(date);
+            navigate(app, day, first_launch);
+        }
+        DeepLink::Open { date } => navigate(app, date, first_launch),
+        DeepLink::Todo(id) => open_todo(app, id, first_launch),
+    }
+}
+
+/// Shows the main window on the day of todo `id`.
+pub fn open_todo<R: Runtime>(app: &AppHandle<R>, id: i64, first_launch: bool) {
+    match app.state::<Store>().get_todo(id) {
+        Ok(todo) => navigate(app, Some(todo.day().to_owned()), first_launch),
+        Err(e) => error!(id, error = %e, "cannot open a missing todo"),
+    }
+}
+
+/// Shows the running app's main window on `date`, or on today when `None`.
+pub fn open_day<R: Runtime>(app: &AppHandle<R>, date: Option<String>) {
+    navigate(app, date, false);
+}
+
+/// Adds a todo from quick-add text, which may name its own day.
+fn add<R: Runtime>(app: &AppHandle<R>, text: &str, date: Option<&str>) -> Option<Todo> {
+    match app.state::<Store>().quick_add(text, date) {
+        Ok(todo) => {
+            info!(id = todo.id, "added todo from launch request");
+            events::todo_added(app, &todo);
+            if todo.project_id.is_some() {
+                events::projects_changed(app);
+            }
+            Some(todo)
+        }
+        Err(e) => {
+            warn!(error = %e, "failed to add todo from launch request");
+            None
+        }
+    }
+}
+
+/// Shows the main window on `date`, or on today when `None`.
+fn navigate<R: Runtime>(app: &AppHandle<R>, date: Option<String>, first_launch: bool) {
+    if first_launch {
+        // The webview has not registered its listeners yet.
+        *app.state::<PendingDate>()
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = date;
+    } else {
+        events::open_date(app, date.as_deref());
+    }
+    focus_main(app);
+}
+
+fn focus_main<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(e) = windows::show_main(app) {
+        warn!(error = %e, "failed to focus main window");
+    }
+}
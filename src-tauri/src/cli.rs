@@ -0,0 +1,1039 @@
+//! Command-line interface.
+//!
+//! `rtodo <subcommand>` works on the same database as the GUI without
+//! creating any window; a bare `rtodo` launches the app as before. Open
+//! windows pick up CLI changes through the `sync` watcher.
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use chrono::{DateTime, Days, Local};
+use clap::{Args, Parser, Subcommand};
+use tracing::warn;
+
+use crate::store::projects::ProjectSummary;
+use crate::store::tags::TagUsage;
+use crate::store::{
+    carry_over, days, parse_date, priority, search, subtasks, Attachment, Document, ImportStrategy,
+    NewTodo, Priority, ProjectDeletion, ReportFormat, SearchHit, Store, TagMatch, Todo, TodoNode,
+};
+use crate::{feed, shortcut};
+
+/// Settings `rtodo config` knows about, with their defaults.
+const SETTINGS: &[(&str, &str)] = &[
+    (carry_over::SETTING, "move"),
+    (days::START_HOUR_SETTING, "0"),
+    (priority::SORT_SETTING, "manual"),
+    (subtasks::COMPLETION_SETTING, "cascade"),
+    (shortcut::SETTING, shortcut::DEFAULT),
+    (feed::SETTING, feed::DEFAULT),
+];
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "rtodo",
+    version,
+    about = "A small daily todo list",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    /// Database file to use instead of the one in the app data directory.
+    /// Without a subcommand the app opens it, unless rtodo is already
+    /// running.
+    #[arg(long, global = true, env = "RTODO_DB", value_name = "PATH")]
+    db: Option<PathBuf>,
+
+    #[command(flatten)]
+    launch: LaunchArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Flags for launching the app. When rtodo is already running they are
+/// forwarded to that instance instead of starting a second one.
+#[derive(Debug, Default, Clone, Args)]
+pub struct LaunchArgs {
+    /// Add a todo in the running app, parsed like quick-add input; it goes
+    /// to today or to --date unless the text names a day.
+    #[arg(long, value_name = "TEXT")]
+    pub add: Option<String>,
+    /// Switch to the compact window.
+    #[arg(long)]
+    pub compact: bool,
+    /// Open the main window on this day (YYYY-MM-DD).
+    #[arg(long, value_name = "DATE")]
+    pub date: Option<String>,
+    /// `rtodo://` link the OS launched us with.
+    #[arg(hide = true, conflicts_with_all = ["add", "compact", "date"])]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Add a todo for today or for the given day. Days, times, #tags,
+    /// !priority, +Project and repeat phrases in the text are recognized, as
+    /// in "Pay rent tomorrow 9am #home !high every month"; options given
+    /// explicitly win.
+    Add {
+        text: String,
+        /// Keep the text as typed instead of looking for days, tags and so
+        /// on in it.
+        #[arg(long)]
+        literal: bool,
+        /// Day to schedule the todo on (YYYY-MM-DD).
+        #[arg(long)]
+        date: Option<String>,
+        /// Deadline, as "YYYY-MM-DD HH:MM" in local time or RFC 3339.
+        #[arg(long, value_name = "WHEN")]
+        due: Option<String>,
+        /// When to show a reminder, in the same formats as --due.
+        #[arg(long, value_name = "WHEN")]
+        remind: Option<String>,
+        /// Project to add the todo to.
+        #[arg(long)]
+        project: Option<String>,
+        /// Tag to add; repeat for several.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// none, low, medium, high or urgent.
+        #[arg(long)]
+        priority: Option<String>,
+        /// Add as a subtask of this todo, on its day.
+        #[arg(long, value_name = "ID", conflicts_with = "date")]
+        parent: Option<i64>,
+        /// Repeat rule, e.g. "every weekday" or "FREQ=MONTHLY;BYDAY=-1FR".
+        #[arg(long, value_name = "RULE", conflicts_with = "parent")]
+        repeat: Option<String>,
+    },
+    /// List today's todos.
+    List {
+        /// Day to list instead of today (YYYY-MM-DD).
+        #[arg(long, conflicts_with = "all")]
+        date: Option<String>,
+        /// List the todos of a project, from every day, in project order.
+        #[arg(long, conflicts_with_all = ["date", "all", "tags"])]
+        project: Option<String>,
+        /// List todos of every day.
+        #[arg(long)]
+        all: bool,
+        /// Only list todos with this tag; repeat for several.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// With several --tag, match todos carrying any of them instead of
+        /// all.
+        #[arg(long, requires = "tags")]
+        any: bool,
+        /// Print JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Mark a todo as completed.
+    Done { id: i64 },
+    /// Set or clear a todo's deadline.
+    Due {
+        id: i64,
+        /// Deadline, as "YYYY-MM-DD HH:MM" in local time or RFC 3339.
+        #[arg(required_unless_present = "clear")]
+        when: Option<String>,
+        /// Remove the deadline.
+        #[arg(long, conflicts_with = "when")]
+        clear: bool,
+    },
+    /// Set or clear the time a todo's reminder is shown while the app runs.
+    Remind {
+        id: i64,
+        /// Reminder time, as "YYYY-MM-DD HH:MM" in local time or RFC 3339.
+        #[arg(required_unless_present = "clear")]
+        when: Option<String>,
+        /// Remove the reminder.
+        #[arg(long, conflicts_with = "when")]
+        clear: bool,
+    },
+    /// Show a todo's reminder again some minutes from now.
+    Snooze {
+        id: i64,
+        #[arg(long, default_value_t = 10)]
+        minutes: u32,
+    },
+    /// Set a todo's priority: none, low, medium, high or urgent.
+    Priority { id: i64, priority: String },
+    /// Make a todo repeat, e.g. "every 2 weeks on monday", "monthly on the
+    /// last friday" or "3 days after completion".
+    Repeat {
+        id: i64,
+        #[arg(required_unless_present = "clear")]
+        rule: Option<String>,
+        /// Stop the todo repeating.
+        #[arg(long, conflicts_with = "rule")]
+        clear: bool,
+    },
+    /// Show a todo's Markdown notes, or replace them with TEXT.
+    Notes {
+        id: i64,
+        #[arg(conflicts_with = "clear")]
+        text: Option<String>,
+        /// Remove the notes.
+        #[arg(long)]
+        clear: bool,
+    },
+    /// List incomplete todos whose deadline has passed.
+    Overdue {
+        /// Print JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search the todos of every day. Words match as prefixes; quote a
+    /// phrase to match it exactly, e.g. `rtodo search '"pay rent"' march`.
+    Search {
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Most results to show.
+        #[arg(long, default_value_t = search::DEFAULT_LIMIT)]
+        limit: usize,
+        /// Print JSON instead of a list.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add tags to a todo.
+    Tag {
+        id: i64,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from a todo.
+    Untag {
+        id: i64,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// List or manage tags.
+    Tags {
+        #[command(subcommand)]
+        command: Option<TagsCommand>,
+    },
+    /// Move todos to a project, or out of any project.
+    Move {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+        #[arg(long, required_unless_present = "none")]
+        project: Option<String>,
+        /// Take the todos out of their project.
+        #[arg(long, conflicts_with = "project")]
+        none: bool,
+    },
+    /// List or manage projects.
+    Projects {
+        #[command(subcommand)]
+        command: Option<ProjectsCommand>,
+    },
+    /// Move a todo and its subtasks under another todo, or back to the top
+    /// level of its day.
+    Nest {
+        id: i64,
+        /// New parent todo.
+        #[arg(long, value_name = "ID", required_unless_present = "top")]
+        under: Option<i64>,
+        /// Make it a top-level todo again.
+        #[arg(long, conflicts_with = "under")]
+        top: bool,
+        /// Position among its new siblings, from 0; the end by default.
+        #[arg(long, value_name = "N")]
+        at: Option<usize>,
+    },
+    /// Copy files into the app and attach them to a todo.
+    Attach {
+        id: i64,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// List the files attached to a todo.
+    Attachments {
+        id: i64,
+        /// Print JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove an attachment by its id.
+    Detach { attachment: i64 },
+    /// Copy the database and all attachments into an empty folder.
+    Backup { dir: PathBuf },
+    /// Write the todos to a file, or to standard output. JSON holds every
+    /// todo, project, tag and attached file; todo.txt leaves out notes,
+    /// subtasks and attachments, and ics leaves out projects and attachments.
+    Export {
+        #[arg(long, default_value = "json", value_parser = ["json", "todo.txt", "ics"])]
+        format: String,
+        /// File to write instead of standard output.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Read todos from a file written by `rtodo export`, or from standard
+    /// input when the file is `-`.
+    Import {
+        file: PathBuf,
+        #[arg(long, default_value = "json", value_parser = ["json", "todo.txt", "ics"])]
+        format: String,
+        /// replace deletes everything first, merge updates todos already
+        /// imported once and adds the rest, append adds everything anew.
+        /// todo.txt lines are never matched to existing todos.
+        #[arg(long, default_value = "merge")]
+        strategy: String,
+    },
+    /// Write the todos of a range of days as CSV or as a Markdown
+    /// checklist, to a file or to standard output.
+    Report {
+        /// First day (YYYY-MM-DD); six days before --to when omitted.
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day (YYYY-MM-DD); today when omitted.
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(long, default_value = "markdown", value_parser = ["csv", "markdown"])]
+        format: String,
+        /// File to write instead of standard output.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Delete a todo.
+    Rm { id: i64 },
+    /// Reorder todos; ids are given in their new order.
+    Reorder {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// List the days that have todos.
+    Dates {
+        /// Print JSON instead of plain lines.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show or change settings; the app reads them when it starts.
+    Config {
+        /// Setting to show or change; lists every setting when omitted.
+        key: Option<String>,
+        /// New value for the setting.
+        value: Option<String>,
+        /// Put the setting back to its default.
+        #[arg(long, requires = "key", conflicts_with = "value")]
+        reset: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TagsCommand {
+    /// List tags with how many todos carry them (the default).
+    List {
+        /// Print JSON instead of plain lines.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rename a tag.
+    Rename { name: String, new_name: String },
+    /// Move the todos of several tags onto one and delete the others.
+    Merge {
+        #[arg(required = true)]
+        sources: Vec<String>,
+        /// Tag to keep; created if it does not exist.
+        #[arg(long)]
+        into: String,
+    },
+    /// Set a tag's color (#RRGGBB).
+    Color {
+        name: String,
+        #[arg(required_unless_present = "clear")]
+        color: Option<String>,
+        /// Go back to the default color.
+        #[arg(long, conflicts_with = "color")]
+        clear: bool,
+    },
+    /// Delete a tag, taking it off every todo.
+    Rm { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum ProjectsCommand {
+    /// List projects with their active and completed counts (the default).
+    List {
+        /// Include archived projects.
+        #[arg(long)]
+        archived: bool,
+        /// Print JSON instead of plain lines.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Create a project.
+    Add {
+        name: String,
+        /// Color as #RRGGBB.
+        #[arg(long)]
+        color: Option<String>,
+        /// Short icon, such as an emoji.
+        #[arg(long)]
+        icon: Option<String>,
+    },
+    /// Rename a project.
+    Rename { name: String, new_name: String },
+    /// Set a project's color and icon; omitted ones go back to the default.
+    Style {
+        name: String,
+        #[arg(long)]
+        color: Option<String>,
+        #[arg(long)]
+        icon: Option<String>,
+    },
+    /// Hide a project from the list without touching its todos.
+    Archive { name: String },
+    /// Bring back an archived project.
+    Unarchive { name: String },
+    /// Reorder projects; names are given in their new order.
+    Reorder {
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Delete a project, with its todos or after moving them elsewhere.
+    Rm {
+        name: String,
+        /// Delete the project's todos too.
+        #[arg(long, required_unless_present_any = ["reassign", "unassign"])]
+        cascade: bool,
+        /// Move the project's todos to this project.
+        #[arg(long, value_name = "PROJECT", conflicts_with = "cascade")]
+        reassign: Option<String>,
+        /// Keep the project's todos outside of any project.
+        #[arg(long, conflicts_with_all = ["cascade", "reassign"])]
+        unassign: bool,
+    },
+}
+
+/// Entry point of the `rtodo` binary.
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let Some(command) = cli.command else {
+        crate::run_with(cli.launch, cli.db);
+        return ExitCode::SUCCESS;
+    };
+
+    crate::init_tracing("warn");
+    match execute(cli.db, command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("rtodo: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parses the argv forwarded by a second instance. Subcommands never get
+/// here because that instance handles them itself.
+pub(crate) fn parse_launch(argv: Vec<String>) -> Result<LaunchArgs, clap::Error> {
+    Ok(Cli::try_parse_from(argv)?.launch)
+}
+
+fn execute(db: Option<PathBuf>, command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match db {
+        Some(path) => path,
+        None => crate::database_path().ok_or("could not determine the app data directory")?,
+    };
+    let store = Store::open(&path)?;
+    // The app may not have run yet today.
+    if let Err(e) = store.carry_over() {
+        warn!(error = %e, "failed to carry over unfinished todos");
+    }
+
+    match command {
+        Command::Add {
+            text,
+            literal,
+            date,
+            due,
+            remind,
+            project,
+            tags,
+            priority,
+            parent,
+            repeat,
+        } => {
+            let parsed = if literal {
+                NewTodo {
+                    text,
+                    ..NewTodo::default()
+                }
+            } else {
+                NewTodo::from(store.parse_quick_add(&text, date.as_deref())?)
+            };
+            let project_id = match project {
+                Some(name) => Some(store.project_by_name(&name)?.id),
+                None => parsed.project_id,
+            };
+            let priority = match priority {
+                Some(priority) => priority.parse()?,
+                None => parsed.priority,
+            };
+            let todo = store.create_todo(&NewTodo {
+                text: parsed.text,
+                date: date.or(parsed.date),
+                due_at: due.or(parsed.due_at),
+                remind_at: remind,
+                project_id,
+                priority,
+                parent_id: parent,
+                tags: parsed.tags.into_iter().chain(tags).collect(),
+                recurrence: repeat.or(parsed.recurrence),
+            })?;
+            println!("Added {}: {}", todo.id, todo.text);
+        }
+        Command::List {
+            project: Some(project),
+            json,
+            ..
+        } => {
+            let todos = store.project_todos(store.project_by_name(&project)?.id)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&todos)?);
+            } else {
+                print_todos(&todos, true);
+            }
+        }
+        Command::List {
+            date,
+            project: None,
+            all,
+            tags,
+            any,
+            json,
+        } => {
+            let day = match date {
+                _ if all => None,
+                Some(date) => Some(date),
+                None => Some(store.today()?),
+            };
+            if let (Some(day), true) = (&day, tags.is_empty()) {
+                let tree = store.todo_tree(day)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&tree)?);
+                } else {
+                    print_tree(&tree);
+                }
+                return Ok(());
+            }
+            let todos = if tags.is_empty() {
+                store.list_todos()?
+            } else {
+                let mode = if any { TagMatch::Any } else { TagMatch::All };
+                store.todos_by_tags(&tags, mode, day.as_deref())?
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&todos)?);
+            } else {
+                print_todos(&todos, all);
+            }
+        }
+        Command::Done { id } => {
+            let todo = store.set_completed(id, true)?;
+            println!("Completed {}: {}", todo.id, todo.text);
+        }
+        Command::Due { id, when, clear: _ } => {
+            let todo = store.set_due(id, when.as_deref())?;
+            match &todo.due_at {
+                Some(due) => println!("{} is due {}", todo.id, local_time(due)),
+                None => println!("{} has no deadline", todo.id),
+            }
+        }
+        Command::Remind { id, when, clear: _ } => {
+            let todo = store.set_reminder(id, when.as_deref())?;
+            match &todo.remind_at {
+                Some(at) => println!("{} will remind you at {}", todo.id, local_time(at)),
+                None => println!("{} has no reminder", todo.id),
+            }
+        }
+        Command::Snooze { id, minutes } => {
+            let todo = store.snooze_reminder(id, minutes)?;
+            if let Some(at) = &todo.remind_at {
+                println!("{} snoozed until {}", todo.id, local_time(at));
+            }
+        }
+        Command::Priority { id, priority } => {
+            let todo = store.set_priority(id, priority.parse()?)?;
+            println!("{} is now {} priority", todo.id, todo.priority);
+        }
+        Command::Repeat { id, rule, clear: _ } => {
+            let todo = store.set_recurrence(id, rule.as_deref())?;
+            match &todo.recurrence {
+                Some(rule) => println!("{} repeats {rule}", todo.id),
+                None => println!("{} no longer repeats", todo.id),
+            }
+        }
+        Command::Notes { id, text, clear } => {
+            if text.is_some() || clear {
+                store.set_notes(id, text.as_deref())?;
+            } else {
+                match store.get_todo(id)?.notes {
+                    Some(notes) => println!("{notes}"),
+                    None => println!("No notes."),
+                }
+            }
+        }
+        Command::Search { query, limit, json } => {
+            let hits = store.search(&query.join(" "), limit)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&hits)?);
+            } else {
+                print_hits(&hits);
+            }
+        }
+        Command::Overdue { json } => {
+            let todos = store.overdue_todos()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&todos)?);
+            } else {
+                print_todos(&todos, true);
+            }
+        }
+        Command::Tag { id, tags } => {
+            let todo = store.tag_todo(id, &tags)?;
+            print_todos(&[todo], false);
+        }
+        Command::Untag { id, tags } => {
+            let todo = store.untag_todo(id, &tags)?;
+            print_todos(&[todo], false);
+        }
+        Command::Tags { command } => match command.unwrap_or(TagsCommand::List { json: false }) {
+            TagsCommand::List { json } => {
+                let tags = store.tags()?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&tags)?);
+                } else {
+                    print_tags(&tags);
+                }
+            }
+            TagsCommand::Rename { name, new_name } => {
+                let tag = store.rename_tag(&name, &new_name)?;
+                println!("Renamed {name} to #{}", tag.name);
+            }
+            TagsCommand::Merge { sources, into } => {
+                let tag = store.merge_tags(&sources, &into)?;
+                println!("Merged into #{}", tag.name);
+            }
+            TagsCommand::Color {
+                name,
+                color,
+                clear: _,
+            } => {
+                let tag = store.set_tag_color(&name, color.as_deref())?;
+                let color = tag.color.as_deref().unwrap_or("default");
+                println!("#{} is now {color}", tag.name);
+            }
+            TagsCommand::Rm { name } => {
+                if !store.delete_tag(&name)? {
+                    return Err(crate::store::Error::TagNotFound(name).into());
+                }
+                println!("Deleted tag {name}");
+            }
+        },
+        Command::Move {
+            ids,
+            project,
+            none: _,
+        } => {
+            let project = match project {
+                Some(name) => Some(store.project_by_name(&name)?),
+                None => None,
+            };
+            let todos = store.move_todos_to_project(&ids, project.as_ref().map(|p| p.id))?;
+            match project {
+                Some(project) => println!("Moved {} todo(s) to {}", todos.len(), project.name),
+                None => println!("Moved {} todo(s) out of their project", todos.len()),
+            }
+        }
+        Command::Projects { command } => match command.unwrap_or(ProjectsCommand::List {
+            archived: false,
+            json: false,
+        }) {
+            ProjectsCommand::List { archived, json } => {
+                let projects = store.projects(archived)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&projects)?);
+                } else {
+                    print_projects(&projects);
+                }
+            }
+            ProjectsCommand::Add { name, color, icon } => {
+                let project = store.create_project(&name, color.as_deref(), icon.as_deref())?;
+                println!("Added project {}: {}", project.id, project.name);
+            }
+            ProjectsCommand::Rename { name, new_name } => {
+                let project = store.rename_project(store.project_by_name(&name)?.id, &new_name)?;
+                println!("Renamed {name} to {}", project.name);
+            }
+            ProjectsCommand::Style { name, color, icon } => {
+                let id = store.project_by_name(&name)?.id;
+                let project = store.set_project_style(id, color.as_deref(), icon.as_deref())?;
+                let color = project.color.as_deref().unwrap_or("default color");
+                let icon = project.icon.as_deref().unwrap_or("no icon");
+                println!("{} now has {color} and {icon}", project.name);
+            }
+            ProjectsCommand::Archive { name } => {
+                let project = store.archive_project(store.project_by_name(&name)?.id, true)?;
+                println!("Archived {}", project.name);
+            }
+            ProjectsCommand::Unarchive { name } => {
+                let project = store.archive_project(store.project_by_name(&name)?.id, false)?;
+                println!("Unarchived {}", project.name);
+            }
+            ProjectsCommand::Reorder { names } => {
+                let ids = names
+                    .iter()
+                    .map(|name| Ok(store.project_by_name(name)?.id))
+                    .collect::<crate::store::Result<Vec<_>>>()?;
+                store.reorder_projects(&ids)?;
+            }
+            ProjectsCommand::Rm {
+                name,
+                cascade,
+                reassign,
+                unassign: _,
+            } => {
+                let id = store.project_by_name(&name)?.id;
+                let todos = match reassign {
+                    _ if cascade => ProjectDeletion::Cascade,
+                    Some(target) => {
+                        ProjectDeletion::Reassign(Some(store.project_by_name(&target)?.id))
+                    }
+                    None => ProjectDeletion::Reassign(None),
+                };
+                let count = store.delete_project(id, todos)?;
+                let what = if cascade { "deleted" } else { "kept" };
+                println!("Deleted project {name}; {count} todo(s) {what}");
+            }
+        },
+        Command::Nest {
+            id,
+            under,
+            top: _,
+            at,
+        } => {
+            let todo = store.move_todo(id, under, at)?;
+            match todo.parent_id {
+                Some(parent) => println!("{} is now a subtask of {parent}", todo.id),
+                None => println!("{} is now a top-level todo", todo.id),
+            }
+        }
+        Command::Attach { id, files } => {
+            for file in files {
+                let attachment = store.attach_file(id, &file)?;
+                println!("Attached {} as {}", attachment.name, attachment.id);
+            }
+        }
+        Command::Attachments { id, json } => {
+            let attachments = store.attachments(id)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&attachments)?);
+            } else {
+                print_attachments(&attachments);
+            }
+        }
+        Command::Detach { attachment } => match store.remove_attachment(attachment)? {
+            Some(removed) => println!("Removed {} from {}", removed.name, removed.todo_id),
+            None => return Err(crate::store::Error::AttachmentNotFound(attachment).into()),
+        },
+        Command::Backup { dir } => {
+            store.backup(&dir)?;
+            println!("Backed up to {}", dir.display());
+        }
+        Command::Export { format, output } => {
+            let text = match format.as_str() {
+                "todo.txt" => store.export_todo_txt()?,
+                "ics" => store.export_ical()?,
+                _ => store.export_json()?.to_json() + "\n",
+            };
+            match output {
+                Some(path) => fs::write(path, text)?,
+                None => print!("{text}"),
+            }
+        }
+        Command::Import {
+            file,
+            format,
+            strategy,
+        } => {
+            let strategy = strategy.parse::<ImportStrategy>()?;
+            let input = read_input(&file)?;
+            let report = match format.as_str() {
+                "todo.txt" => store.import_todo_txt(&input, strategy)?,
+                "ics" => store.import_ical(&input, strategy)?,
+                _ => store.import_json(&Document::parse(&input)?, strategy)?,
+            };
+            println!("Imported: {report}");
+        }
+        Command::Report {
+            from,
+            to,
+            format,
+            output,
+        } => {
+            let to = match to {
+                Some(to) => to,
+                None => store.today()?,
+            };
+            let from = match from {
+                Some(from) => from,
+                None => (parse_date(&to)? - Days::new(6)).to_string(),
+            };
+            let report = store.report(&from, &to, format.parse::<ReportFormat>()?)?;
+            match output {
+                Some(path) => fs::write(path, report)?,
+                None => print!("{report}"),
+            }
+        }
+        Command::Rm { id } => {
+            if !store.delete_todo(id)? {
+                return Err(crate::store::Error::NotFound(id).into());
+            }
+            println!("Deleted {id}");
+        }
+        Command::Reorder { ids } => store.reorder_todos(&ids)?,
+        Command::Dates { json } => {
+            let past = store.historical_dates()?;
+            let upcoming = store.future_dates()?;
+            if json {
+                let dates = serde_json::json!({ "historical": past, "future": upcoming });
+                println!("{}", serde_json::to_string_pretty(&dates)?);
+            } else {
+                for day in upcoming.iter().rev().chain(&past) {
+                    println!("{day}");
+                }
+            }
+        }
+        Command::Config { key, value, reset } => {
+            let Some(key) = key else {
+                for (key, default) in SETTINGS {
+                    let value = store.setting(key)?;
+                    println!("{key} = {}", value.as_deref().unwrap_or(default));
+                }
+                return Ok(());
+            };
+            let Some(&(key, default)) = SETTINGS.iter().find(|(name, _)| *name == key) else {
+                return Err(format!("unknown setting {key:?}").into());
+            };
+            if reset {
+                store.reset_setting(key)?;
+                println!("{key} = {default}");
+            } else if let Some(value) = value {
+                match key {
+                    carry_over::SETTING => {
+                        value.parse::<carry_over::Mode>()?;
+                    }
+                    days::START_HOUR_SETTING => {
+                        days::parse_start_hour(&value)?;
+                    }
+                    priority::SORT_SETTING => {
+                        value.parse::<priority::SortMode>()?;
+                    }
+                    subtasks::COMPLETION_SETTING => {
+                        value.parse::<subtasks::Completion>()?;
+                    }
+                    shortcut::SETTING => {
+                        shortcut::parse(&value)?;
+                    }
+                    feed::SETTING => {
+                        feed::parse(&value)?;
+                    }
+                    _ => {}
+                }
+                store.set_setting(key, value.trim())?;
+                println!("{key} = {}", value.trim());
+            } else {
+                println!(
+                    "{key} = {}",
+                    store.setting(key)?.as_deref().unwrap_or(default)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_todos(todos: &[Todo], with_day: bool) {
+    let rows: Vec<_> = todos.iter().map(|todo| (0, todo)).collect();
+    print_rows(&rows, with_day);
+}
+
+/// Prints todos with their subtasks indented below them.
+fn print_tree(nodes: &[TodoNode]) {
+    fn flatten<'a>(nodes: &'a [TodoNode], depth: usize, rows: &mut Vec<(usize, &'a Todo)>) {
+        for node in nodes {
+            rows.push((depth, &node.todo));
+            flatten(&node.children, depth + 1, rows);
+        }
+    }
+    let mut rows = Vec::new();
+    flatten(nodes, 0, &mut rows);
+    print_rows(&rows, false);
+}
+
+/// Prints one line per todo, indented by its depth.
+fn print_rows(rows: &[(usize, &Todo)], with_day: bool) {
+    if rows.is_empty() {
+        println!("Nothing to do.");
+        return;
+    }
+    let width = rows
+        .iter()
+        .map(|(_, t)| t.id.to_string().len())
+        .max()
+        .unwrap_or(1);
+    for &(depth, todo) in rows {
+        let indent = "  ".repeat(depth);
+        let mark = if todo.completed { 'x' } else { ' ' };
+        let day = if with_day { todo.day() } else { "" };
+        let sep = if with_day { "  " } else { "" };
+        let due = match &todo.due_at {
+            Some(due) => format!("  (due {})", local_time(due)),
+            None => String::new(),
+        };
+        let remind = match &todo.remind_at {
+            Some(at) => format!("  (remind {})", local_time(at)),
+            None => String::new(),
+        };
+        let tags: String = todo.tags.iter().map(|t| format!(" #{}", t.name)).collect();
+        let priority = match todo.priority {
+            Priority::None => String::new(),
+            priority => format!(" !{priority}"),
+        };
+        let progress = match &todo.progress {
+            Some(p) => format!(" [{}/{}]", p.completed, p.total),
+            None => String::new(),
+        };
+        let repeats = if todo.recurrence.is_some() {
+            " ↻"
+        } else {
+            ""
+        };
+        println!(
+            "{indent}[{mark}] {:>width$}  {day}{sep}{}{repeats}{progress}{priority}{tags}{due}{remind}",
+            todo.id, todo.text
+        );
+    }
+}
+
+/// Prints search results with the matches in bold when writing to a
+/// terminal.
+fn print_hits(hits: &[SearchHit]) {
+    if hits.is_empty() {
+        println!("No matches.");
+        return;
+    }
+    let (open, close) = if io::stdout().is_terminal() {
+        ("\x1b[1m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    let width = hits
+        .iter()
+        .map(|h| h.todo.id.to_string().len())
+        .max()
+        .unwrap_or(1);
+    for hit in hits {
+        let mark = if hit.todo.completed { 'x' } else { ' ' };
+        println!(
+            "[{mark}] {:>width$}  {}  {}",
+            hit.todo.id,
+            hit.todo.day(),
+            hit.title.marked(open, close)
+        );
+        if let Some(snippet) = &hit.snippet {
+            let indent = " ".repeat(width + 18);
+            for line in snippet.marked(open, close).lines() {
+                println!("{indent}{line}");
+            }
+        }
+    }
+}
+
+fn print_tags(tags: &[TagUsage]) {
+    if tags.is_empty() {
+        println!("No tags.");
+        return;
+    }
+    let width = tags.iter().map(|t| t.tag.name.len()).max().unwrap_or(1);
+    for usage in tags {
+        let color = match &usage.tag.color {
+            Some(color) => format!("  {color}"),
+            None => String::new(),
+        };
+        println!(
+            "#{:<width$}  {:>4}{color}",
+            usage.tag.name, usage.todo_count
+        );
+    }
+}
+
+fn print_attachments(attachments: &[Attachment]) {
+    if attachments.is_empty() {
+        println!("No attachments.");
+        return;
+    }
+    let width = attachments
+        .iter()
+        .map(|a| a.id.to_string().len())
+        .max()
+        .unwrap_or(1);
+    for attachment in attachments {
+        println!(
+            "{:>width$}  {}  ({} bytes)",
+            attachment.id, attachment.name, attachment.size
+        );
+    }
+}
+
+fn print_projects(projects: &[ProjectSummary]) {
+    if projects.is_empty() {
+        println!("No projects.");
+        return;
+    }
+    let width = projects
+        .iter()
+        .map(|p| p.project.name.chars().count())
+        .max()
+        .unwrap_or(1);
+    for summary in projects {
+        let project = &summary.project;
+        let icon = project.icon.as_deref().unwrap_or(" ");
+        let mut extra = String::new();
+        if let Some(color) = &project.color {
+            extra.push_str(&format!("  {color}"));
+        }
+        if project.archived {
+            extra.push_str("  (archived)");
+        }
+        println!(
+            "{icon} {:<width$}  {:>4} active  {:>4} done{extra}",
+            project.name, summary.active_count, summary.completed_count
+        );
+    }
+}
+
+/// Formats a stored UTC timestamp in local time for display.
+fn local_time(timestamp: &str) -> String {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(at) => at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        Err(_) => timestamp.to_owned(),
+    }
+}
+
+/// Contents of the file at `path`, or of standard input for `-`.
+fn read_input(path: &Path) -> io::Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
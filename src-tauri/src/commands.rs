@@ -0,0 +1,841 @@
+//! Tauri commands exposing the [`Store`] to the webview.
+//!
+//! Every command runs its database work on the blocking pool and emits the
+//! matching event from `events` once the change is committed.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Serialize, Serializer};
+use tauri::{
+    async_runtime, AppHandle, DragDropEvent, Manager, Runtime, State, Window, WindowEvent,
+};
+use tauri_plugin_opener::OpenerExt;
+use tracing::instrument;
+
+use crate::store::projects::ProjectSummary;
+use crate::store::tags::TagUsage;
+use crate::store::{
+    self, notes, search, Attachment, Document, FirstTodo, ImportReport, ImportStrategy, NewTodo,
+    Priority, Project, ProjectDeletion, QuickAdd, RenderedNotes, ReportFormat, SearchHit, SortMode,
+    Store, Tag, TagMatch, Todo, TodoNode, TodoUpdate,
+};
+use crate::{events, shortcut, windows};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Store(#[from] store::Error),
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+    #[error(transparent)]
+    Shortcut(#[from] shortcut::Error),
+    #[error(transparent)]
+    Opener(#[from] tauri_plugin_opener::Error),
+    #[error("`{0}` is not a link in the todo's notes")]
+    UnknownLink(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+type CommandResult<T> = Result<T, Error>;
+
+/// Runs `f` against the store without blocking the async executor.
+async fn blocking<T, F>(store: &Store, f: F) -> CommandResult<T>
+where
+    F: FnOnce(&Store) -> store::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let store = store.clone();
+    Ok(async_runtime::spawn_blocking(move || f(&store)).await??)
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn list_todos(store: State<'_, Store>) -> CommandResult<Vec<Todo>> {
+    blocking(&store, |s| s.list_todos()).await
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn todos_by_date(store: State<'_, Store>, date: String) -> CommandResult<Vec<Todo>> {
+    blocking(&store, move |s| s.todos_by_date(&date)).await
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn first_todo(store: State<'_, Store>) -> CommandResult<FirstTodo> {
+    blocking(&store, |s| s.first_todo()).await
+}
+
+#[tauri::command]
+#[instrument(skip(app, store, text))]
+pub async fn add_todo(
+    app: AppHandle,
+    store: State<'_, Store>,
+    text: String,
+    date: Option<String>,
+    due_at: Option<String>,
+    project_id: Option<i64>,
+    priority: Option<Priority>,
+    parent_id: Option<i64>,
+    recurrence: Option<String>,
+    remind_at: Option<String>,
+) -> CommandResult<Todo> {
+    let new = NewTodo {
+        text,
+        date,
+        due_at,
+        remind_at,
+        project_id,
+        priority: priority.unwrap_or_default(),
+        parent_id,
+        recurrence,
+        ..NewTodo::default()
+    };
+    let todo = blocking(&store, move |s| s.create_todo(&new)).await?;
+    events::todo_added(&app, &todo);
+    related_changed(&app, &todo);
+    if todo.project_id.is_some() {
+        events::projects_changed(&app);
+    }
+    Ok(todo)
+}
+
+/// Adds a todo from natural-language input such as "Pay rent tomorrow 9am
+/// #home !high"; `date` is where it goes when the text names no day.
+#[tauri::command]
+#[instrument(skip(app, store, text))]
+pub async fn quick_add(
+    app: AppHandle,
+    store: State<'_, Store>,
+    text: String,
+    date: Option<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.quick_add(&text, date.as_deref())).await?;
+    events::todo_added(&app, &todo);
+    if todo.project_id.is_some() {
+        events::projects_changed(&app);
+    }
+    Ok(todo)
+}
+
+/// What [`quick_add`] would make of `text`, for highlighting it while it
+/// is typed.
+#[tauri::command]
+#[instrument(skip(store, text))]
+pub async fn parse_quick_add(
+    store: State<'_, Store>,
+    text: String,
+    date: Option<String>,
+) -> CommandResult<QuickAdd> {
+    blocking(&store, move |s| s.parse_quick_add(&text, date.as_deref())).await
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn toggle_todo(app: AppHandle, store: State<'_, Store>, id: i64) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.toggle_todo(id)).await?;
+    events::todo_updated(&app, &todo, "toggled");
+    related_changed(&app, &todo);
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store, text))]
+pub async fn update_todo(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    text: Option<String>,
+    completed: Option<bool>,
+) -> CommandResult<Todo> {
+    let update = TodoUpdate { text, completed };
+    let todo = blocking(&store, move |s| s.update_todo(id, update)).await?;
+    events::todo_updated(&app, &todo, "updated");
+    related_changed(&app, &todo);
+    Ok(todo)
+}
+
+/// Completing a todo may have completed its subtasks too or brought back a
+/// repeating todo, and a subtask changes its parent's rollup, none of which
+/// `todo-updated` carries.
+fn related_changed(app: &AppHandle, todo: &Todo) {
+    if todo.parent_id.is_some()
+        || (todo.completed && (todo.progress.is_some() || todo.recurrence.is_some()))
+    {
+        events::todos_changed(app);
+    }
+}
+
+/// Top-level todos of `date` with their subtasks nested in `children`.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn todo_tree(store: State<'_, Store>, date: String) -> CommandResult<Vec<TodoNode>> {
+    blocking(&store, move |s| s.todo_tree(&date)).await
+}
+
+/// Moves a todo with its subtasks under `parent_id`, or to the top level of
+/// its day when `None`, at `position` among its new siblings.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn move_todo(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    parent_id: Option<i64>,
+    position: Option<usize>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.move_todo(id, parent_id, position)).await?;
+    events::todos_changed(&app);
+    Ok(todo)
+}
+
+/// `due_at` of `None` clears the deadline.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_todo_due(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    due_at: Option<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.set_due(id, due_at.as_deref())).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+/// `remind_at` of `None` removes the reminder.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_todo_reminder(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    remind_at: Option<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.set_reminder(id, remind_at.as_deref())).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn snooze_reminder(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    minutes: u32,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.snooze_reminder(id, minutes)).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_todo_priority(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    priority: Priority,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.set_priority(id, priority)).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+/// Makes a todo repeat by `rule`, in RRULE syntax or a phrase such as
+/// "every weekday", or stops it repeating when `None`.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_todo_recurrence(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    rule: Option<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.set_recurrence(id, rule.as_deref())).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store, notes))]
+pub async fn set_todo_notes(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    notes: Option<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.set_notes(id, notes.as_deref())).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn render_notes(store: State<'_, Store>, id: i64) -> CommandResult<RenderedNotes> {
+    blocking(&store, move |s| s.render_notes(id)).await
+}
+
+/// Opens a link from a todo's notes in the default browser or mail client.
+/// Only links the notes actually contain are opened.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn open_link(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    url: String,
+) -> CommandResult<()> {
+    let todo = blocking(&store, move |s| s.get_todo(id)).await?;
+    if !notes::links_to(todo.notes.as_deref().unwrap_or_default(), &url) {
+        return Err(Error::UnknownLink(url));
+    }
+    app.opener().open_url(url, None::<&str>)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn list_attachments(
+    store: State<'_, Store>,
+    todo_id: i64,
+) -> CommandResult<Vec<Attachment>> {
+    blocking(&store, move |s| s.attachments(todo_id)).await
+}
+
+/// Files last dropped onto the main window. They are kept here rather than
+/// handed to the webview, so it can only attach files the user dropped.
+#[derive(Default)]
+pub struct DroppedFiles(Mutex<Vec<PathBuf>>);
+
+/// Remembers the files dropped onto the main window and tells it where they
+/// landed.
+pub fn remember_dropped_files<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    let WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }) = event else {
+        return;
+    };
+    if window.label() != windows::MAIN {
+        return;
+    }
+    *window
+        .state::<DroppedFiles>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = paths.clone();
+    events::files_dropped(window.app_handle(), *position);
+}
+
+/// Copies the files last dropped onto the main window into the app's
+/// attachment folder. Each drop can be attached once.
+#[tauri::command]
+#[instrument(skip(app, store, dropped))]
+pub async fn attach_dropped_files(
+    app: AppHandle,
+    store: State<'_, Store>,
+    dropped: State<'_, DroppedFiles>,
+    todo_id: i64,
+) -> CommandResult<Vec<Attachment>> {
+    let paths = std::mem::take(&mut *dropped.0.lock().unwrap_or_else(|e| e.into_inner()));
+    let attached = blocking(&store, move |s| {
+        paths
+            .iter()
+            .map(|path| s.attach_file(todo_id, path))
+            .collect::<store::Result<Vec<_>>>()
+    })
+    .await;
+    // Files copied before a failure stay attached.
+    events::attachments_changed(&app, todo_id);
+    attached
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn open_attachment(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+) -> CommandResult<()> {
+    let path = blocking(&store, move |s| s.attachment_path(id)).await?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn remove_attachment(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+) -> CommandResult<bool> {
+    let removed = blocking(&store, move |s| s.remove_attachment(id)).await?;
+    if let Some(attachment) = &removed {
+        events::attachments_changed(&app, attachment.todo_id);
+    }
+    Ok(removed.is_some())
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn overdue_todos(store: State<'_, Store>) -> CommandResult<Vec<Todo>> {
+    blocking(&store, |s| s.overdue_todos()).await
+}
+
+/// Todos of every day matching `query`, best match first, with the matches
+/// marked in HTML; see [`Store::search`] for the query syntax.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn search_todos(
+    store: State<'_, Store>,
+    query: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    let limit = limit.unwrap_or(search::DEFAULT_LIMIT);
+    blocking(&store, move |s| s.search(&query, limit)).await
+}
+
+/// Every project, tag, todo and attachment as a JSON export document.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn export_json(store: State<'_, Store>) -> CommandResult<String> {
+    blocking(&store, |s| Ok(s.export_json()?.to_json())).await
+}
+
+/// Imports a document written by [`export_json`]; `strategy` is replace,
+/// merge or append.
+#[tauri::command]
+#[instrument(skip(app, store, json))]
+pub async fn import_json(
+    app: AppHandle,
+    store: State<'_, Store>,
+    json: String,
+    strategy: String,
+) -> CommandResult<ImportReport> {
+    let strategy = strategy.parse::<ImportStrategy>()?;
+    let report = blocking(&store, move |s| {
+        s.import_json(&Document::parse(&json)?, strategy)
+    })
+    .await?;
+    events::todos_changed(&app);
+    events::projects_changed(&app);
+    events::tags_changed(&app);
+    Ok(report)
+}
+
+/// Every todo as todo.txt lines.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn export_todo_txt(store: State<'_, Store>) -> CommandResult<String> {
+    blocking(&store, |s| s.export_todo_txt()).await
+}
+
+/// Imports the todos of a todo.txt file; `strategy` is replace, merge or
+/// append, and merge adds every todo since lines carry no uid.
+#[tauri::command]
+#[instrument(skip(app, store, text))]
+pub async fn import_todo_txt(
+    app: AppHandle,
+    store: State<'_, Store>,
+    text: String,
+    strategy: String,
+) -> CommandResult<ImportReport> {
+    let strategy = strategy.parse::<ImportStrategy>()?;
+    let report = blocking(&store, move |s| s.import_todo_txt(&text, strategy)).await?;
+    events::todos_changed(&app);
+    events::projects_changed(&app);
+    events::tags_changed(&app);
+    Ok(report)
+}
+
+/// Every todo as an iCalendar document of VTODOs.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn export_ical(store: State<'_, Store>) -> CommandResult<String> {
+    blocking(&store, |s| s.export_ical()).await
+}
+
+/// Imports the VTODOs of an iCalendar file; `strategy` is replace, merge or
+/// append, and merge matches todos by UID.
+#[tauri::command]
+#[instrument(skip(app, store, text))]
+pub async fn import_ical(
+    app: AppHandle,
+    store: State<'_, Store>,
+    text: String,
+    strategy: String,
+) -> CommandResult<ImportReport> {
+    let strategy = strategy.parse::<ImportStrategy>()?;
+    let report = blocking(&store, move |s| s.import_ical(&text, strategy)).await?;
+    events::todos_changed(&app);
+    events::tags_changed(&app);
+    Ok(report)
+}
+
+/// The todos from `from` to `to`, both included, as a `csv` or `markdown`
+/// report.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn report(
+    store: State<'_, Store>,
+    from: String,
+    to: String,
+    format: String,
+) -> CommandResult<String> {
+    let format = format.parse::<ReportFormat>()?;
+    blocking(&store, move |s| s.report(&from, &to, format)).await
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn list_tags(store: State<'_, Store>) -> CommandResult<Vec<TagUsage>> {
+    blocking(&store, |s| s.tags()).await
+}
+
+/// Adds `tags` to a todo, creating any that do not exist yet.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn tag_todo(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    tags: Vec<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.tag_todo(id, &tags)).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn untag_todo(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    tags: Vec<String>,
+) -> CommandResult<Todo> {
+    let todo = blocking(&store, move |s| s.untag_todo(id, &tags)).await?;
+    events::todo_updated(&app, &todo, "updated");
+    Ok(todo)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn rename_tag(
+    app: AppHandle,
+    store: State<'_, Store>,
+    name: String,
+    new_name: String,
+) -> CommandResult<Tag> {
+    let tag = blocking(&store, move |s| s.rename_tag(&name, &new_name)).await?;
+    events::tags_changed(&app);
+    Ok(tag)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn merge_tags(
+    app: AppHandle,
+    store: State<'_, Store>,
+    sources: Vec<String>,
+    into: String,
+) -> CommandResult<Tag> {
+    let tag = blocking(&store, move |s| s.merge_tags(&sources, &into)).await?;
+    events::tags_changed(&app);
+    Ok(tag)
+}
+
+/// `color` of `None` goes back to the default color.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_tag_color(
+    app: AppHandle,
+    store: State<'_, Store>,
+    name: String,
+    color: Option<String>,
+) -> CommandResult<Tag> {
+    let tag = blocking(&store, move |s| s.set_tag_color(&name, color.as_deref())).await?;
+    events::tags_changed(&app);
+    Ok(tag)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn delete_tag(
+    app: AppHandle,
+    store: State<'_, Store>,
+    name: String,
+) -> CommandResult<bool> {
+    let removed = blocking(&store, move |s| s.delete_tag(&name)).await?;
+    if removed {
+        events::tags_changed(&app);
+    }
+    Ok(removed)
+}
+
+/// Todos carrying all (the default) or any of `tags`, optionally on one day.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn todos_by_tags(
+    store: State<'_, Store>,
+    tags: Vec<String>,
+    mode: Option<TagMatch>,
+    date: Option<String>,
+) -> CommandResult<Vec<Todo>> {
+    blocking(&store, move |s| {
+        s.todos_by_tags(&tags, mode.unwrap_or_default(), date.as_deref())
+    })
+    .await
+}
+
+/// Projects with their active and completed counts; archived ones only
+/// when `include_archived` is set.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn list_projects(
+    store: State<'_, Store>,
+    include_archived: Option<bool>,
+) -> CommandResult<Vec<ProjectSummary>> {
+    blocking(&store, move |s| {
+        s.projects(include_archived.unwrap_or(false))
+    })
+    .await
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn create_project(
+    app: AppHandle,
+    store: State<'_, Store>,
+    name: String,
+    color: Option<String>,
+    icon: Option<String>,
+) -> CommandResult<Project> {
+    let project = blocking(&store, move |s| {
+        s.create_project(&name, color.as_deref(), icon.as_deref())
+    })
+    .await?;
+    events::projects_changed(&app);
+    Ok(project)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn rename_project(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    name: String,
+) -> CommandResult<Project> {
+    let project = blocking(&store, move |s| s.rename_project(id, &name)).await?;
+    events::projects_changed(&app);
+    Ok(project)
+}
+
+/// A `color` or `icon` of `None` goes back to the default.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_project_style(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    color: Option<String>,
+    icon: Option<String>,
+) -> CommandResult<Project> {
+    let project = blocking(&store, move |s| {
+        s.set_project_style(id, color.as_deref(), icon.as_deref())
+    })
+    .await?;
+    events::projects_changed(&app);
+    Ok(project)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn archive_project(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    archived: bool,
+) -> CommandResult<Project> {
+    let project = blocking(&store, move |s| s.archive_project(id, archived)).await?;
+    events::projects_changed(&app);
+    Ok(project)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn reorder_projects(
+    app: AppHandle,
+    store: State<'_, Store>,
+    project_ids: Vec<i64>,
+) -> CommandResult<()> {
+    blocking(&store, move |s| s.reorder_projects(&project_ids)).await?;
+    events::projects_changed(&app);
+    Ok(())
+}
+
+/// Deletes a project together with its todos when `cascade` is set, and
+/// otherwise moves them to `reassign_to` (or out of any project). Returns
+/// how many todos were deleted or moved.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn delete_project(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    cascade: bool,
+    reassign_to: Option<i64>,
+) -> CommandResult<usize> {
+    let todos = if cascade {
+        ProjectDeletion::Cascade
+    } else {
+        ProjectDeletion::Reassign(reassign_to)
+    };
+    let count = blocking(&store, move |s| s.delete_project(id, todos)).await?;
+    events::projects_changed(&app);
+    if count > 0 {
+        events::todos_changed(&app);
+    }
+    Ok(count)
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn project_todos(store: State<'_, Store>, id: i64) -> CommandResult<Vec<Todo>> {
+    blocking(&store, move |s| s.project_todos(id)).await
+}
+
+/// Reorders a project's todos independently of their days' order.
+#[tauri::command]
+#[instrument(skip(app, store, todo_ids))]
+pub async fn reorder_project_todos(
+    app: AppHandle,
+    store: State<'_, Store>,
+    id: i64,
+    todo_ids: Vec<i64>,
+) -> CommandResult<()> {
+    blocking(&store, move |s| s.reorder_project_todos(id, &todo_ids)).await?;
+    events::projects_changed(&app);
+    Ok(())
+}
+
+/// Moves todos to the end of a project, or out of any project when
+/// `project_id` is `None`.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn move_todos_to_project(
+    app: AppHandle,
+    store: State<'_, Store>,
+    todo_ids: Vec<i64>,
+    project_id: Option<i64>,
+) -> CommandResult<Vec<Todo>> {
+    let todos = blocking(&store, move |s| {
+        s.move_todos_to_project(&todo_ids, project_id)
+    })
+    .await?;
+    for todo in &todos {
+        events::todo_updated(&app, todo, "moved");
+    }
+    events::projects_changed(&app);
+    Ok(todos)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn delete_todo(app: AppHandle, store: State<'_, Store>, id: i64) -> CommandResult<bool> {
+    let removed = blocking(&store, move |s| s.delete_todo(id)).await?;
+    if removed {
+        events::todo_deleted(&app, id);
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn clear_completed(app: AppHandle, store: State<'_, Store>) -> CommandResult<usize> {
+    let count = blocking(&store, |s| s.clear_completed()).await?;
+    if count > 0 {
+        events::todos_cleared(&app, count);
+    }
+    Ok(count)
+}
+
+#[tauri::command]
+#[instrument(skip(app, store, todo_ids))]
+pub async fn reorder_todos(
+    app: AppHandle,
+    store: State<'_, Store>,
+    todo_ids: Vec<i64>,
+) -> CommandResult<()> {
+    let ids = todo_ids.clone();
+    blocking(&store, move |s| s.reorder_todos(&ids)).await?;
+    events::todos_reordered(&app, &todo_ids);
+    Ok(())
+}
+
+/// The logical day the webview should treat as today.
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn today(store: State<'_, Store>) -> CommandResult<String> {
+    blocking(&store, |s| s.today()).await
+}
+
+/// "manual" or "priority".
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn sort_mode(store: State<'_, Store>) -> CommandResult<String> {
+    let mode = blocking(&store, |s| s.sort_mode()).await?;
+    Ok(mode.to_string())
+}
+
+/// Every list changes order, so windows reload.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_sort_mode(
+    app: AppHandle,
+    store: State<'_, Store>,
+    mode: String,
+) -> CommandResult<()> {
+    blocking(&store, move |s| s.set_sort_mode(mode.parse::<SortMode>()?)).await?;
+    events::todos_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn historical_dates(store: State<'_, Store>) -> CommandResult<Vec<String>> {
+    blocking(&store, |s| s.historical_dates()).await
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn future_dates(store: State<'_, Store>) -> CommandResult<Vec<String>> {
+    blocking(&store, |s| s.future_dates()).await
+}
+
+#[tauri::command]
+#[instrument(skip(store))]
+pub async fn quick_add_shortcut(store: State<'_, Store>) -> CommandResult<String> {
+    let store = store.inner().clone();
+    Ok(async_runtime::spawn_blocking(move || shortcut::configured(&store)).await?)
+}
+
+/// Registering waits on the main thread, so this must stay async.
+#[tauri::command]
+#[instrument(skip(app, store))]
+pub async fn set_quick_add_shortcut(
+    app: AppHandle,
+    store: State<'_, Store>,
+    accelerator: String,
+) -> CommandResult<()> {
+    let store = store.inner().clone();
+    async_runtime::spawn_blocking(move || shortcut::set(&app, &store, &accelerator)).await??;
+    Ok(())
+}
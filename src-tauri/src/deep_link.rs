@@ -0,0 +1,179 @@
+//! Parsing of `rtodo://` URLs.
+//!
+//! Supported forms:
+//!
+//! - `rtodo://add?text=Buy%20milk&date=2026-10-21` adds a todo (`date` is
+//!   optional and defaults to today; `text` is read like quick-add input,
+//!   so it may name its own day, tags and so on)
+//! - `rtodo://open?date=2026-10-15` opens the main window on a day (today
+//!   when `date` is omitted)
+//! - `rtodo://todo/42` opens the day that todo 42 belongs to
+//!
+//! Carrying the links out is left to `actions`, which also handles the
+//! equivalent command-line flags.
+
+use url::Url;
+
+use crate::store;
+
+pub const SCHEME: &str = "rtodo";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("unsupported scheme `{0}`")]
+    Scheme(String),
+    #[error("unknown action `{0}`")]
+    UnknownAction(String),
+    #[error("missing `{0}` parameter")]
+    MissingParam(&'static str),
+    #[error("invalid todo id `{0}`")]
+    InvalidId(String),
+    #[error(transparent)]
+    Store(#[from] store::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    Add { text: String, date: Option<String> },
+    Open { date: Option<String> },
+    Todo(i64),
+}
+
+impl DeepLink {
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let url = Url::parse(input)?;
+        if url.scheme() != SCHEME {
+            return Err(Error::Scheme(url.scheme().to_owned()));
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+        };
+        let date = param("date");
+        if let Some(date) = &date {
+            store::parse_date(date)?;
+        }
+
+        // `rtodo://add?..` puts the action in the host position.
+        let action = url.host_str().unwrap_or_default();
+        match action {
+            "add" => {
+                let text = param("text").ok_or(Error::MissingParam("text"))?;
+                Ok(Self::Add { text, date })
+            }
+            "open" => Ok(Self::Open { date }),
+            "todo" => {
+                let id = url.path().trim_matches('/');
+                id.parse()
+                    .map(Self::Todo)
+                    .map_err(|_| Error::InvalidId(id.to_owned()))
+            }
+            other => Err(Error::UnknownAction(other.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> DeepLink {
+        DeepLink::parse(input).unwrap()
+    }
+
+    #[test]
+    fn parses_every_form() {
+        assert_eq!(
+            parse("rtodo://add?text=Buy%20milk&date=2026-10-21"),
+            DeepLink::Add {
+                text: "Buy milk".to_owned(),
+                date: Some("2026-10-21".to_owned())
+            }
+        );
+        assert_eq!(
+            parse("rtodo://open?date=2026-10-15"),
+            DeepLink::Open {
+                date: Some("2026-10-15".to_owned())
+            }
+        );
+        assert_eq!(parse("rtodo://open"), DeepLink::Open { date: None });
+        assert_eq!(parse("rtodo://open/?date="), DeepLink::Open { date: None });
+        assert_eq!(parse("rtodo://todo/42"), DeepLink::Todo(42));
+        assert_eq!(parse("rtodo://todo/42/"), DeepLink::Todo(42));
+        assert_eq!(parse("RTODO://todo/7"), DeepLink::Todo(7));
+    }
+
+    #[test]
+    fn decodes_parameters() {
+        assert_eq!(
+            parse("rtodo://add?date=2026-10-21&text=Pay+rent+%23home+%26+bills%21&extra=1"),
+            DeepLink::Add {
+                text: "Pay rent #home & bills!".to_owned(),
+                date: Some("2026-10-21".to_owned())
+            }
+        );
+        assert_eq!(
+            parse("rtodo://add?text=%20%20Caf%C3%A9%20"),
+            DeepLink::Add {
+                text: "Café".to_owned(),
+                date: None
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_links() {
+        assert!(matches!(
+            DeepLink::parse("https://add?text=x"),
+            Err(Error::Scheme(scheme)) if scheme == "https"
+        ));
+        assert!(matches!(
+            DeepLink::parse("rtodo://delete?id=1"),
+            Err(Error::UnknownAction(action)) if action == "delete"
+        ));
+        assert!(matches!(
+            DeepLink::parse("rtodo:add?text=x"),
+            Err(Error::UnknownAction(action)) if action.is_empty()
+        ));
+        assert!(matches!(DeepLink::parse("not a url"), Err(Error::Url(_))));
+    }
+
+    #[test]
+    fn rejects_bad_parameters() {
+        for input in [
+            "rtodo://add",
+            "rtodo://add?text=",
+            "rtodo://add?text=%20",
+            "rtodo://add?title=Buy+milk",
+        ] {
+            assert!(
+                matches!(DeepLink::parse(input), Err(Error::MissingParam("text"))),
+                "{input}"
+            );
+        }
+        for input in [
+            "rtodo://open?date=tomorrow",
+            "rtodo://open?date=2026-02-30",
+            "rtodo://add?text=x&date=21.10.2026",
+        ] {
+            assert!(
+                matches!(
+                    DeepLink::parse(input),
+                    Err(Error::Store(store::Error::InvalidDate(_)))
+                ),
+                "{input}"
+            );
+        }
+        for input in ["rtodo://todo", "rtodo://todo/abc", "rtodo://todo/4/2"] {
+            assert!(
+                matches!(DeepLink::parse(input), Err(Error::InvalidId(_))),
+                "{input}"
+            );
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Events the backend broadcasts to every window, mostly after it changes
+//! todos.
+//!
+//! Names and payload shapes match what `src/utils/events.ts` listeners
+//! already expect, so windows refresh no matter who made the change.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, PhysicalPosition, Runtime};
+use tracing::warn;
+
+use crate::store::Todo;
+
+pub const TODO_ADDED: &str = "todo-added";
+pub const TODO_UPDATED: &str = "todo-updated";
+pub const TODO_DELETED: &str = "todo-deleted";
+pub const TODOS_REORDERED: &str = "todos-reordered";
+/// Something other than a command changed the todos, such as another
+/// process or the start of a new day, or a command changed more than the
+/// other events describe, such as a whole subtree; reload everything.
+pub const TODOS_CHANGED: &str = "todos-changed";
+/// Tags were renamed, merged, recolored or deleted, which may change how
+/// any todo looks.
+pub const TAGS_CHANGED: &str = "tags-changed";
+/// Projects were added, edited, reordered or deleted, or todos moved between
+/// them, so project lists and their counts are stale.
+pub const PROJECTS_CHANGED: &str = "projects-changed";
+/// Files were attached to or removed from the payload's todo.
+pub const ATTACHMENTS_CHANGED: &str = "attachments-changed";
+/// Files were dropped onto the main window at the payload's position, in
+/// physical pixels; `attach_dropped_files` attaches them to a todo.
+pub const FILES_DROPPED: &str = "files-dropped";
+/// The main window should switch to the given day, or to today when the
+/// payload's date is null.
+pub const OPEN_DATE: &str = "open-date";
+
+#[derive(Clone, Serialize)]
+struct TodoPayload<'a> {
+    todo: &'a Todo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<&'a str>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeletedPayload {
+    id: Option<i64>,
+    count: Option<usize>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentsPayload {
+    todo_id: i64,
+}
+
+#[derive(Clone, Serialize)]
+struct PositionPayload {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Clone, Serialize)]
+struct DatePayload<'a> {
+    date: Option<&'a str>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReorderedPayload<'a> {
+    todo_ids: &'a [i64],
+}
+
+fn emit<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: S) {
+    // Listeners only refresh their view, so a failed emit is not worth
+    // failing the command that already committed.
+    if let Err(e) = app.emit(event, payload) {
+        warn!(event, error = %e, "failed to emit event");
+    }
+}
+
+pub fn todo_added<R: Runtime>(app: &AppHandle<R>, todo: &Todo) {
+    emit(app, TODO_ADDED, TodoPayload { todo, action: None });
+}
+
+pub fn todo_updated<R: Runtime>(app: &AppHandle<R>, todo: &Todo, action: &str) {
+    emit(
+        app,
+        TODO_UPDATED,
+        TodoPayload {
+            todo,
+            action: Some(action),
+        },
+    );
+}
+
+pub fn todo_deleted<R: Runtime>(app: &AppHandle<R>, id: i64) {
+    emit(
+        app,
+        TODO_DELETED,
+        DeletedPayload {
+            id: Some(id),
+            count: None,
+        },
+    );
+}
+
+pub fn todos_cleared<R: Runtime>(app: &AppHandle<R>, count: usize) {
+    emit(
+        app,
+        TODO_DELETED,
+        DeletedPayload {
+            id: None,
+            count: Some(count),
+        },
+    );
+}
+
+pub fn todos_reordered<R: Runtime>(app: &AppHandle<R>, todo_ids: &[i64]) {
+    emit(app, TODOS_REORDERED, ReorderedPayload { todo_ids });
+}
+
+pub fn todos_changed<R: Runtime>(app: &AppHandle<R>) {
+    emit(app, TODOS_CHANGED, ());
+}
+
+pub fn open_date<R: Runtime>(app: &AppHandle<R>, date: Option<&str>) {
+    emit(app, OPEN_DATE, DatePayload { date });
+}
+
+pub fn tags_changed<R: Runtime>(app: &AppHandle<R>) {
+    emit(app, TAGS_CHANGED, ());
+}
+
+pub fn projects_changed<R: Runtime>(app: &AppHandle<R>) {
+    emit(app, PROJECTS_CHANGED, ());
+}
+
+pub fn attachments_changed<R: Runtime>(app: &AppHandle<R>, todo_id: i64) {
+    emit(app, ATTACHMENTS_CHANGED, AttachmentsPayload { todo_id });
+}
+
+pub fn files_dropped<R: Runtime>(app: &AppHandle<R>, position: PhysicalPosition<f64>) {
+    emit(
+        app,
+        FILES_DROPPED,
+        PositionPayload {
+            x: position.x,
+            y: position.y,
+        },
+    );
+}
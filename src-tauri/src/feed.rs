@@ -0,0 +1,256 @@
+//! Read-only iCalendar feed of scheduled todos on localhost, so calendar
+//! clients can subscribe to `http://127.0.0.1:<port>/rtodo.ics` and show
+//! the day's todos next to meetings.
+//!
+//! The feed is off unless the `ical_feed_port` setting holds a port, and
+//! the setting is read when the app starts. It only ever listens on the
+//! loopback interface, answers `GET` and `HEAD`, and refuses requests whose
+//! `Host` is not the loopback address, so a web page cannot reach it through
+//! DNS rebinding.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use crate::store::Store;
+
+/// Settings key holding the feed port, or `off`.
+pub const SETTING: &str = "ical_feed_port";
+pub const DEFAULT: &str = "off";
+
+/// Path the feed is served at; `/` works too.
+pub const PATH: &str = "/rtodo.ics";
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request head read before giving up on a client.
+const MAX_HEAD: usize = 8 * 1024;
+
+/// Clients served at once; further connections are closed right away.
+const MAX_CLIENTS: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid feed port {0:?}, expected 1 to 65535 or off")]
+    InvalidPort(String),
+}
+
+/// The port in a setting value, or `None` when the feed is off.
+pub fn parse(value: &str) -> Result<Option<u16>, Error> {
+    match value.trim() {
+        "off" => Ok(None),
+        port => port
+            .parse()
+            .ok()
+            .filter(|&port| port != 0)
+            .map(Some)
+            .ok_or_else(|| Error::InvalidPort(value.to_owned())),
+    }
+}
+
+/// Starts serving the feed when it is turned on. A port that is taken or a
+/// setting that does not parse is only logged.
+pub fn spawn(store: Store) {
+    let port = match store.setting(SETTING) {
+        Ok(value) => match parse(value.as_deref().unwrap_or(DEFAULT)) {
+            Ok(Some(port)) => port,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(error = %e, "calendar feed disabled");
+                return;
+            }
+        },
+        Err(e) => {
+            warn!(error = %e, "failed to read calendar feed port");
+            return;
+        }
+    };
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!(port, error = %e, "calendar feed unavailable");
+            return;
+        }
+    };
+    info!(url = %format!("http://127.0.0.1:{port}{PATH}"), "serving calendar feed");
+    thread::spawn(move || {
+        let clients = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let Some(client) = Client::admit(&clients) else {
+                        debug!("too many calendar feed clients, dropping connection");
+                        continue;
+                    };
+                    let store = store.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = serve(stream, &store, port) {
+                            debug!(error = %e, "calendar feed request failed");
+                        }
+                        drop(client);
+                    });
+                }
+                Err(e) => warn!(error = %e, "failed to accept calendar feed client"),
+            }
+        }
+    });
+}
+
+/// A slot among the [`MAX_CLIENTS`], given back when dropped.
+struct Client(Arc<AtomicUsize>);
+
+impl Client {
+    fn admit(clients: &Arc<AtomicUsize>) -> Option<Self> {
+        clients
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < MAX_CLIENTS).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| Self(Arc::clone(clients)))
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Answers one request and closes the connection.
+fn serve(stream: TcpStream, store: &Store, port: u16) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?.take(MAX_HEAD as u64));
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    let mut host = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_owned());
+            }
+        }
+        header.clear();
+    }
+    if !host.as_deref().is_some_and(|host| is_loopback(host, port)) {
+        return Response::error("403 Forbidden", "").write(stream, false);
+    }
+
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let response = match (method, path) {
+        ("GET" | "HEAD", "/" | PATH) => match store.ical_feed() {
+            Ok(calendar) => Response::ok(calendar),
+            Err(e) => {
+                warn!(error = %e, "failed to build calendar feed");
+                Response::error("500 Internal Server Error", "")
+            }
+        },
+        ("GET" | "HEAD", _) => Response::error("404 Not Found", ""),
+        _ => Response::error("405 Method Not Allowed", "Allow: GET, HEAD\r\n"),
+    };
+    response.write(stream, method == "HEAD")
+}
+
+/// Whether a `Host` header names the feed on the loopback interface.
+fn is_loopback(host: &str, port: u16) -> bool {
+    let Some((name, host_port)) = host.rsplit_once(':') else {
+        return false;
+    };
+    host_port == port.to_string() && (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost"))
+}
+
+struct Response {
+    status: &'static str,
+    headers: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(calendar: String) -> Self {
+        Self {
+            status: "200 OK",
+            headers: "Cache-Control: no-cache\r\n",
+            content_type: "text/calendar; charset=utf-8",
+            body: calendar,
+        }
+    }
+
+    fn error(status: &'static str, headers: &'static str) -> Self {
+        Self {
+            status,
+            headers,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{status}\n"),
+        }
+    }
+
+    fn write(self, mut stream: TcpStream, head_only: bool) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.headers,
+        )?;
+        if !head_only {
+            stream.write_all(self.body.as_bytes())?;
+        }
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ports() {
+        assert_eq!(parse("off").unwrap(), None);
+        assert_eq!(parse(" 8765 ").unwrap(), Some(8765));
+        for value in ["0", "65536", "on", ""] {
+            assert!(matches!(parse(value), Err(Error::InvalidPort(_))));
+        }
+    }
+
+    #[test]
+    fn accepts_only_loopback_hosts() {
+        assert!(is_loopback("127.0.0.1:8765", 8765));
+        assert!(is_loopback("localhost:8765", 8765));
+        assert!(is_loopback("LocalHost:8765", 8765));
+        for host in [
+            "127.0.0.1",
+            "localhost",
+            "127.0.0.1:8766",
+            "localhost:08765",
+            "evil.example:8765",
+            "localhost.evil.example:8765",
+            "[::1]:8765",
+            "",
+        ] {
+            assert!(!is_loopback(host, 8765), "{host:?}");
+        }
+    }
+
+    #[test]
+    fn admits_a_limited_number_of_clients() {
+        let clients = Arc::new(AtomicUsize::new(0));
+        let admitted: Vec<Client> = (0..MAX_CLIENTS)
+            .map(|_| Client::admit(&clients).unwrap())
+            .collect();
+        assert!(Client::admit(&clients).is_none());
+        drop(admitted);
+        assert_eq!(clients.load(Ordering::Acquire), 0);
+        assert!(Client::admit(&clients).is_some());
+    }
+}
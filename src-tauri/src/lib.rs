@@ -1,46 +1,186 @@
-use tauri::Manager;
-use tauri::async_runtime;
-use tracing::{info, instrument};
-
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-#[instrument(skip(name))]
-pub async fn greet(name: &str) -> String {
-    // Offload any potential blocking work from the async executor
-    let name = name.to_string();
-    let res = async_runtime::spawn_blocking(move || {
-        format!("Hello, {}! You've been greeted from Rust!", name)
-    })
-    .await
-    .unwrap_or_else(|_| "Hello!".to_string());
-
-    res
+use std::path::PathBuf;
+
+use tauri::{Manager, WebviewWindowBuilder};
+use tracing::{error, info, warn};
+
+mod actions;
+pub mod cli;
+mod commands;
+mod deep_link;
+mod events;
+mod feed;
+mod reminders;
+mod shortcut;
+pub mod store;
+mod sync;
+mod tray;
+mod windows;
+
+use cli::LaunchArgs;
+use store::Store;
+
+/// File name of the todo database inside the app data directory.
+pub const DB_FILE: &str = "rtodo.db";
+
+/// Must match `identifier` in tauri.conf.json, which names the app data
+/// directory.
+const IDENTIFIER: &str = "rtodo";
+
+/// Location of the database for code that runs without a Tauri app, such as
+/// the CLI. Mirrors `PathResolver::app_data_dir`.
+pub fn database_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join(IDENTIFIER).join(DB_FILE))
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // Initialize tracing subscriber with env filter support.
+fn init_tracing(default_filter: &str) {
     // Set RUST_LOG=info,tauri=warn to adjust verbosity in development.
     let filter_layer = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info,tauri=warn,wry=warn"));
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_filter));
     tracing_subscriber::fmt()
         .with_env_filter(filter_layer)
+        .with_writer(std::io::stderr)
         .compact()
         .init();
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    run_with(LaunchArgs::default(), None);
+}
+
+/// Starts the app on the database at `db`, or on the one in the app data
+/// directory, and then carries out the launch flags it was given.
+pub fn run_with(launch: LaunchArgs, db: Option<PathBuf>) {
+    init_tracing("info,tauri=warn,wry=warn");
 
     info!("Starting Tauri application");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_sql::Builder::default().build())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
-        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
-            if let Some(window) = app.webview_windows().get("main") {
-                let _ = window.set_focus();
-            }
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            // On Windows and Linux this also receives `rtodo://` links, which
+            // the OS passes as the second instance's argument.
+            actions::forward(app, argv);
         }))
-        .invoke_handler(tauri::generate_handler![greet])
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(shortcut::init())
+        .manage(actions::PendingDate::default())
+        .manage(commands::DroppedFiles::default())
+        .on_window_event(commands::remember_dropped_files)
+        .setup(move |app| {
+            // The main window is declared with `create: false` so migrations
+            // finish before any webview can reach the database.
+            let path = match db {
+                Some(path) => path,
+                None => app.path().app_data_dir()?.join(DB_FILE),
+            };
+            let store = Store::open(&path).inspect_err(|e| {
+                error!(path = %path.display(), error = %e, "failed to open todo database");
+            })?;
+            if let Err(e) = store.carry_over() {
+                warn!(error = %e, "failed to carry over unfinished todos");
+            }
+            sync::spawn(app.handle().clone(), store.clone());
+            reminders::spawn(app.handle().clone(), store.clone());
+            feed::spawn(store.clone());
+            shortcut::register(app.handle(), &store);
+            app.manage(store);
+            tray::create(app.handle())?;
+
+            if let Some(config) = app
+                .config()
+                .app
+                .windows
+                .iter()
+                .find(|w| w.label == windows::MAIN)
+            {
+                WebviewWindowBuilder::from_config(app.handle(), config)?.build()?;
+            }
+
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                app.deep_link().register_all()?;
+            }
+            #[cfg(target_os = "macos")]
+            {
+                // macOS delivers links as events rather than through argv.
+                use tauri_plugin_deep_link::DeepLinkExt;
+                let handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        actions::open_link(&handle, url.as_str(), false);
+                    }
+                });
+            }
+
+            actions::apply(app.handle(), launch, true);
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::list_todos,
+            commands::todos_by_date,
+            commands::first_todo,
+            commands::add_todo,
+            commands::quick_add,
+            commands::parse_quick_add,
+            commands::toggle_todo,
+            commands::update_todo,
+            commands::todo_tree,
+            commands::move_todo,
+            commands::set_todo_due,
+            commands::set_todo_reminder,
+            commands::snooze_reminder,
+            commands::set_todo_priority,
+            commands::set_todo_recurrence,
+            commands::set_todo_notes,
+            commands::render_notes,
+            commands::open_link,
+            commands::list_attachments,
+            commands::attach_dropped_files,
+            commands::open_attachment,
+            commands::remove_attachment,
+            commands::overdue_todos,
+            commands::search_todos,
+            commands::export_json,
+            commands::import_json,
+            commands::export_todo_txt,
+            commands::import_todo_txt,
+            commands::export_ical,
+            commands::import_ical,
+            commands::report,
+            commands::delete_todo,
+            commands::clear_completed,
+            commands::reorder_todos,
+            commands::today,
+            commands::sort_mode,
+            commands::set_sort_mode,
+            commands::historical_dates,
+            commands::future_dates,
+            commands::list_tags,
+            commands::tag_todo,
+            commands::untag_todo,
+            commands::rename_tag,
+            commands::merge_tags,
+            commands::set_tag_color,
+            commands::delete_tag,
+            commands::todos_by_tags,
+            commands::list_projects,
+            commands::create_project,
+            commands::rename_project,
+            commands::set_project_style,
+            commands::archive_project,
+            commands::reorder_projects,
+            commands::delete_project,
+            commands::project_todos,
+            commands::reorder_project_todos,
+            commands::move_todos_to_project,
+            actions::take_pending_date,
+            commands::quick_add_shortcut,
+            commands::set_quick_add_shortcut,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
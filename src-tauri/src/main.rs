@@ -1,6 +1,6 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-fn main() {
-    rtodo_lib::run()
+fn main() -> std::process::ExitCode {
+    rtodo_lib::cli::main()
 }
@@ -0,0 +1,112 @@
+//! Desktop notifications for todo reminders.
+//!
+//! The scheduler polls the database instead of sleeping until the next
+//! reminder, so reminders set from the CLI are picked up and waking from
+//! sleep is noticed within one poll; whatever fell due meanwhile fires then.
+//! After a longer absence the missed reminders are folded into one
+//! notification rather than a burst of them.
+//!
+//! Clicking a notification shows the main window on the todo's day, and its
+//! snooze actions move the reminder later.
+
+use std::thread;
+use std::time::Duration;
+
+use notify_rust::Notification;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{debug, info, warn};
+
+use crate::store::{Store, Todo};
+use crate::{actions, events};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Action a click on the notification itself reports.
+const OPEN: &str = "default";
+
+/// Snooze actions offered on a reminder, as (action, label, minutes).
+const SNOOZES: [(&str, &str, u32); 2] = [
+    ("snooze-10", "Snooze 10 min", 10),
+    ("snooze-60", "Snooze 1 hour", 60),
+];
+
+/// More reminders due at once than this are shown as one summary.
+const MAX_SEPARATE: usize = 3;
+
+pub fn spawn<R: Runtime>(app: AppHandle<R>, store: Store) {
+    thread::spawn(move || loop {
+        match store.take_due_reminders() {
+            Ok(todos) if todos.len() > MAX_SEPARATE => {
+                info!(count = todos.len(), "showing missed reminders");
+                show_summary(&app, &todos);
+            }
+            Ok(todos) => {
+                for todo in &todos {
+                    show_reminder(&app, todo);
+                }
+            }
+            Err(e) => warn!(error = %e, "failed to check reminders"),
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+fn show_reminder<R: Runtime>(app: &AppHandle<R>, todo: &Todo) {
+    debug!(id = todo.id, "showing reminder");
+    let mut notification = Notification::new();
+    notification
+        .summary(&todo.text)
+        .body(&format!("Scheduled for {}", todo.day()))
+        .action(OPEN, "Open");
+    for (action, label, _) in SNOOZES {
+        notification.action(action, label);
+    }
+    show(app, notification, Some(todo.id));
+}
+
+fn show_summary<R: Runtime>(app: &AppHandle<R>, todos: &[Todo]) {
+    let mut body: Vec<&str> = todos
+        .iter()
+        .take(MAX_SEPARATE)
+        .map(|t| t.text.as_str())
+        .collect();
+    let more = format!("and {} more", todos.len() - MAX_SEPARATE);
+    body.push(&more);
+    let mut notification = Notification::new();
+    notification
+        .summary(&format!("{} reminders", todos.len()))
+        .body(&body.join("\n"))
+        .action(OPEN, "Open");
+    show(app, notification, None);
+}
+
+/// Shows `notification` and waits on a thread of its own for the user to
+/// act on it, since some platforms block until then.
+fn show<R: Runtime>(app: &AppHandle<R>, notification: Notification, todo_id: Option<i64>) {
+    let app = app.clone();
+    thread::spawn(move || match notification.show() {
+        Ok(handle) => handle.wait_for_action(|action| on_action(&app, todo_id, action)),
+        Err(e) => warn!(error = %e, "failed to show notification"),
+    });
+}
+
+fn on_action<R: Runtime>(app: &AppHandle<R>, todo_id: Option<i64>, action: &str) {
+    if action == OPEN {
+        return match todo_id {
+            Some(id) => actions::open_todo(app, id, false),
+            None => actions::open_day(app, None),
+        };
+    }
+    let (Some(id), Some(&(_, _, minutes))) =
+        (todo_id, SNOOZES.iter().find(|(name, ..)| *name == action))
+    else {
+        return;
+    };
+    match app.state::<Store>().snooze_reminder(id, minutes) {
+        Ok(todo) => {
+            info!(id, minutes, "snoozed reminder");
+            events::todo_updated(app, &todo, "updated");
+        }
+        Err(e) => warn!(id, error = %e, "failed to snooze reminder"),
+    }
+}
@@ -0,0 +1,110 @@
+//! Global shortcut that opens the quick-add popup from any application.
+//!
+//! The accelerator is stored in the `quick_add_shortcut` setting, using the
+//! plugin's syntax such as `Ctrl+Alt+T` or `CommandOrControl+Shift+Space`.
+
+use std::str::FromStr;
+
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_global_shortcut::{Builder, GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{info, warn};
+
+use crate::store::{self, Store};
+use crate::windows;
+
+/// Settings key holding the quick-add accelerator.
+pub const SETTING: &str = "quick_add_shortcut";
+pub const DEFAULT: &str = "Ctrl+Alt+T";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid shortcut {0:?}")]
+    Invalid(String),
+    #[error("could not register shortcut {shortcut:?}: {source}")]
+    Register {
+        shortcut: String,
+        source: tauri_plugin_global_shortcut::Error,
+    },
+    #[error(transparent)]
+    Store(#[from] store::Error),
+}
+
+/// The global shortcut plugin, routing every registered shortcut to the
+/// quick-add popup.
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = windows::toggle_quick_add(app) {
+                    warn!(error = %e, "failed to toggle quick-add window");
+                }
+            }
+        })
+        .build()
+}
+
+pub fn parse(accelerator: &str) -> Result<Shortcut, Error> {
+    Shortcut::from_str(accelerator).map_err(|_| Error::Invalid(accelerator.to_owned()))
+}
+
+/// The configured accelerator, falling back to [`DEFAULT`] when the setting
+/// is missing or no longer parses.
+pub fn configured(store: &Store) -> String {
+    match store.setting(SETTING) {
+        Ok(Some(accelerator)) if parse(&accelerator).is_ok() => accelerator,
+        Ok(Some(accelerator)) => {
+            warn!(%accelerator, "ignoring invalid quick-add shortcut");
+            DEFAULT.to_owned()
+        }
+        Ok(None) => DEFAULT.to_owned(),
+        Err(e) => {
+            warn!(error = %e, "failed to read quick-add shortcut");
+            DEFAULT.to_owned()
+        }
+    }
+}
+
+/// Registers the configured shortcut at startup. Another application may
+/// already own the keys, so failure is only logged; the popup stays
+/// reachable from the tray.
+pub fn register<R: Runtime>(app: &AppHandle<R>, store: &Store) {
+    let accelerator = configured(store);
+    match register_accelerator(app, &accelerator) {
+        Ok(()) => info!(%accelerator, "registered quick-add shortcut"),
+        Err(e) => warn!(error = %e, "quick-add shortcut unavailable"),
+    }
+}
+
+/// Replaces the registered shortcut and saves it. The previous shortcut is
+/// restored when the new one cannot be registered.
+pub fn set<R: Runtime>(app: &AppHandle<R>, store: &Store, accelerator: &str) -> Result<(), Error> {
+    let accelerator = accelerator.trim();
+    let shortcut = parse(accelerator)?;
+    let previous = configured(store);
+    let shortcuts = app.global_shortcut();
+
+    if let Ok(old) = parse(&previous) {
+        if shortcuts.is_registered(old) && old != shortcut {
+            let _ = shortcuts.unregister(old);
+        }
+    }
+    if !shortcuts.is_registered(shortcut) {
+        if let Err(e) = register_accelerator(app, accelerator) {
+            let _ = register_accelerator(app, &previous);
+            return Err(e);
+        }
+    }
+    store.set_setting(SETTING, accelerator)?;
+    info!(%accelerator, "changed quick-add shortcut");
+    Ok(())
+}
+
+fn register_accelerator<R: Runtime>(app: &AppHandle<R>, accelerator: &str) -> Result<(), Error> {
+    app.global_shortcut()
+        .register(parse(accelerator)?)
+        .map_err(|source| Error::Register {
+            shortcut: accelerator.to_owned(),
+            source,
+        })
+}
@@ -0,0 +1,756 @@
+//! Native todo storage backed by SQLite.
+//!
+//! The store owns the `todos` table: opening the database, migrating the
+//! schema, CRUD, reordering and the per-day queries used by the date picker.
+//! A todo is listed under its `scheduled_date`; `created_at`, `updated_at`
+//! and `completed_at` record what actually happened and when.
+//! It is deliberately free of any Tauri types so the CLI and tests can use it
+//! directly; the webview reaches it through `commands`.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Params, Row};
+use serde::{Serialize, Serializer};
+use tracing::{debug, info};
+
+pub use attachments::Attachment;
+pub use export::{Document, ImportReport, ImportStrategy};
+pub use notes::RenderedNotes;
+pub use priority::{Priority, SortMode};
+pub use projects::{Project, ProjectDeletion};
+pub use quick_add::QuickAdd;
+pub use recurrence::Rule;
+pub use report::{ReportDay, ReportFormat};
+pub use search::{Highlighted, SearchHit};
+pub use subtasks::{Progress, TodoNode};
+pub use tags::{Tag, TagMatch};
+
+pub mod attachments;
+pub mod carry_over;
+pub mod days;
+pub mod export;
+pub mod ical;
+mod migrations;
+pub mod notes;
+pub mod priority;
+pub mod projects;
+pub mod quick_add;
+pub mod recurrence;
+pub mod reminders;
+pub mod report;
+pub mod search;
+pub mod subtasks;
+pub mod tags;
+#[cfg(test)]
+mod testing;
+pub mod todotxt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("todo {0} not found")]
+    NotFound(i64),
+    #[error("invalid date `{0}`, expected YYYY-MM-DD")]
+    InvalidDate(String),
+    #[error("invalid date and time `{0}`, expected YYYY-MM-DD HH:MM or RFC 3339")]
+    InvalidDateTime(String),
+    #[error("invalid day start hour `{0}`, expected 0-23")]
+    InvalidStartHour(String),
+    #[error("invalid carry-over mode `{0}`, expected move, copy or leave")]
+    InvalidCarryOver(String),
+    #[error("invalid tag name `{0}`")]
+    InvalidTagName(String),
+    #[error("tag `{0}` not found")]
+    TagNotFound(String),
+    #[error("tag `{0}` already exists; merge the tags instead")]
+    TagExists(String),
+    #[error("invalid color `{0}`, expected #RRGGBB")]
+    InvalidColor(String),
+    #[error("invalid priority `{0}`, expected none, low, medium, high or urgent")]
+    InvalidPriority(String),
+    #[error("invalid sort mode `{0}`, expected manual or priority")]
+    InvalidSortMode(String),
+    #[error("todo {0} cannot be moved under itself or one of its subtasks")]
+    InvalidParent(i64),
+    #[error("invalid subtask completion `{0}`, expected cascade or independent")]
+    InvalidSubtaskCompletion(String),
+    #[error("invalid repeat rule `{0}`, expected RRULE syntax or e.g. `every 2 weeks on monday`")]
+    InvalidRecurrence(String),
+    #[error("subtasks cannot repeat; set the rule on the top-level todo")]
+    RecurringSubtask,
+    #[error("cannot snooze for {0} minutes, expected at least one")]
+    InvalidSnooze(u32),
+    #[error("project `{0}` not found")]
+    ProjectNotFound(String),
+    #[error("project name must not be empty")]
+    EmptyProjectName,
+    #[error("project `{0}` already exists")]
+    ProjectExists(String),
+    #[error("attachment {0} not found")]
+    AttachmentNotFound(i64),
+    #[error("cannot attach `{0}`, expected a file")]
+    InvalidAttachment(String),
+    #[error("an in-memory database cannot hold attachments")]
+    NoAttachmentFolder,
+    #[error("`{0}` already exists; back up into an empty folder")]
+    BackupExists(String),
+    #[error("not an rtodo export: {0}")]
+    InvalidExport(String),
+    #[error(
+        "export version {found} is newer than this app supports ({supported}); please update rtodo"
+    )]
+    ExportTooNew { found: u32, supported: u32 },
+    #[error("invalid import strategy `{0}`, expected replace, merge or append")]
+    InvalidImportStrategy(String),
+    #[error("not an iCalendar file")]
+    InvalidCalendar,
+    #[error("invalid report format `{0}`, expected csv or markdown")]
+    InvalidReportFormat(String),
+    #[error("invalid date range: {from} is after {to}")]
+    InvalidDateRange { from: String, to: String },
+    #[error("todo text must not be empty")]
+    EmptyText,
+    #[error("database schema version {found} is newer than this app supports ({supported}); please update rtodo")]
+    SchemaTooNew { found: u32, supported: u32 },
+}
+
+// Commands hand errors to the webview, which only needs the message.
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Todo {
+    pub id: i64,
+    /// Random UUID identifying the todo across exports and imports.
+    pub uid: String,
+    pub text: String,
+    /// Markdown; render with [`notes::render`] before showing it as HTML.
+    pub notes: Option<String>,
+    pub completed: bool,
+    /// `YYYY-MM-DD` day the todo is listed under.
+    pub scheduled_date: String,
+    /// Deadline as a UTC RFC 3339 timestamp.
+    pub due_at: Option<String>,
+    /// When to show a reminder, as a UTC RFC 3339 timestamp; see
+    /// [`reminders`].
+    pub remind_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub completed_at: Option<String>,
+    /// Day the todo was planned for before being carried over to a later one.
+    pub original_date: Option<String>,
+    /// Todo this one was copied from by carry-over.
+    pub carried_from: Option<i64>,
+    pub project_id: Option<i64>,
+    pub priority: Priority,
+    /// Todo this one is a subtask of.
+    pub parent_id: Option<i64>,
+    /// How the todo repeats; see [`recurrence`].
+    pub recurrence: Option<Rule>,
+    /// Previous instance of a repeating todo.
+    pub recurs_from: Option<i64>,
+    pub sort_order: i64,
+    /// Sorted by name.
+    pub tags: Vec<Tag>,
+    /// Rollup of the direct subtasks, or `None` when there are none.
+    pub progress: Option<Progress>,
+}
+
+impl Todo {
+    /// The `YYYY-MM-DD` day this todo is listed under.
+    pub fn day(&self) -> &str {
+        &self.scheduled_date
+    }
+
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            uid: row.get("uid")?,
+            text: row.get("text")?,
+            notes: row.get("notes")?,
+            completed: row.get("completed")?,
+            scheduled_date: row.get("scheduled_date")?,
+            due_at: row.get("due_at")?,
+            remind_at: row.get("remind_at")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+            completed_at: row.get("completed_at")?,
+            original_date: row.get("original_date")?,
+            carried_from: row.get("carried_from")?,
+            project_id: row.get("project_id")?,
+            priority: row.get("priority")?,
+            parent_id: row.get("parent_id")?,
+            recurrence: row.get("recurrence")?,
+            recurs_from: row.get("recurs_from")?,
+            sort_order: row.get("sort_order")?,
+            tags: Vec::new(),
+            progress: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstTodo {
+    pub todo: Option<Todo>,
+    pub active_count: usize,
+}
+
+/// A todo to be created by [`Store::create_todo`].
+#[derive(Debug, Default, Clone)]
+pub struct NewTodo {
+    pub text: String,
+    /// Day to schedule it on (YYYY-MM-DD); today when `None`.
+    pub date: Option<String>,
+    /// Deadline in any format [`parse_due`] accepts.
+    pub due_at: Option<String>,
+    /// Reminder time in any format [`parse_due`] accepts.
+    pub remind_at: Option<String>,
+    pub project_id: Option<i64>,
+    pub priority: Priority,
+    /// Todo to add this one under; it then goes on the parent's day and
+    /// `date` is ignored.
+    pub parent_id: Option<i64>,
+    /// Tag names, created when missing.
+    pub tags: Vec<String>,
+    /// Repeat rule in any form [`Rule`] parses; the series starts on the
+    /// todo's day.
+    pub recurrence: Option<String>,
+}
+
+/// Fields that may be changed by [`Store::update_todo`]; `None` leaves the
+/// column untouched.
+#[derive(Debug, Default, Clone)]
+pub struct TodoUpdate {
+    pub text: Option<String>,
+    pub completed: Option<bool>,
+}
+
+/// Manual order of a day, which `sort_order` always follows whatever the
+/// [`SortMode`].
+const ORDER_BY: &str = "ORDER BY completed ASC, sort_order ASC, created_at DESC";
+
+/// Cheaply cloneable handle to the todo database.
+///
+/// All access goes through a single connection guarded by a mutex, which
+/// gives the same single-writer semantics the webview used to emulate with a
+/// promise chain.
+#[derive(Clone)]
+pub struct Store {
+    conn: Arc<Mutex<Connection>>,
+    /// Attachment blobs, in a folder next to the database file; `None` for
+    /// an in-memory database.
+    attachments_dir: Option<Arc<Path>>,
+}
+
+impl Store {
+    #[tracing::instrument]
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            // A missing directory surfaces as SQLITE_CANTOPEN below.
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let mut conn = Connection::open(path)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             PRAGMA busy_timeout = 5000;
+             PRAGMA foreign_keys = ON;
+             PRAGMA cache_size = -20000;
+             PRAGMA temp_store = MEMORY;",
+        )?;
+        migrations::run(&mut conn)?;
+        let in_memory = matches!(path.to_str(), Some("" | ":memory:"));
+        let attachments_dir = if in_memory {
+            None
+        } else {
+            let dir = attachments::dir_for(path);
+            attachments::adopt_legacy(&conn, path, &dir)?;
+            Some(dir.into())
+        };
+        info!(path = %path.display(), "opened todo database");
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            attachments_dir,
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic while holding the lock cannot leave SQLite in a torn state,
+        // so a poisoned mutex is still safe to use.
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn list_todos(&self) -> Result<Vec<Todo>> {
+        let conn = self.conn();
+        let order_by = priority::sort_mode(&conn)?.order_by("sort_order");
+        query_todos(
+            &conn,
+            &format!("SELECT * FROM todos WHERE parent_id IS NULL {order_by}"),
+            [],
+        )
+    }
+
+    pub fn todos_by_date(&self, date: &str) -> Result<Vec<Todo>> {
+        let date = parse_date(date)?;
+        let conn = self.conn();
+        let order_by = priority::sort_mode(&conn)?.order_by("sort_order");
+        query_todos(
+            &conn,
+            &format!(
+                "SELECT * FROM todos WHERE scheduled_date = ?1 AND parent_id IS NULL {order_by}"
+            ),
+            [date.to_string()],
+        )
+    }
+
+    pub fn get_todo(&self, id: i64) -> Result<Todo> {
+        let conn = self.conn();
+        get_todo(&conn, id)
+    }
+
+    /// Next incomplete todo of today and how many are left, which is all
+    /// the compact window is allowed to see.
+    pub fn first_todo(&self) -> Result<FirstTodo> {
+        let conn = self.conn();
+        let order_by = priority::sort_mode(&conn)?.order_by("sort_order");
+        let active = query_todos(
+            &conn,
+            &format!(
+                "SELECT * FROM todos
+                 WHERE completed = 0 AND scheduled_date = ?1 AND parent_id IS NULL
+                 {order_by}"
+            ),
+            [today(&conn)?],
+        )?;
+        Ok(FirstTodo {
+            active_count: active.len(),
+            todo: active.into_iter().next(),
+        })
+    }
+
+    /// Adds a todo at the end of the incomplete items of `date`, or of today
+    /// when no date is given.
+    pub fn add_todo(&self, text: &str, date: Option<&str>) -> Result<Todo> {
+        self.create_todo(&NewTodo {
+            text: text.to_owned(),
+            date: date.map(str::to_owned),
+            ..NewTodo::default()
+        })
+    }
+
+    /// Adds a todo with everything it should start out with, at the end of
+    /// the incomplete items of its day and of its project.
+    #[tracing::instrument(skip(self, new), fields(project_id = new.project_id))]
+    pub fn create_todo(&self, new: &NewTodo) -> Result<Todo> {
+        let text = new.text.trim();
+        if text.is_empty() {
+            return Err(Error::EmptyText);
+        }
+        let due_at = new.due_at.as_deref().map(parse_due).transpose()?;
+        let remind_at = new.remind_at.as_deref().map(parse_due).transpose()?;
+        let tags = tags::normalize_names(&new.tags)?;
+        let recurrence = new
+            .recurrence
+            .as_deref()
+            .map(str::parse::<Rule>)
+            .transpose()?;
+        if recurrence.is_some() && new.parent_id.is_some() {
+            return Err(Error::RecurringSubtask);
+        }
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let (scheduled_date, next_sort) = match new.parent_id {
+            Some(parent) => subtasks::next_child(&tx, parent)?,
+            None => {
+                let scheduled_date = match &new.date {
+                    Some(date) => parse_date(date)?.to_string(),
+                    None => today(&tx)?,
+                };
+                let next_sort = tx.query_row(
+                    "SELECT COALESCE(MAX(sort_order), 0) + 1 FROM todos
+                     WHERE completed = 0 AND scheduled_date = ?1 AND parent_id IS NULL",
+                    [&scheduled_date],
+                    |row| row.get(0),
+                )?;
+                (scheduled_date, next_sort)
+            }
+        };
+        let project_order = match new.project_id {
+            Some(project_id) => projects::next_order(&tx, project_id)?,
+            None => 0,
+        };
+        let id: i64 = tx.query_row(
+            "INSERT INTO todos
+                 (text, completed, scheduled_date, due_at, remind_at, project_id,
+                  project_order, priority, parent_id, recurrence, recurrence_start,
+                  created_at, updated_at, sort_order)
+             VALUES (?1, 0, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11, ?12) RETURNING id",
+            params![
+                text,
+                scheduled_date,
+                due_at,
+                remind_at,
+                new.project_id,
+                project_order,
+                new.priority,
+                new.parent_id,
+                recurrence,
+                recurrence.as_ref().map(|_| &scheduled_date),
+                now(),
+                next_sort
+            ],
+            |row| row.get(0),
+        )?;
+        tags::add(&tx, id, &tags)?;
+        let todo = get_todo(&tx, id)?;
+        tx.commit()?;
+        debug!(id, "added todo");
+        Ok(todo)
+    }
+
+    pub fn update_todo(&self, id: i64, update: TodoUpdate) -> Result<Todo> {
+        if update.text.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return Err(Error::EmptyText);
+        }
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let now = now();
+        let changed = tx.execute(
+            "UPDATE todos SET
+                 text = COALESCE(?2, text),
+                 completed = COALESCE(?3, completed),
+                 completed_at = CASE WHEN COALESCE(?3, completed)
+                     THEN COALESCE(completed_at, ?4) END,
+                 updated_at = ?4
+             WHERE id = ?1",
+            params![
+                id,
+                update.text.as_deref().map(str::trim),
+                update.completed,
+                now
+            ],
+        )?;
+        if changed == 0 {
+            return Err(Error::NotFound(id));
+        }
+        if update.completed == Some(true) {
+            subtasks::complete_descendants(&tx, id, &now)?;
+            recurrence::advance(&tx, id, &today(&tx)?)?;
+        }
+        let todo = get_todo(&tx, id)?;
+        tx.commit()?;
+        Ok(todo)
+    }
+
+    /// Flips completion and moves the todo to the other group of its day.
+    #[tracing::instrument(skip(self))]
+    pub fn toggle_todo(&self, id: i64) -> Result<Todo> {
+        self.change_completion(id, None)
+    }
+
+    /// Like [`Store::toggle_todo`] but idempotent, for callers that know the
+    /// state they want.
+    #[tracing::instrument(skip(self))]
+    pub fn set_completed(&self, id: i64, completed: bool) -> Result<Todo> {
+        self.change_completion(id, Some(completed))
+    }
+
+    fn change_completion(&self, id: i64, completed: Option<bool>) -> Result<Todo> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let now = now();
+        let (day, parent, completed): (String, Option<i64>, bool) = tx
+            .query_row(
+                "UPDATE todos SET
+                     completed = COALESCE(?2, NOT completed),
+                     completed_at = CASE WHEN COALESCE(?2, NOT completed)
+                         THEN COALESCE(completed_at, ?3) END,
+                     updated_at = ?3
+                 WHERE id = ?1 RETURNING scheduled_date, parent_id, completed",
+                params![id, completed, now],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+            .ok_or(Error::NotFound(id))?;
+        if completed {
+            subtasks::complete_descendants(&tx, id, &now)?;
+            recurrence::advance(&tx, id, &today(&tx)?)?;
+        }
+        renumber(&tx, &day, parent)?;
+        let todo = get_todo(&tx, id)?;
+        tx.commit()?;
+        Ok(todo)
+    }
+
+    /// Sets or clears the deadline; see [`parse_due`] for accepted formats.
+    #[tracing::instrument(skip(self))]
+    pub fn set_due(&self, id: i64, due_at: Option<&str>) -> Result<Todo> {
+        let due_at = due_at.map(parse_due).transpose()?;
+        let conn = self.conn();
+        let changed = conn.execute(
+            "UPDATE todos SET due_at = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id, due_at, now()],
+        )?;
+        if changed == 0 {
+            return Err(Error::NotFound(id));
+        }
+        get_todo(&conn, id)
+    }
+
+    /// Incomplete todos whose deadline has passed, most overdue first.
+    pub fn overdue_todos(&self) -> Result<Vec<Todo>> {
+        let conn = self.conn();
+        query_todos(
+            &conn,
+            "SELECT * FROM todos WHERE completed = 0 AND due_at < ?1
+             ORDER BY due_at ASC, sort_order ASC",
+            [now()],
+        )
+    }
+
+    /// Returns whether a todo was actually removed. Files attached to it or
+    /// its subtasks are removed with it.
+    pub fn delete_todo(&self, id: i64) -> Result<bool> {
+        let conn = self.conn();
+        let removed = conn.execute("DELETE FROM todos WHERE id = ?1", [id])?;
+        if removed > 0 {
+            attachments::collect_garbage(&conn, self.attachments_dir.as_deref())?;
+        }
+        Ok(removed > 0)
+    }
+
+    /// Deletes completed todos, except those still holding an unfinished
+    /// subtask somewhere below them.
+    pub fn clear_completed(&self) -> Result<usize> {
+        let conn = self.conn();
+        let removed = conn.execute(
+            "WITH RECURSIVE open_ancestors (id) AS (
+                 SELECT parent_id FROM todos WHERE completed = 0 AND parent_id IS NOT NULL
+                 UNION
+                 SELECT todos.parent_id FROM todos
+                 JOIN open_ancestors ON todos.id = open_ancestors.id
+                 WHERE todos.parent_id IS NOT NULL
+             )
+             DELETE FROM todos WHERE completed = 1 AND id NOT IN open_ancestors",
+            [],
+        )?;
+        if removed > 0 {
+            attachments::collect_garbage(&conn, self.attachments_dir.as_deref())?;
+        }
+        Ok(removed)
+    }
+
+    /// Applies a drag-and-drop order. Incomplete todos keep coming first, so
+    /// the ids are split by completion before numbering; unknown ids are
+    /// ignored. When sorting by priority, todos are also grouped by priority
+    /// as the list shows them, so the manual order stays consistent with what
+    /// the user saw.
+    #[tracing::instrument(skip(self, ids), fields(count = ids.len()))]
+    pub fn reorder_todos(&self, ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut incomplete = Vec::new();
+        let mut completed = Vec::new();
+        {
+            let mut stmt = tx.prepare("SELECT completed, priority FROM todos WHERE id = ?1")?;
+            for &id in ids {
+                match stmt
+                    .query_row([id], |row| Ok((row.get::<_, bool>(0)?, row.get(1)?)))
+                    .optional()?
+                {
+                    Some((true, priority)) => completed.push((id, priority)),
+                    Some((false, priority)) => incomplete.push((id, priority)),
+                    None => {}
+                }
+            }
+        }
+        let mode = priority::sort_mode(&tx)?;
+        mode.arrange(&mut incomplete);
+        mode.arrange(&mut completed);
+        write_order(&tx, incomplete.iter().chain(&completed).map(|(id, _)| id))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Days up to and including today that have todos, newest first.
+    pub fn historical_dates(&self) -> Result<Vec<String>> {
+        self.dates(
+            "SELECT DISTINCT scheduled_date FROM todos
+             WHERE scheduled_date <= ?1
+             ORDER BY scheduled_date DESC",
+        )
+    }
+
+    /// Days after today that have todos, or will have once repeating todos
+    /// come back, soonest first.
+    pub fn future_dates(&self) -> Result<Vec<String>> {
+        let mut dates = self.dates(
+            "SELECT DISTINCT scheduled_date FROM todos
+             WHERE scheduled_date > ?1
+             ORDER BY scheduled_date ASC",
+        )?;
+        dates.extend(recurrence::projected_dates(&self.conn())?);
+        dates.sort_unstable();
+        dates.dedup();
+        Ok(dates)
+    }
+
+    /// Counter that changes whenever another connection, such as the CLI,
+    /// commits to the database.
+    pub fn data_version(&self) -> Result<i64> {
+        let conn = self.conn();
+        Ok(conn.query_row("PRAGMA data_version", [], |row| row.get(0))?)
+    }
+
+    /// The logical day it is now; see [`days`].
+    pub fn today(&self) -> Result<String> {
+        today(&self.conn())
+    }
+
+    /// Value of an app setting, or `None` while it is left at its default.
+    pub fn setting(&self, key: &str) -> Result<Option<String>> {
+        setting(&self.conn(), key)
+    }
+
+    /// Every setting that differs from its default, ordered by key.
+    pub fn settings(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(settings)
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        put_setting(&self.conn(), key, value)
+    }
+
+    /// Puts a setting back to its default. Returns whether it was set.
+    pub fn reset_setting(&self, key: &str) -> Result<bool> {
+        let conn = self.conn();
+        Ok(conn.execute("DELETE FROM settings WHERE key = ?1", [key])? > 0)
+    }
+
+    /// Runs a query for distinct days, relative to today as `?1`.
+    fn dates(&self, sql: &str) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let today = today(&conn)?;
+        let mut stmt = conn.prepare_cached(sql)?;
+        let dates = stmt
+            .query_map([today], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(dates)
+    }
+}
+
+fn get_todo(conn: &Connection, id: i64) -> Result<Todo> {
+    let mut todo = conn
+        .query_row("SELECT * FROM todos WHERE id = ?1", [id], Todo::from_row)
+        .optional()?
+        .ok_or(Error::NotFound(id))?;
+    tags::attach(conn, std::slice::from_mut(&mut todo))?;
+    subtasks::attach(conn, std::slice::from_mut(&mut todo))?;
+    Ok(todo)
+}
+
+/// Runs a `SELECT * FROM todos` query and fills in what lives in other
+/// tables.
+fn query_todos(conn: &Connection, sql: &str, params: impl Params) -> Result<Vec<Todo>> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut todos = stmt
+        .query_map(params, Todo::from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    tags::attach(conn, &mut todos)?;
+    subtasks::attach(conn, &mut todos)?;
+    Ok(todos)
+}
+
+/// Renumbers the subtasks of `parent`, or the top-level todos of `day`, so
+/// incomplete todos precede completed ones while keeping their relative
+/// order.
+fn renumber(conn: &Connection, day: &str, parent: Option<i64>) -> Result<()> {
+    write_order(conn, &subtasks::sibling_ids(conn, parent, day)?)
+}
+
+fn write_order<'a>(conn: &Connection, ids: impl IntoIterator<Item = &'a i64>) -> Result<()> {
+    let mut stmt = conn.prepare_cached("UPDATE todos SET sort_order = ?1 WHERE id = ?2")?;
+    for (position, id) in ids.into_iter().enumerate() {
+        stmt.execute(params![position as i64, id])?;
+    }
+    Ok(())
+}
+
+pub fn parse_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| Error::InvalidDate(date.to_owned()))
+}
+
+/// Parses a deadline given either as RFC 3339 or as `YYYY-MM-DD HH:MM`
+/// (a `T` separator works too) in local time, and returns it in the UTC
+/// format stored in `due_at`.
+pub fn parse_due(value: &str) -> Result<String> {
+    let value = value.trim();
+    let invalid = || Error::InvalidDateTime(value.to_owned());
+    let due = match DateTime::parse_from_rfc3339(value) {
+        Ok(due) => due.with_timezone(&Utc),
+        Err(_) => {
+            let naive = ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .ok_or_else(invalid)?;
+            Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .ok_or_else(invalid)?
+                .with_timezone(&Utc)
+        }
+    };
+    Ok(timestamp(due))
+}
+
+fn setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let value = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    Ok(value)
+}
+
+fn put_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+/// Today in the local time zone, honouring the configured day start hour.
+/// A stored hour that no longer parses counts as midnight.
+fn today(conn: &Connection) -> Result<String> {
+    let start_hour = setting(conn, days::START_HOUR_SETTING)?
+        .and_then(|hour| days::parse_start_hour(&hour).ok())
+        .unwrap_or(0);
+    Ok(days::day_of(Utc::now(), &Local, start_hour).to_string())
+}
+
+fn now() -> String {
+    timestamp(Utc::now())
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
@@ -0,0 +1,552 @@
+//! Files attached to todos.
+//!
+//! Attached files are copied into a content-addressed folder next to the
+//! database, named by the SHA-256 of their contents plus the original
+//! extension so the OS still knows how to open them. Attaching the same file
+//! twice stores it once. The `attachments` table maps todos to blobs; a blob
+//! no row points at any more is removed by [`Store::collect_garbage`], which
+//! runs after every deletion that can orphan one.
+//!
+//! The folder is named after the database, `rtodo.attachments` for
+//! `rtodo.db`, so it is never one the user keeps files in, and garbage
+//! collection only ever deletes files named like a blob. An in-memory
+//! database has no folder and cannot hold attachments.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use super::{now, Error, Result, Store};
+use crate::DB_FILE;
+
+/// Extension of the blob folder, which takes the name of the database file.
+const DIR_EXTENSION: &str = "attachments";
+
+/// Shared blob folder of earlier versions, whose blobs are moved over.
+const LEGACY_DIR: &str = "attachments";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Attachment {
+    pub id: i64,
+    pub todo_id: i64,
+    /// File name the attachment was added with.
+    pub name: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Blob file name inside the folder of [`dir_for`].
+    pub blob: String,
+    pub created_at: String,
+}
+
+impl Attachment {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            todo_id: row.get("todo_id")?,
+            name: row.get("name")?,
+            size: row.get("size")?,
+            blob: row.get("blob")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+impl Store {
+    /// Copies the file at `source` into the blob folder and attaches it to
+    /// todo `todo_id`.
+    #[tracing::instrument(skip(self))]
+    pub fn attach_file(&self, todo_id: i64, source: &Path) -> Result<Attachment> {
+        let name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| Error::InvalidAttachment(source.display().to_string()))?;
+        if !source.is_file() {
+            return Err(Error::InvalidAttachment(source.display().to_string()));
+        }
+        let dir = self.blob_dir()?;
+        // Fail before copying anything when the todo is gone.
+        self.get_todo(todo_id)?;
+        let staged = stage_blob(dir, source)?;
+
+        // The blob is moved into place under the connection lock, which
+        // garbage collection also holds, so it cannot be collected before
+        // its row exists.
+        let conn = self.conn();
+        let blob = staged.commit()?;
+        let inserted = conn
+            .query_row(
+                "INSERT INTO attachments (todo_id, name, size, blob, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 RETURNING *",
+                params![todo_id, name, blob.size, blob.name, now()],
+                Attachment::from_row,
+            )
+            .map_err(Error::from);
+        if inserted.is_err() {
+            // The todo was deleted meanwhile; do not leave the copy behind.
+            collect_garbage(&conn, Some(dir))?;
+        }
+        inserted
+    }
+
+    /// Attachments of a todo, oldest first.
+    pub fn attachments(&self, todo_id: i64) -> Result<Vec<Attachment>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT * FROM attachments WHERE todo_id = ?1 ORDER BY created_at, id",
+        )?;
+        let rows = stmt.query_map([todo_id], Attachment::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Where the contents of an attachment live on disk.
+    pub fn attachment_path(&self, id: i64) -> Result<PathBuf> {
+        let conn = self.conn();
+        let attachment = find(&conn, id)?;
+        Ok(self.blob_dir()?.join(attachment.blob))
+    }
+
+    /// Detaches a file, removing its blob when nothing else uses it. Returns
+    /// the attachment, or `None` when it did not exist.
+    #[tracing::instrument(skip(self))]
+    pub fn remove_attachment(&self, id: i64) -> Result<Option<Attachment>> {
+        let conn = self.conn();
+        let removed = conn
+            .query_row(
+                "DELETE FROM attachments WHERE id = ?1 RETURNING *",
+                [id],
+                Attachment::from_row,
+            )
+            .optional()?;
+        if removed.is_some() {
+            collect_garbage(&conn, self.attachments_dir.as_deref())?;
+        }
+        Ok(removed)
+    }
+
+    /// Removes blobs no attachment refers to, returning how many went.
+    pub fn collect_garbage(&self) -> Result<usize> {
+        collect_garbage(&self.conn(), self.attachments_dir.as_deref())
+    }
+
+    /// The blob folder, which an in-memory database does not have.
+    pub(super) fn blob_dir(&self) -> Result<&Path> {
+        self.attachments_dir
+            .as_deref()
+            .ok_or(Error::NoAttachmentFolder)
+    }
+
+    /// Writes a consistent copy of the database and every attachment blob
+    /// into `dir`, laid out like the app data directory so it can be
+    /// restored by copying it back.
+    #[tracing::instrument(skip(self))]
+    pub fn backup(&self, dir: &Path) -> Result<()> {
+        let database = dir.join(DB_FILE);
+        if database.exists() {
+            return Err(Error::BackupExists(database.display().to_string()));
+        }
+        let target = dir_for(&database);
+        fs::create_dir_all(&target)?;
+        let conn = self.conn();
+        conn.execute("VACUUM INTO ?1", [database.to_string_lossy()])?;
+        let blobs = blob_names(&conn)?;
+        if let Some(source) = self.attachments_dir.as_deref() {
+            for blob in blobs {
+                fs::copy(source.join(&blob), target.join(&blob))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The blob folder of the database file at `database`.
+pub fn dir_for(database: &Path) -> PathBuf {
+    let stem = database.file_stem().unwrap_or_default().to_string_lossy();
+    database.with_file_name(format!("{stem}.{DIR_EXTENSION}"))
+}
+
+/// Moves the blobs of a database out of the `attachments` folder earlier
+/// versions kept next to it into `dir`. Only files attachment rows name are
+/// moved; whatever else is in that folder stays where it is.
+pub(super) fn adopt_legacy(conn: &Connection, database: &Path, dir: &Path) -> Result<()> {
+    let legacy = database.with_file_name(LEGACY_DIR);
+    if legacy == dir || !legacy.is_dir() {
+        return Ok(());
+    }
+    let mut moved = 0;
+    for blob in blob_names(conn)? {
+        let (from, to) = (legacy.join(&blob), dir.join(&blob));
+        if !is_blob_name(&blob) || to.exists() || !from.is_file() {
+            continue;
+        }
+        fs::create_dir_all(dir)?;
+        fs::rename(from, to)?;
+        moved += 1;
+    }
+    if moved > 0 {
+        info!(moved, dir = %dir.display(), "moved attachment blobs into their own folder");
+    }
+    Ok(())
+}
+
+fn blob_names(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT blob FROM attachments")?;
+    let blobs = stmt.query_map([], |row| row.get(0))?;
+    Ok(blobs.collect::<rusqlite::Result<_>>()?)
+}
+
+fn find(conn: &Connection, id: i64) -> Result<Attachment> {
+    conn.query_row(
+        "SELECT * FROM attachments WHERE id = ?1",
+        [id],
+        Attachment::from_row,
+    )
+    .optional()?
+    .ok_or(Error::AttachmentNotFound(id))
+}
+
+/// A file copied into the blob folder under a temporary name.
+struct Staged {
+    temp: PathBuf,
+    target: PathBuf,
+    name: String,
+    size: u64,
+}
+
+impl Staged {
+    /// Moves the copy into place, unless an identical blob is already there.
+    fn commit(self) -> Result<Self> {
+        if self.target.exists() {
+            fs::remove_file(&self.temp)?;
+        } else {
+            fs::rename(&self.temp, &self.target)?;
+            debug!(blob = %self.name, size = self.size, "stored attachment blob");
+        }
+        Ok(self)
+    }
+}
+
+/// Copies `source` into `dir`, hashing it on the way to find its blob name.
+fn stage_blob(dir: &Path, source: &Path) -> Result<Staged> {
+    fs::create_dir_all(dir)?;
+    let temp = temp_path(dir);
+    let mut hasher = Sha256::new();
+    let copied = File::open(source).and_then(|file| {
+        let mut writer = HashingWriter {
+            file: File::create(&temp)?,
+            hasher: &mut hasher,
+        };
+        io::copy(&mut BufReader::new(file), &mut writer)
+    });
+    let size = match copied {
+        Ok(size) => size,
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+    };
+
+    let name = blob_name(&format!("{:x}", hasher.finalize()), source);
+    Ok(Staged {
+        target: dir.join(&name),
+        temp,
+        name,
+        size,
+    })
+}
+
+/// Writes `data`, the contents of a file called `file_name`, into `dir`
+/// unless an identical blob is already there, and returns the blob name.
+pub(super) fn write_blob(dir: &Path, file_name: &str, data: &[u8]) -> Result<String> {
+    let name = blob_name(&format!("{:x}", Sha256::digest(data)), Path::new(file_name));
+    let target = dir.join(&name);
+    if !target.exists() {
+        fs::create_dir_all(dir)?;
+        let temp = temp_path(dir);
+        if let Err(e) = fs::write(&temp, data).and_then(|()| fs::rename(&temp, &target)) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        debug!(blob = %name, size = data.len(), "stored attachment blob");
+    }
+    Ok(name)
+}
+
+/// A fresh name in `dir` for a blob being written. Dot files are never
+/// collected as garbage.
+fn temp_path(dir: &Path) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    dir.join(format!(
+        ".incoming-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Whether `name` is one [`blob_name`] makes: 64 lowercase hex digits,
+/// optionally followed by a lowercase alphanumeric extension.
+fn is_blob_name(name: &str) -> bool {
+    let (hash, extension) = match name.split_once('.') {
+        Some((hash, extension)) => (hash, Some(extension)),
+        None => (name, None),
+    };
+    hash.len() == 64
+        && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        && extension.is_none_or(|ext| {
+            !ext.is_empty() && ext.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'z'))
+        })
+}
+
+/// The SHA-256 `hash` plus the lowercased extension of `file_name`, when it
+/// has a plain one.
+fn blob_name(hash: &str, file_name: &Path) -> String {
+    let extension = file_name
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()));
+    match extension {
+        Some(ext) => format!("{hash}.{ext}"),
+        None => hash.to_owned(),
+    }
+}
+
+struct HashingWriter<'a> {
+    file: File,
+    hasher: &'a mut Sha256,
+}
+
+impl Write for HashingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Deletes blobs in `dir` that no attachment row names. Files not named
+/// like a blob, such as temporary files of copies in progress, are left
+/// alone, and without a folder there is nothing to do.
+pub(super) fn collect_garbage(conn: &Connection, dir: Option<&Path>) -> Result<usize> {
+    let Some(dir) = dir else {
+        return Ok(0);
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    let mut stmt =
+        conn.prepare_cached("SELECT EXISTS (SELECT 1 FROM attachments WHERE blob = ?1)")?;
+    let mut removed = 0;
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !is_blob_name(&name) || !entry.file_type()?.is_file() {
+            continue;
+        }
+        if !stmt.query_row([&name], |row| row.get::<_, bool>(0))? {
+            match fs::remove_file(entry.path()) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!(blob = %name, error = %e, "failed to remove orphaned attachment"),
+            }
+        }
+    }
+    if removed > 0 {
+        debug!(removed, "collected orphaned attachment blobs");
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::store::testing::{add, store, store_in, todo};
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Names of the files in `dir`, sorted.
+    fn files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn attaching_the_same_contents_twice_stores_one_blob() {
+        let tmp = TempDir::new().unwrap();
+        let store = store_in(tmp.path());
+        let first = add(&store, todo("Plan trip", "2026-10-20"));
+        let second = add(&store, todo("Pack", "2026-10-20"));
+        let source = write(tmp.path(), "Tickets.PDF", "seat 12A");
+
+        let a = store.attach_file(first.id, &source).unwrap();
+        let b = store.attach_file(second.id, &source).unwrap();
+        assert_eq!(a.name, "Tickets.PDF");
+        assert_eq!(a.size, 8);
+        assert_eq!(a.blob, b.blob);
+        assert!(is_blob_name(&a.blob) && a.blob.ends_with(".pdf"));
+
+        let dir = dir_for(&tmp.path().join(DB_FILE));
+        assert_eq!(dir, tmp.path().join("rtodo.attachments"));
+        assert_eq!(files(&dir), [a.blob.as_str()]);
+        let path = store.attachment_path(a.id).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "seat 12A");
+        assert!(matches!(
+            store.attach_file(first.id, tmp.path()),
+            Err(Error::InvalidAttachment(_))
+        ));
+    }
+
+    #[test]
+    fn blobs_go_with_their_last_attachment() {
+        let tmp = TempDir::new().unwrap();
+        let store = store_in(tmp.path());
+        let dir = dir_for(&tmp.path().join(DB_FILE));
+        let first = add(&store, todo("Plan trip", "2026-10-20"));
+        let second = add(&store, todo("Pack", "2026-10-20"));
+        let source = write(tmp.path(), "tickets.pdf", "seat 12A");
+        let a = store.attach_file(first.id, &source).unwrap();
+        let b = store.attach_file(second.id, &source).unwrap();
+
+        assert_eq!(store.remove_attachment(a.id).unwrap(), Some(a.clone()));
+        assert_eq!(files(&dir), [b.blob.as_str()]);
+        assert_eq!(store.remove_attachment(a.id).unwrap(), None);
+        store.delete_todo(second.id).unwrap();
+        assert!(files(&dir).is_empty());
+        // The source file is only ever copied.
+        assert!(source.is_file());
+    }
+
+    #[test]
+    fn garbage_collection_leaves_foreign_files_alone() {
+        let tmp = TempDir::new().unwrap();
+        // A folder of the user's own that the blob folder used to share a
+        // name with.
+        let legacy = tmp.path().join(LEGACY_DIR);
+        fs::create_dir(&legacy).unwrap();
+        write(&legacy, "thesis.pdf", "chapter 1");
+        let store = store_in(tmp.path());
+        let todo = add(&store, todo("Plan trip", "2026-10-20"));
+        let source = write(tmp.path(), "tickets.pdf", "seat 12A");
+        let kept = store.attach_file(todo.id, &source).unwrap();
+
+        let dir = dir_for(&tmp.path().join(DB_FILE));
+        let orphan = format!("{}.pdf", "0".repeat(64));
+        for name in [
+            "thesis.pdf",
+            ".incoming-1-1",
+            &"A".repeat(64),
+            &format!("{}.PDF", "0".repeat(64)),
+            &orphan,
+        ] {
+            write(&dir, name, "mine");
+        }
+        assert_eq!(store.collect_garbage().unwrap(), 1);
+        let mut expected = vec![
+            ".incoming-1-1".to_owned(),
+            format!("{}.PDF", "0".repeat(64)),
+            "A".repeat(64),
+            kept.blob,
+            "thesis.pdf".to_owned(),
+        ];
+        expected.sort();
+        assert_eq!(files(&dir), expected);
+        assert_eq!(files(&legacy), ["thesis.pdf"]);
+    }
+
+    #[test]
+    fn in_memory_stores_have_no_blob_folder() {
+        let tmp = TempDir::new().unwrap();
+        let store = store();
+        let todo = add(&store, todo("Plan trip", "2026-10-20"));
+        let source = write(tmp.path(), "tickets.pdf", "seat 12A");
+        assert!(matches!(
+            store.attach_file(todo.id, &source),
+            Err(Error::NoAttachmentFolder)
+        ));
+        assert_eq!(store.collect_garbage().unwrap(), 0);
+        assert!(store.delete_todo(todo.id).unwrap());
+    }
+
+    #[test]
+    fn backups_restore_with_their_blobs() {
+        let tmp = TempDir::new().unwrap();
+        let store = store_in(tmp.path());
+        let todo = add(&store, todo("Plan trip", "2026-10-20"));
+        let source = write(tmp.path(), "tickets.pdf", "seat 12A");
+        let attachment = store.attach_file(todo.id, &source).unwrap();
+
+        let backup = tmp.path().join("backup");
+        store.backup(&backup).unwrap();
+        assert!(matches!(store.backup(&backup), Err(Error::BackupExists(_))));
+        let restored = store_in(&backup);
+        assert_eq!(
+            restored.attachments(todo.id).unwrap(),
+            std::slice::from_ref(&attachment)
+        );
+        let path = restored.attachment_path(attachment.id).unwrap();
+        assert!(path.starts_with(backup.join("rtodo.attachments")));
+        assert_eq!(fs::read_to_string(path).unwrap(), "seat 12A");
+    }
+
+    #[test]
+    fn blobs_move_out_of_the_legacy_folder() {
+        let tmp = TempDir::new().unwrap();
+        let store = store_in(tmp.path());
+        let todo = add(&store, todo("Plan trip", "2026-10-20"));
+        let source = write(tmp.path(), "tickets.pdf", "seat 12A");
+        let attachment = store.attach_file(todo.id, &source).unwrap();
+        drop(store);
+
+        let dir = dir_for(&tmp.path().join(DB_FILE));
+        let legacy = tmp.path().join(LEGACY_DIR);
+        fs::rename(&dir, &legacy).unwrap();
+        write(&legacy, "thesis.pdf", "chapter 1");
+
+        let store = store_in(tmp.path());
+        assert_eq!(files(&dir), [attachment.blob.as_str()]);
+        assert_eq!(files(&legacy), ["thesis.pdf"]);
+        let path = store.attachment_path(attachment.id).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "seat 12A");
+    }
+
+    #[test]
+    fn blob_names_are_hashes_with_plain_extensions() {
+        let hash = "0123456789abcdef".repeat(4);
+        assert!(is_blob_name(&hash));
+        assert!(is_blob_name(&format!("{hash}.pdf")));
+        assert!(is_blob_name(&format!("{hash}.mp4")));
+        assert!(!is_blob_name(&format!("{hash}.")));
+        assert!(!is_blob_name(&format!("{hash}.PDF")));
+        assert!(!is_blob_name(&format!("{hash}.tar.gz")));
+        assert!(!is_blob_name(&hash.to_uppercase()));
+        assert!(!is_blob_name(&hash[1..]));
+        assert!(!is_blob_name("thesis.pdf"));
+        assert_eq!(blob_name(&hash, Path::new("notes.")), hash);
+        assert_eq!(
+            blob_name(&hash, Path::new("Scan.JPEG")),
+            format!("{hash}.jpeg")
+        );
+        assert_eq!(blob_name(&hash, Path::new("archive.tar-gz!")), hash);
+    }
+}
@@ -0,0 +1,397 @@
+//! Carrying unfinished todos from past days over to today.
+//!
+//! Runs at most once per logical day: the app calls it at startup and when
+//! the day rolls over while it is running, the CLI before every command.
+//! Whichever comes first on a new day does the work.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rusqlite::{params, Connection};
+use tracing::info;
+
+use super::{now, projects, put_setting, recurrence, setting, today, Error, Result, Store};
+
+/// Settings key holding the [`Mode`].
+pub const SETTING: &str = "carry_over";
+/// Last day carry-over ran for, so it never runs twice on the same day.
+const LAST_RUN_SETTING: &str = "carry_over_last_run";
+
+/// What happens to incomplete todos of past days when a new day starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Reschedule them onto today.
+    #[default]
+    Move,
+    /// Leave them where they are and add a linked copy to today.
+    Copy,
+    /// Leave them where they are.
+    Leave,
+}
+
+impl FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "move" => Ok(Self::Move),
+            "copy" => Ok(Self::Copy),
+            "leave" => Ok(Self::Leave),
+            _ => Err(Error::InvalidCarryOver(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Move => "move",
+            Self::Copy => "copy",
+            Self::Leave => "leave",
+        })
+    }
+}
+
+impl Store {
+    /// Carries unfinished todos of past days over to today according to the
+    /// configured [`Mode`], unless that already happened today. Returns how
+    /// many todos were moved or copied.
+    #[tracing::instrument(skip(self))]
+    pub fn carry_over(&self) -> Result<usize> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let today = today(&tx)?;
+        // Days are YYYY-MM-DD, so they compare as strings. A clock that went
+        // backwards must not pull todos from "future" days either.
+        if setting(&tx, LAST_RUN_SETTING)?.is_some_and(|last| last >= today) {
+            return Ok(0);
+        }
+        // Repeating todos left behind come back first, so their missed
+        // instances are carried over alongside the new ones.
+        let repeated = recurrence::advance_stale(&tx, &today)?;
+        // An unreadable mode is treated as "leave" rather than moving
+        // todos the user did not ask to move.
+        let mode = setting(&tx, SETTING)?
+            .map(|mode| mode.parse().unwrap_or(Mode::Leave))
+            .unwrap_or_default();
+        let carried = match mode {
+            Mode::Move => move_stale(&tx, &today)?,
+            Mode::Copy => copy_stale(&tx, &today)?,
+            Mode::Leave => 0,
+        };
+        put_setting(&tx, LAST_RUN_SETTING, &today)?;
+        tx.commit()?;
+        if carried > 0 {
+            info!(%mode, carried, %today, "carried over unfinished todos");
+        }
+        if repeated > 0 {
+            info!(repeated, %today, "repeated todos left on past days");
+        }
+        Ok(carried)
+    }
+}
+
+/// Incomplete top-level todos of days before `today` in the order they
+/// should appear on today, oldest day first. Subtasks travel with them.
+fn stale_ids(conn: &Connection, today: &str, uncopied_only: bool) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM todos AS t
+         WHERE completed = 0 AND scheduled_date < ?1 AND parent_id IS NULL
+           AND (?2 = 0 OR NOT EXISTS (SELECT 1 FROM todos WHERE carried_from = t.id))
+         ORDER BY scheduled_date ASC, sort_order ASC",
+    )?;
+    let ids = stmt.query_map(params![today, uncopied_only], |row| row.get(0))?;
+    Ok(ids.collect::<rusqlite::Result<_>>()?)
+}
+
+/// First free position after today's incomplete todos.
+fn next_sort(conn: &Connection, today: &str) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(sort_order), 0) + 1 FROM todos
+         WHERE completed = 0 AND scheduled_date = ?1 AND parent_id IS NULL",
+        [today],
+        |row| row.get(0),
+    )?)
+}
+
+fn move_stale(conn: &Connection, today: &str) -> Result<usize> {
+    let ids = stale_ids(conn, today, false)?;
+    let mut stmt = conn.prepare(
+        "UPDATE todos SET
+             original_date = COALESCE(original_date, scheduled_date),
+             scheduled_date = ?2,
+             sort_order = ?3,
+             updated_at = ?4
+         WHERE id = ?1",
+    )?;
+    let mut move_subtasks = conn.prepare(
+        "WITH RECURSIVE subtree (id) AS (
+             SELECT id FROM todos WHERE parent_id = ?1
+             UNION ALL
+             SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id
+         )
+         UPDATE todos SET scheduled_date = ?2 WHERE id IN subtree",
+    )?;
+    let now = now();
+    for (sort_order, id) in (next_sort(conn, today)?..).zip(&ids) {
+        stmt.execute(params![id, today, sort_order, now])?;
+        move_subtasks.execute(params![id, today])?;
+    }
+    Ok(ids.len())
+}
+
+/// Copies keep the day the task was first planned for, their project, tags,
+/// reminder and unfinished subtasks, and point back at the todo they were
+/// copied from. The reminder moves to the copy rather than going off twice.
+/// A todo is copied only once, so the original left on its day is not
+/// copied again the next day; an unfinished copy is.
+fn copy_stale(conn: &Connection, today: &str) -> Result<usize> {
+    let ids = stale_ids(conn, today, true)?;
+    let now = now();
+    for (sort_order, &id) in (next_sort(conn, today)?..).zip(&ids) {
+        copy_todo(conn, id, None, today, sort_order, &now)?;
+    }
+    Ok(ids.len())
+}
+
+fn copy_todo(
+    conn: &Connection,
+    id: i64,
+    parent: Option<i64>,
+    today: &str,
+    sort_order: i64,
+    now: &str,
+) -> Result<()> {
+    let project_id: Option<i64> =
+        conn.query_row("SELECT project_id FROM todos WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })?;
+    let project_order = match project_id {
+        Some(project_id) => projects::next_order(conn, project_id)?,
+        None => 0,
+    };
+    conn.prepare_cached(
+        "INSERT INTO todos
+             (text, notes, completed, scheduled_date, due_at, remind_at, reminded_at,
+              project_id, project_order, priority, parent_id, original_date, carried_from,
+              created_at, updated_at, sort_order)
+         SELECT text, notes, 0, ?2, due_at, remind_at, reminded_at, project_id, ?6, priority,
+                ?5, COALESCE(original_date, scheduled_date), id, ?4, ?4, ?3
+         FROM todos WHERE id = ?1",
+    )?
+    .execute(params![id, today, sort_order, now, parent, project_order])?;
+    let copy = conn.last_insert_rowid();
+    conn.prepare_cached("UPDATE todos SET remind_at = NULL, reminded_at = NULL WHERE id = ?1")?
+        .execute([id])?;
+    conn.prepare_cached(
+        "INSERT INTO todo_tags (todo_id, tag_id)
+         SELECT ?2, tag_id FROM todo_tags WHERE todo_id = ?1",
+    )?
+    .execute(params![id, copy])?;
+    // Copies share the original's blobs.
+    conn.prepare_cached(
+        "INSERT INTO attachments (todo_id, name, size, blob, created_at)
+         SELECT ?2, name, size, blob, created_at FROM attachments WHERE todo_id = ?1",
+    )?
+    .execute(params![id, copy])?;
+
+    let children = {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id FROM todos WHERE parent_id = ?1 AND completed = 0 ORDER BY sort_order",
+        )?;
+        let ids = stmt.query_map([id], |row| row.get::<_, i64>(0))?;
+        ids.collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    for (position, child) in (0..).zip(children) {
+        copy_todo(conn, child, Some(copy), today, position, now)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Days, NaiveDate};
+
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::{NewTodo, Todo, TodoNode};
+
+    /// The day `days` before today.
+    fn days_ago(store: &Store, days: u64) -> String {
+        let today: NaiveDate = store.today().unwrap().parse().unwrap();
+        (today - Days::new(days)).to_string()
+    }
+
+    fn carry_over(store: &Store, mode: Mode) -> usize {
+        store.set_setting(SETTING, &mode.to_string()).unwrap();
+        store.carry_over().unwrap()
+    }
+
+    fn texts(nodes: &[TodoNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.todo.text.as_str()).collect()
+    }
+
+    fn get(store: &Store, todo: &Todo) -> Todo {
+        store.get_todo(todo.id).unwrap()
+    }
+
+    #[test]
+    fn moves_unfinished_todos_with_their_subtasks() {
+        let store = store();
+        let (older, yesterday) = (days_ago(&store, 2), days_ago(&store, 1));
+        let today = store.today().unwrap();
+        add(&store, todo("Already today", &today));
+        let trip = add(&store, todo("Plan trip", &yesterday));
+        let hotel = NewTodo {
+            parent_id: Some(trip.id),
+            ..todo("Book hotel", &yesterday)
+        };
+        add(&store, hotel);
+        let bank = add(&store, todo("Call the bank", &older));
+        let done = add(&store, todo("Water plants", &yesterday));
+        store.set_completed(done.id, true).unwrap();
+
+        assert_eq!(carry_over(&store, Mode::Move), 2);
+        let tree = store.todo_tree(&today).unwrap();
+        assert_eq!(
+            texts(&tree),
+            ["Already today", "Call the bank", "Plan trip"]
+        );
+        assert_eq!(texts(&tree[2].children), ["Book hotel"]);
+        assert_eq!(get(&store, &bank).original_date, Some(older));
+        assert_eq!(get(&store, &trip).original_date, Some(yesterday.clone()));
+        assert_eq!(get(&store, &done).scheduled_date, yesterday);
+    }
+
+    #[test]
+    fn copies_keep_what_the_original_had() {
+        let store = store();
+        let yesterday = days_ago(&store, 1);
+        let today = store.today().unwrap();
+        let project = store.create_project("Travel", None, None).unwrap();
+        let trip = NewTodo {
+            project_id: Some(project.id),
+            tags: vec!["home".to_owned()],
+            remind_at: Some("2099-01-01T09:00:00Z".to_owned()),
+            ..todo("Plan trip", &yesterday)
+        };
+        let trip = add(&store, trip);
+        let hotel = NewTodo {
+            parent_id: Some(trip.id),
+            ..todo("Book hotel", &yesterday)
+        };
+        add(&store, hotel);
+        let train = NewTodo {
+            parent_id: Some(trip.id),
+            ..todo("Book train", &yesterday)
+        };
+        let train = add(&store, train);
+        store.set_completed(train.id, true).unwrap();
+
+        assert_eq!(carry_over(&store, Mode::Copy), 1);
+        let tree = store.todo_tree(&today).unwrap();
+        assert_eq!(texts(&tree), ["Plan trip"]);
+        assert_eq!(texts(&tree[0].children), ["Book hotel"]);
+        let copy = &tree[0].todo;
+        assert_eq!(copy.carried_from, Some(trip.id));
+        assert_eq!(copy.original_date, Some(yesterday.clone()));
+        assert_eq!(copy.project_id, Some(project.id));
+        assert_eq!(copy.tags, trip.tags);
+        assert_eq!(copy.remind_at, trip.remind_at);
+        assert_ne!(copy.uid, trip.uid);
+        let ids: Vec<i64> = store
+            .project_todos(project.id)
+            .unwrap()
+            .iter()
+            .map(|todo| todo.id)
+            .collect();
+        assert_eq!(ids, [trip.id, copy.id]);
+
+        let original = get(&store, &trip);
+        assert_eq!(original.scheduled_date, yesterday);
+        assert!(!original.completed);
+        assert_eq!(original.remind_at, None);
+        assert_eq!(
+            texts(&store.todo_tree(&yesterday).unwrap()[0].children),
+            ["Book hotel", "Book train"]
+        );
+    }
+
+    #[test]
+    fn copies_are_made_once() {
+        let store = store();
+        let (older, yesterday) = (days_ago(&store, 2), days_ago(&store, 1));
+        add(&store, todo("Plan trip", &older));
+        assert_eq!(carry_over(&store, Mode::Copy), 1);
+        put_setting(&store.conn(), LAST_RUN_SETTING, &yesterday).unwrap();
+        assert_eq!(store.carry_over().unwrap(), 0);
+
+        // Left unfinished for another day, the copy is copied in turn.
+        let copy = store.todos_by_date(&store.today().unwrap()).unwrap()[0].clone();
+        store
+            .conn()
+            .execute(
+                "UPDATE todos SET scheduled_date = ?2 WHERE id = ?1",
+                params![copy.id, yesterday],
+            )
+            .unwrap();
+        put_setting(&store.conn(), LAST_RUN_SETTING, &yesterday).unwrap();
+        assert_eq!(store.carry_over().unwrap(), 1);
+        let copies = store.todos_by_date(&store.today().unwrap()).unwrap();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].carried_from, Some(copy.id));
+        assert_eq!(copies[0].original_date, Some(older));
+    }
+
+    #[test]
+    fn leave_keeps_todos_on_their_day() {
+        let store = store();
+        let yesterday = days_ago(&store, 1);
+        let trip = add(&store, todo("Plan trip", &yesterday));
+        assert_eq!(carry_over(&store, Mode::Leave), 0);
+        assert_eq!(get(&store, &trip).scheduled_date, yesterday);
+
+        // A mode that does not parse leaves todos too.
+        put_setting(&store.conn(), LAST_RUN_SETTING, &yesterday).unwrap();
+        put_setting(&store.conn(), SETTING, "teleport").unwrap();
+        assert_eq!(store.carry_over().unwrap(), 0);
+        assert_eq!(get(&store, &trip).scheduled_date, yesterday);
+    }
+
+    #[test]
+    fn runs_once_per_day() {
+        let store = store();
+        let yesterday = days_ago(&store, 1);
+        add(&store, todo("Plan trip", &yesterday));
+        assert_eq!(carry_over(&store, Mode::Move), 1);
+        assert_eq!(
+            store.setting(LAST_RUN_SETTING).unwrap(),
+            Some(store.today().unwrap())
+        );
+
+        let bank = add(&store, todo("Call the bank", &yesterday));
+        assert_eq!(store.carry_over().unwrap(), 0);
+        assert_eq!(get(&store, &bank).scheduled_date, yesterday);
+
+        // Nor after the clock went back a day.
+        let tomorrow =
+            (store.today().unwrap().parse::<NaiveDate>().unwrap() + Days::new(1)).to_string();
+        put_setting(&store.conn(), LAST_RUN_SETTING, &tomorrow).unwrap();
+        assert_eq!(store.carry_over().unwrap(), 0);
+
+        put_setting(&store.conn(), LAST_RUN_SETTING, &yesterday).unwrap();
+        assert_eq!(store.carry_over().unwrap(), 1);
+        assert_eq!(get(&store, &bank).scheduled_date, store.today().unwrap());
+    }
+
+    #[test]
+    fn parses_modes() {
+        for mode in [Mode::Move, Mode::Copy, Mode::Leave] {
+            assert_eq!(mode.to_string().parse::<Mode>().unwrap(), mode);
+        }
+        assert!(matches!(
+            "teleport".parse::<Mode>(),
+            Err(Error::InvalidCarryOver(_))
+        ));
+    }
+}
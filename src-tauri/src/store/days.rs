@@ -0,0 +1,183 @@
+//! Logical days.
+//!
+//! Todos are filed under the day the user is living in, not the UTC date:
+//! the calendar day in the local time zone, shifted by a configurable start
+//! hour so that work done at 1am can still count towards the evening before.
+
+use chrono::{DateTime, NaiveDate, TimeDelta, TimeZone, Utc};
+
+use super::{Error, Result};
+
+/// Settings key holding the hour (0-23, local time) at which a day starts.
+pub const START_HOUR_SETTING: &str = "day_start_hour";
+
+/// The logical day `at` falls on in `zone` when days start at `start_hour`.
+pub fn day_of<Tz: TimeZone>(at: DateTime<Utc>, zone: &Tz, start_hour: u32) -> NaiveDate {
+    let local = at.with_timezone(zone).naive_local();
+    (local - TimeDelta::hours(start_hour.into())).date()
+}
+
+pub fn parse_start_hour(value: &str) -> Result<u32> {
+    match value.trim().parse() {
+        Ok(hour) if hour < 24 => Ok(hour),
+        _ => Err(Error::InvalidStartHour(value.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().to_utc()
+    }
+
+    fn zone(offset: &str) -> FixedOffset {
+        offset.parse().unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn utc_midnight_start_matches_calendar_date() {
+        let utc = zone("+00:00");
+        assert_eq!(
+            day_of(at("2026-10-18T00:00:00Z"), &utc, 0),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            day_of(at("2026-10-18T23:59:59Z"), &utc, 0),
+            date("2026-10-18")
+        );
+    }
+
+    #[test]
+    fn east_of_utc_rolls_over_before_utc_does() {
+        // Tokyo: 15:30 UTC is already 00:30 the next day.
+        let tokyo = zone("+09:00");
+        assert_eq!(
+            day_of(at("2026-10-18T15:30:00Z"), &tokyo, 0),
+            date("2026-10-19")
+        );
+        assert_eq!(
+            day_of(at("2026-10-18T14:59:59Z"), &tokyo, 0),
+            date("2026-10-18")
+        );
+
+        // Half-hour offsets: India is 05:30 ahead.
+        let kolkata = zone("+05:30");
+        assert_eq!(
+            day_of(at("2026-10-18T18:29:59Z"), &kolkata, 0),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            day_of(at("2026-10-18T18:30:00Z"), &kolkata, 0),
+            date("2026-10-19")
+        );
+    }
+
+    #[test]
+    fn west_of_utc_rolls_over_after_utc_does() {
+        // Los Angeles in winter: 05:00 UTC is still 21:00 the day before.
+        let los_angeles = zone("-08:00");
+        assert_eq!(
+            day_of(at("2026-10-19T05:00:00Z"), &los_angeles, 0),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            day_of(at("2026-10-19T08:00:00Z"), &los_angeles, 0),
+            date("2026-10-19")
+        );
+
+        // Newfoundland: 03:30 behind.
+        let st_johns = zone("-03:30");
+        assert_eq!(
+            day_of(at("2026-10-19T03:29:00Z"), &st_johns, 0),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            day_of(at("2026-10-19T03:30:00Z"), &st_johns, 0),
+            date("2026-10-19")
+        );
+    }
+
+    #[test]
+    fn extreme_offsets_can_be_two_days_apart() {
+        let instant = at("2026-10-18T11:00:00Z");
+        assert_eq!(day_of(instant, &zone("+14:00"), 0), date("2026-10-19"));
+        assert_eq!(day_of(instant, &zone("-12:00"), 0), date("2026-10-17"));
+    }
+
+    #[test]
+    fn start_hour_keeps_early_hours_on_the_previous_day() {
+        let berlin = zone("+02:00");
+        // 01:30 local on the 19th belongs to the 18th when days start at 4am.
+        let late = at("2026-10-18T23:30:00Z");
+        assert_eq!(day_of(late, &berlin, 0), date("2026-10-19"));
+        assert_eq!(day_of(late, &berlin, 4), date("2026-10-18"));
+        // 04:00 local is the first moment of the new day.
+        assert_eq!(
+            day_of(at("2026-10-19T01:59:59Z"), &berlin, 4),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            day_of(at("2026-10-19T02:00:00Z"), &berlin, 4),
+            date("2026-10-19")
+        );
+    }
+
+    #[test]
+    fn start_hour_applies_in_local_time_not_utc() {
+        // 4am in Tokyo is 19:00 UTC the day before.
+        let tokyo = zone("+09:00");
+        assert_eq!(
+            day_of(at("2026-10-18T18:59:00Z"), &tokyo, 4),
+            date("2026-10-18")
+        );
+        assert_eq!(
+            day_of(at("2026-10-18T19:00:00Z"), &tokyo, 4),
+            date("2026-10-19")
+        );
+
+        let los_angeles = zone("-08:00");
+        assert_eq!(
+            day_of(at("2026-10-18T11:59:00Z"), &los_angeles, 4),
+            date("2026-10-17")
+        );
+        assert_eq!(
+            day_of(at("2026-10-18T12:00:00Z"), &los_angeles, 4),
+            date("2026-10-18")
+        );
+    }
+
+    #[test]
+    fn start_hour_crosses_month_and_year_ends() {
+        let utc = zone("+00:00");
+        assert_eq!(
+            day_of(at("2026-11-01T03:00:00Z"), &utc, 4),
+            date("2026-10-31")
+        );
+        assert_eq!(
+            day_of(at("2027-01-01T03:00:00Z"), &utc, 4),
+            date("2026-12-31")
+        );
+        assert_eq!(
+            day_of(at("2028-03-01T01:00:00Z"), &utc, 2),
+            date("2028-02-29")
+        );
+    }
+
+    #[test]
+    fn parses_start_hours() {
+        assert_eq!(parse_start_hour("0").unwrap(), 0);
+        assert_eq!(parse_start_hour(" 4 ").unwrap(), 4);
+        assert_eq!(parse_start_hour("23").unwrap(), 23);
+        assert!(parse_start_hour("24").is_err());
+        assert!(parse_start_hour("-1").is_err());
+        assert!(parse_start_hour("4am").is_err());
+    }
+}
@@ -0,0 +1,864 @@
+//! Export and import of the whole database as one JSON document.
+//!
+//! A [`Document`] holds every project, tag, todo and attachment, with the
+//! attached files inlined as base64 so a single file carries everything.
+//! Todos refer to each other by `uid`, the random UUID each todo gets when it
+//! is created, and to projects and tags by name, so a document can go into a
+//! database other than the one it came from.
+//!
+//! An import runs in one transaction: either all of it lands or none of it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::attachments::{collect_garbage, write_blob};
+use super::tags::{normalize_name, normalize_names, parse_color};
+use super::{now, parse_date, parse_due, renumber, tags, Error, Priority, Result, Rule, Store};
+
+/// What [`Document::format`] says.
+pub const FORMAT: &str = "rtodo";
+/// Layout version this build writes. Documents of earlier versions import
+/// too; later ones are refused.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Document {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default)]
+    pub projects: Vec<ProjectRecord>,
+    #[serde(default)]
+    pub tags: Vec<TagRecord>,
+    /// Day by day, each day in its list order.
+    #[serde(default)]
+    pub todos: Vec<TodoRecord>,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentRecord>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectRecord {
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub archived: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagRecord {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// A todo with every column, references replaced by uids and names.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoRecord {
+    /// Todos without one are always imported as new.
+    pub uid: String,
+    /// Row id in the exporting database, kept only by
+    /// [`ImportStrategy::Replace`].
+    pub id: Option<i64>,
+    pub text: String,
+    pub notes: Option<String>,
+    pub completed: bool,
+    pub scheduled_date: String,
+    pub due_at: Option<String>,
+    pub remind_at: Option<String>,
+    pub reminded_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub completed_at: Option<String>,
+    pub original_date: Option<String>,
+    /// Uid of the todo this one was carried over from.
+    pub carried_from: Option<String>,
+    /// Project name.
+    pub project: Option<String>,
+    pub project_order: i64,
+    pub priority: Priority,
+    /// Uid of the todo this one is a subtask of.
+    pub parent: Option<String>,
+    pub recurrence: Option<String>,
+    pub recurrence_start: Option<String>,
+    /// Uid of the previous instance of a repeating todo.
+    pub recurs_from: Option<String>,
+    pub sort_order: i64,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentRecord {
+    /// Uid of the todo the file is attached to.
+    pub todo: String,
+    pub name: String,
+    pub created_at: String,
+    /// The file's contents in base64.
+    pub data: String,
+}
+
+/// How [`Store::import_json`] treats what is already in the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportStrategy {
+    /// Delete every todo, project and tag first.
+    Replace,
+    /// Update todos with the same uid when the imported copy was changed
+    /// more recently, and add the rest.
+    #[default]
+    Merge,
+    /// Add every todo as a new one, with a new uid.
+    Append,
+}
+
+impl FromStr for ImportStrategy {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "replace" => Ok(Self::Replace),
+            "merge" => Ok(Self::Merge),
+            "append" => Ok(Self::Append),
+            _ => Err(Error::InvalidImportStrategy(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for ImportStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Replace => "replace",
+            Self::Merge => "merge",
+            Self::Append => "append",
+        })
+    }
+}
+
+/// How many imported todos were added, changed an existing todo, or were
+/// left out because the database already had them as recent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} created, {} updated, {} skipped",
+            self.created, self.updated, self.skipped
+        )
+    }
+}
+
+impl Document {
+    /// Reads a document, refusing anything that is not an rtodo export of
+    /// a version this build understands.
+    pub fn parse(json: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Header {
+            format: String,
+            version: u32,
+        }
+
+        let invalid = |e: serde_json::Error| Error::InvalidExport(e.to_string());
+        let header: Header = serde_json::from_str(json).map_err(invalid)?;
+        if header.format != FORMAT {
+            return Err(Error::InvalidExport(format!(
+                "format is `{}`, expected `{FORMAT}`",
+                header.format
+            )));
+        }
+        if header.version > VERSION {
+            return Err(Error::ExportTooNew {
+                found: header.version,
+                supported: VERSION,
+            });
+        }
+        serde_json::from_str(json).map_err(invalid)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("documents serialize")
+    }
+}
+
+impl Store {
+    /// Everything in the database, attached files included.
+    #[tracing::instrument(skip(self))]
+    pub fn export_json(&self) -> Result<Document> {
+        let conn = self.conn();
+        let projects = {
+            let mut stmt = conn.prepare(
+                "SELECT name, color, icon, archived, created_at FROM projects
+                 ORDER BY sort_order, id",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(ProjectRecord {
+                    name: row.get(0)?,
+                    color: row.get(1)?,
+                    icon: row.get(2)?,
+                    archived: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let tags = {
+            let mut stmt = conn.prepare("SELECT name, color FROM tags ORDER BY name")?;
+            let rows = stmt.query_map([], |row| {
+                Ok(TagRecord {
+                    name: row.get(0)?,
+                    color: row.get(1)?,
+                })
+            })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        let todos = export_todos(&conn)?;
+        let attachments = export_attachments(&conn, self.attachments_dir.as_deref())?;
+        Ok(Document {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            exported_at: now(),
+            projects,
+            tags,
+            todos,
+            attachments,
+        })
+    }
+
+    /// Brings the contents of `document` into the database as `strategy`
+    /// says.
+    #[tracing::instrument(skip(self, document), fields(todos = document.todos.len()))]
+    pub fn import_json(
+        &self,
+        document: &Document,
+        strategy: ImportStrategy,
+    ) -> Result<ImportReport> {
+        let todos = validate(&document.todos)?;
+        let mut conn = self.conn();
+        let imported = conn.transaction().map_err(Error::from).and_then(|tx| {
+            let report = import(
+                &tx,
+                self.attachments_dir.as_deref(),
+                document,
+                &todos,
+                strategy,
+            )?;
+            tx.commit()?;
+            Ok(report)
+        });
+        // Blobs of a failed import, or of attachments `replace` deleted, are
+        // no longer used.
+        collect_garbage(&conn, self.attachments_dir.as_deref())?;
+        let report = imported?;
+        info!(%strategy, %report, "imported todos");
+        Ok(report)
+    }
+}
+
+/// Every todo as a record, day by day in list order.
+pub(super) fn export_todos(conn: &Connection) -> Result<Vec<TodoRecord>> {
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT todo_id, name FROM todo_tags JOIN tags ON tags.id = tag_id ORDER BY name",
+    )?;
+    for row in stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))? {
+        let (todo_id, name) = row?;
+        tags.entry(todo_id).or_default().push(name);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT todos.*,
+                carried.uid AS carried_uid,
+                parent.uid AS parent_uid,
+                previous.uid AS previous_uid,
+                projects.name AS project_name
+         FROM todos
+         LEFT JOIN todos carried ON carried.id = todos.carried_from
+         LEFT JOIN todos parent ON parent.id = todos.parent_id
+         LEFT JOIN todos previous ON previous.id = todos.recurs_from
+         LEFT JOIN projects ON projects.id = todos.project_id
+         ORDER BY todos.scheduled_date, todos.parent_id IS NOT NULL, todos.parent_id,
+                  todos.completed, todos.sort_order, todos.created_at DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let id = row.get("id")?;
+        Ok(TodoRecord {
+            uid: row.get("uid")?,
+            id: Some(id),
+            text: row.get("text")?,
+            notes: row.get("notes")?,
+            completed: row.get("completed")?,
+            scheduled_date: row.get("scheduled_date")?,
+            due_at: row.get("due_at")?,
+            remind_at: row.get("remind_at")?,
+            reminded_at: row.get("reminded_at")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+            completed_at: row.get("completed_at")?,
+            original_date: row.get("original_date")?,
+            carried_from: row.get("carried_uid")?,
+            project: row.get("project_name")?,
+            project_order: row.get("project_order")?,
+            priority: row.get("priority")?,
+            parent: row.get("parent_uid")?,
+            recurrence: row.get("recurrence")?,
+            recurrence_start: row.get("recurrence_start")?,
+            recurs_from: row.get("previous_uid")?,
+            sort_order: row.get("sort_order")?,
+            tags: tags.remove(&id).unwrap_or_default(),
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+fn export_attachments(conn: &Connection, dir: Option<&Path>) -> Result<Vec<AttachmentRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT todos.uid, attachments.name, attachments.created_at, attachments.blob
+         FROM attachments JOIN todos ON todos.id = attachments.todo_id
+         ORDER BY todos.scheduled_date, todos.id, attachments.created_at, attachments.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    let mut attachments = Vec::new();
+    for row in rows {
+        let (todo, name, created_at, blob) = row?;
+        attachments.push(AttachmentRecord {
+            todo,
+            name,
+            created_at,
+            data: BASE64.encode(fs::read(dir.ok_or(Error::NoAttachmentFolder)?.join(blob))?),
+        });
+    }
+    Ok(attachments)
+}
+
+/// The columns of a [`TodoRecord`] as they are stored.
+struct Checked<'a> {
+    record: &'a TodoRecord,
+    text: &'a str,
+    due_at: Option<String>,
+    remind_at: Option<String>,
+    recurrence: Option<Rule>,
+}
+
+/// Checks every todo before anything is written, so a bad record cannot
+/// fail the import halfway through.
+fn validate(records: &[TodoRecord]) -> Result<Vec<Checked<'_>>> {
+    let mut uids = HashSet::new();
+    records
+        .iter()
+        .map(|record| {
+            if !record.uid.is_empty() && !uids.insert(record.uid.as_str()) {
+                return Err(Error::InvalidExport(format!(
+                    "todo {} appears more than once",
+                    record.uid
+                )));
+            }
+            let text = record.text.trim();
+            if text.is_empty() {
+                return Err(Error::EmptyText);
+            }
+            parse_date(&record.scheduled_date)?;
+            for date in [&record.original_date, &record.recurrence_start]
+                .into_iter()
+                .flatten()
+            {
+                parse_date(date)?;
+            }
+            normalize_names(&record.tags)?;
+            Ok(Checked {
+                record,
+                text,
+                due_at: record.due_at.as_deref().map(parse_due).transpose()?,
+                remind_at: record.remind_at.as_deref().map(parse_due).transpose()?,
+                recurrence: record.recurrence.as_deref().map(str::parse).transpose()?,
+            })
+        })
+        .collect()
+}
+
+fn import(
+    conn: &Connection,
+    dir: Option<&Path>,
+    document: &Document,
+    todos: &[Checked<'_>],
+    strategy: ImportStrategy,
+) -> Result<ImportReport> {
+    if strategy == ImportStrategy::Replace {
+        conn.execute_batch(
+            "DELETE FROM todos;
+             DELETE FROM tags;
+             DELETE FROM projects;",
+        )?;
+    }
+    for project in &document.projects {
+        project_id(conn, &project.name, Some(project))?;
+    }
+    for tag in &document.tags {
+        let color = tag.color.as_deref().map(parse_color).transpose()?;
+        conn.execute(
+            "INSERT INTO tags (name, color) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET color = COALESCE(tags.color, excluded.color)",
+            params![normalize_name(&tag.name)?, color],
+        )?;
+    }
+
+    // Unless replacing, new todos go after the ones already in their lists,
+    // keeping their order among themselves; `renumber` closes the gaps at
+    // the end.
+    let (sort_offset, project_offset): (i64, i64) = conn.query_row(
+        "SELECT COALESCE(MAX(sort_order), 0) + 1, COALESCE(MAX(project_order), 0) + 1
+         FROM todos",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let now = now();
+    let mut report = ImportReport::default();
+    // Row ids by document uid, and the todos written by this import.
+    let mut ids: HashMap<&str, i64> = HashMap::new();
+    let mut written = Vec::new();
+    for todo in todos {
+        let record = todo.record;
+        let existing = match strategy {
+            ImportStrategy::Append => None,
+            _ => conn
+                .query_row(
+                    "SELECT id, updated_at FROM todos WHERE uid = ?1",
+                    [&record.uid],
+                    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+                )
+                .optional()?,
+        };
+        let project_id = record
+            .project
+            .as_deref()
+            .map(|name| project_id(conn, name, None))
+            .transpose()?;
+        let id = match existing {
+            Some((id, updated_at)) if updated_at >= record.updated_at => {
+                report.skipped += 1;
+                ids.insert(&record.uid, id);
+                continue;
+            }
+            Some((id, _)) => {
+                update_todo(conn, id, todo, project_id, sort_offset, project_offset)?;
+                report.updated += 1;
+                id
+            }
+            None => {
+                let (id, sort_offset, project_offset) = match strategy {
+                    ImportStrategy::Replace => (record.id, 0, 0),
+                    _ => (None, sort_offset, project_offset),
+                };
+                let uid = match strategy {
+                    ImportStrategy::Append => None,
+                    _ => Some(&record.uid).filter(|uid| !uid.is_empty()),
+                };
+                let id = conn.query_row(
+                    "INSERT INTO todos
+                         (id, uid, text, notes, completed, scheduled_date, due_at, remind_at,
+                          reminded_at, created_at, updated_at, completed_at, original_date,
+                          project_id, project_order, priority, recurrence, recurrence_start,
+                          sort_order)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                             ?16, ?17, ?18, ?19)
+                     RETURNING id",
+                    params![
+                        id,
+                        uid,
+                        todo.text,
+                        record.notes,
+                        record.completed,
+                        record.scheduled_date,
+                        todo.due_at,
+                        todo.remind_at,
+                        record.reminded_at,
+                        or_now(&record.created_at, &now),
+                        or_now(&record.updated_at, &now),
+                        record.completed_at,
+                        record.original_date,
+                        project_id,
+                        record.project_order + project_offset,
+                        record.priority,
+                        todo.recurrence,
+                        record.recurrence_start,
+                        record.sort_order + sort_offset,
+                    ],
+                    |row| row.get(0),
+                )?;
+                report.created += 1;
+                id
+            }
+        };
+        tags::add(conn, id, &normalize_names(&record.tags)?)?;
+        if !record.uid.is_empty() {
+            ids.insert(&record.uid, id);
+        }
+        written.push((id, todo));
+    }
+
+    link(conn, &ids, &written, strategy)?;
+    import_attachments(conn, dir, &document.attachments, &ids, &written)?;
+
+    if strategy == ImportStrategy::Replace {
+        return Ok(report);
+    }
+    let mut lists = HashSet::new();
+    let mut stmt =
+        conn.prepare_cached("SELECT scheduled_date, parent_id FROM todos WHERE id = ?1")?;
+    for (id, _) in &written {
+        lists.insert(stmt.query_row([id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?))
+        })?);
+    }
+    for (day, parent) in lists {
+        renumber(conn, &day, parent)?;
+    }
+    Ok(report)
+}
+
+/// Overwrites an existing todo with an imported copy. It keeps its place
+/// in its day's list unless the day changes.
+fn update_todo(
+    conn: &Connection,
+    id: i64,
+    todo: &Checked<'_>,
+    project_id: Option<i64>,
+    sort_offset: i64,
+    project_offset: i64,
+) -> Result<()> {
+    let record = todo.record;
+    conn.execute(
+        "UPDATE todos SET
+             text = ?2, notes = ?3, completed = ?4,
+             sort_order = CASE WHEN scheduled_date = ?5 THEN sort_order ELSE ?6 END,
+             scheduled_date = ?5, due_at = ?7, remind_at = ?8, reminded_at = ?9,
+             updated_at = ?10, completed_at = ?11, original_date = ?12,
+             project_order = CASE WHEN project_id IS ?13 THEN project_order ELSE ?14 END,
+             project_id = ?13, priority = ?15, recurrence = ?16, recurrence_start = ?17
+         WHERE id = ?1",
+        params![
+            id,
+            todo.text,
+            record.notes,
+            record.completed,
+            record.scheduled_date,
+            record.sort_order + sort_offset,
+            todo.due_at,
+            todo.remind_at,
+            record.reminded_at,
+            record.updated_at,
+            record.completed_at,
+            record.original_date,
+            project_id,
+            record.project_order + project_offset,
+            record.priority,
+            todo.recurrence,
+            record.recurrence_start,
+        ],
+    )?;
+    conn.execute("DELETE FROM todo_tags WHERE todo_id = ?1", [id])?;
+    Ok(())
+}
+
+/// Points the written todos at their parents, the todos they were carried
+/// over from and their previous instances. A reference to a todo neither in
+/// the document nor, when merging, in the database is dropped.
+fn link(
+    conn: &Connection,
+    ids: &HashMap<&str, i64>,
+    written: &[(i64, &Checked<'_>)],
+    strategy: ImportStrategy,
+) -> Result<()> {
+    let resolve = |uid: &Option<String>| -> Result<Option<i64>> {
+        let Some(uid) = uid.as_deref() else {
+            return Ok(None);
+        };
+        if let Some(&id) = ids.get(uid) {
+            return Ok(Some(id));
+        }
+        if strategy != ImportStrategy::Merge {
+            return Ok(None);
+        }
+        Ok(conn
+            .query_row("SELECT id FROM todos WHERE uid = ?1", [uid], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    };
+    let mut stmt = conn.prepare_cached(
+        "UPDATE todos SET carried_from = ?2, parent_id = ?3, recurs_from = ?4 WHERE id = ?1",
+    )?;
+    for (id, todo) in written {
+        let record = todo.record;
+        let parent = resolve(&record.parent)?.filter(|parent| parent != id);
+        stmt.execute(params![
+            id,
+            resolve(&record.carried_from)?,
+            parent,
+            resolve(&record.recurs_from)?
+        ])?;
+    }
+    // Checked once everything is linked, since a cycle can go through
+    // several records.
+    let mut nested_in_itself = conn.prepare_cached(
+        "WITH RECURSIVE ancestors (id) AS (
+             SELECT parent_id FROM todos WHERE id = ?1
+             UNION
+             SELECT todos.parent_id FROM todos JOIN ancestors ON todos.id = ancestors.id
+         )
+         SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = ?1)",
+    )?;
+    for (id, todo) in written {
+        if nested_in_itself.query_row([id], |row| row.get(0))? {
+            return Err(Error::InvalidExport(format!(
+                "todo {} is nested under itself",
+                todo.record.uid
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Adds the files of the written todos, leaving out any already attached
+/// to the same todo under the same name.
+fn import_attachments(
+    conn: &Connection,
+    dir: Option<&Path>,
+    attachments: &[AttachmentRecord],
+    ids: &HashMap<&str, i64>,
+    written: &[(i64, &Checked<'_>)],
+) -> Result<()> {
+    let written: HashSet<i64> = written.iter().map(|(id, _)| *id).collect();
+    let now = now();
+    for attachment in attachments {
+        let Some(&todo_id) = ids.get(attachment.todo.as_str()) else {
+            return Err(Error::InvalidExport(format!(
+                "attachment `{}` belongs to unknown todo {}",
+                attachment.name, attachment.todo
+            )));
+        };
+        if !written.contains(&todo_id) {
+            continue;
+        }
+        let data = BASE64
+            .decode(&attachment.data)
+            .map_err(|e| Error::InvalidExport(format!("attachment `{}`: {e}", attachment.name)))?;
+        let dir = dir.ok_or(Error::NoAttachmentFolder)?;
+        let blob = write_blob(dir, &attachment.name, &data)?;
+        conn.execute(
+            "INSERT INTO attachments (todo_id, name, size, blob, created_at)
+             SELECT ?1, ?2, ?3, ?4, ?5
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM attachments WHERE todo_id = ?1 AND name = ?2 AND blob = ?4
+             )",
+            params![
+                todo_id,
+                attachment.name,
+                data.len() as u64,
+                blob,
+                or_now(&attachment.created_at, &now)
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Id of the project called `name`, created with the style of `record`
+/// when there is none.
+fn project_id(conn: &Connection, name: &str, record: Option<&ProjectRecord>) -> Result<i64> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::EmptyProjectName);
+    }
+    let existing = conn
+        .query_row("SELECT id FROM projects WHERE name = ?1", [name], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    let record = record.cloned().unwrap_or_default();
+    let color = record.color.as_deref().map(parse_color).transpose()?;
+    Ok(conn.query_row(
+        "INSERT INTO projects (name, color, icon, sort_order, archived, created_at)
+         VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM projects), ?4, ?5)
+         RETURNING id",
+        params![
+            name,
+            color,
+            record.icon,
+            record.archived,
+            or_now(&record.created_at, &now())
+        ],
+        |row| row.get(0),
+    )?)
+}
+
+/// `value`, or `now` for a timestamp the document left out.
+fn or_now<'a>(value: &'a str, now: &'a str) -> &'a str {
+    if value.is_empty() {
+        now
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::{NewTodo, TodoUpdate};
+
+    const DAY: &str = "2026-10-20";
+
+    /// A todo on [`DAY`] tagged `home`, under `parent_id` when given.
+    fn home(text: &str, parent_id: Option<i64>) -> NewTodo {
+        NewTodo {
+            parent_id,
+            tags: vec!["home".to_owned()],
+            ..todo(text, DAY)
+        }
+    }
+
+    /// The document without its export time, for comparing exports.
+    fn contents(store: &Store) -> Document {
+        Document {
+            exported_at: String::new(),
+            ..store.export_json().unwrap()
+        }
+    }
+
+    #[test]
+    fn replace_restores_an_export_exactly() {
+        let source = store();
+        let parent = add(&source, home("Plan trip", None)).id;
+        add(&source, home("Book hotel", Some(parent)));
+        add(&source, home("Pack", None));
+        let document = Document::parse(&source.export_json().unwrap().to_json()).unwrap();
+
+        let target = store();
+        add(&target, home("Gone after replace", None));
+        let report = target
+            .import_json(&document, ImportStrategy::Replace)
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                created: 3,
+                updated: 0,
+                skipped: 0
+            }
+        );
+        assert_eq!(contents(&target), contents(&source));
+    }
+
+    #[test]
+    fn merge_updates_newer_todos_and_skips_the_rest() {
+        let source = store();
+        let changed = add(&source, home("Plan trip", None)).id;
+        add(&source, home("Pack", None));
+        let target = store();
+        target
+            .import_json(&source.export_json().unwrap(), ImportStrategy::Replace)
+            .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        source
+            .update_todo(
+                changed,
+                TodoUpdate {
+                    completed: Some(true),
+                    ..TodoUpdate::default()
+                },
+            )
+            .unwrap();
+        add(&source, home("Buy sunscreen", None));
+        let report = target
+            .import_json(&source.export_json().unwrap(), ImportStrategy::Merge)
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                created: 1,
+                updated: 1,
+                skipped: 1
+            }
+        );
+        assert!(target.get_todo(changed).unwrap().completed);
+    }
+
+    #[test]
+    fn append_copies_todos_with_new_uids_and_links() {
+        let store = store();
+        let parent = add(&store, home("Plan trip", None)).id;
+        add(&store, home("Book hotel", Some(parent)));
+        let document = store.export_json().unwrap();
+        let report = store
+            .import_json(&document, ImportStrategy::Append)
+            .unwrap();
+        assert_eq!(report.created, 2);
+
+        let todos = store.export_json().unwrap().todos;
+        let uids: HashSet<&str> = todos.iter().map(|t| t.uid.as_str()).collect();
+        assert_eq!(uids.len(), 4);
+        let parents: HashSet<Option<&str>> = todos
+            .iter()
+            .filter(|t| t.text == "Book hotel")
+            .map(|t| t.parent.as_deref())
+            .collect();
+        assert_eq!(parents.len(), 2);
+    }
+
+    #[test]
+    fn failed_imports_change_nothing() {
+        let store = store();
+        let parent = add(&store, home("Plan trip", None)).id;
+        add(&store, home("Book hotel", Some(parent)));
+        let mut document = store.export_json().unwrap();
+        // Nest the parent under its own subtask.
+        document.todos[0].parent = Some(document.todos[1].uid.clone());
+        let before = contents(&store);
+        assert!(matches!(
+            store.import_json(&document, ImportStrategy::Append),
+            Err(Error::InvalidExport(_))
+        ));
+        assert_eq!(contents(&store), before);
+    }
+
+    #[test]
+    fn documents_from_newer_versions_are_refused() {
+        let json = format!(r#"{{"format": "rtodo", "version": {}}}"#, VERSION + 1);
+        assert!(matches!(
+            Document::parse(&json),
+            Err(Error::ExportTooNew { .. })
+        ));
+        assert!(matches!(
+            Document::parse(r#"{"format": "todo.txt", "version": 1}"#),
+            Err(Error::InvalidExport(_))
+        ));
+    }
+}
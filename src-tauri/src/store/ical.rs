@@ -0,0 +1,656 @@
+//! Import and export as iCalendar ([RFC 5545]), one `VTODO` per todo.
+//!
+//! A todo's uid is its `UID`, so merging a calendar exported here, possibly
+//! after another application changed it, updates the same todos. The day a
+//! todo is listed under is its `DTSTART`, a plain date unless the todo has a
+//! deadline: `DUE` is a UTC date-time, and RFC 5545 wants both of the same
+//! type, so `DTSTART` is then local midnight of that day. `STATUS`,
+//! `COMPLETED`, `PRIORITY`, `CATEGORIES` (tags), `DESCRIPTION` (notes),
+//! `RELATED-TO` (the parent todo) and `RRULE` carry the rest. Projects,
+//! reminders and attachments are left out.
+//!
+//! Importing reads every `VTODO` with a summary and ignores the other
+//! components. Times with a `TZID` are read as local time like floating
+//! ones, a `DUE` date means the end of that day, and repeat rules beyond
+//! what [`Rule`] supports are dropped.
+//!
+//! [RFC 5545]: https://www.rfc-editor.org/rfc/rfc5545
+
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use super::export::{export_todos, Document, ImportReport, ImportStrategy, TodoRecord};
+use super::export::{FORMAT, VERSION};
+use super::todotxt::DUE_TIME;
+use super::{now, parse_date, timestamp, today, Error, Priority, Result, Rule, Store};
+
+const PRODID: &str = "-//rtodo//rtodo//EN";
+
+/// How many days before today the feed of [`Store::ical_feed`] reaches.
+pub const FEED_DAYS: u64 = 30;
+
+/// Longest content line in octets, not counting the line break.
+const LINE_LIMIT: usize = 75;
+
+/// `PRIORITY` written for each priority, where 1 is the highest.
+const PRIORITIES: [(Priority, u8); 4] = [
+    (Priority::Urgent, 1),
+    (Priority::High, 3),
+    (Priority::Medium, 5),
+    (Priority::Low, 7),
+];
+
+impl Store {
+    /// Every todo as an iCalendar document.
+    #[tracing::instrument(skip(self))]
+    pub fn export_ical(&self) -> Result<String> {
+        let todos = export_todos(&self.conn())?;
+        Ok(write_calendar(&todos, Utc::now(), &Local))
+    }
+
+    /// The todos listed from [`FEED_DAYS`] days ago onwards, as the
+    /// calendar the local feed serves.
+    pub fn ical_feed(&self) -> Result<String> {
+        let conn = self.conn();
+        let from = (parse_date(&today(&conn)?)? - Days::new(FEED_DAYS)).to_string();
+        let mut todos = export_todos(&conn)?;
+        todos.retain(|todo| todo.scheduled_date >= from);
+        Ok(write_calendar(&todos, Utc::now(), &Local))
+    }
+
+    /// Brings the `VTODO`s of an iCalendar document into the database as
+    /// `strategy` says. Merging matches them to todos by `UID`.
+    #[tracing::instrument(skip(self, text))]
+    pub fn import_ical(&self, text: &str, strategy: ImportStrategy) -> Result<ImportReport> {
+        let today = parse_date(&today(&self.conn())?)?;
+        let mut todos = parse_calendar(text, today, &Local)?;
+        for (position, todo) in (0..).zip(&mut todos) {
+            todo.sort_order = position;
+        }
+        let document = Document {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            exported_at: now(),
+            projects: Vec::new(),
+            tags: Vec::new(),
+            todos,
+            attachments: Vec::new(),
+        };
+        self.import_json(&document, strategy)
+    }
+}
+
+/// Writes `todos` as a calendar stamped `stamp`, with days local to `zone`.
+pub fn write_calendar<Tz: TimeZone>(
+    todos: &[TodoRecord],
+    stamp: DateTime<Utc>,
+    zone: &Tz,
+) -> String {
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        &format!("PRODID:{PRODID}"),
+        "CALSCALE:GREGORIAN",
+        "X-WR-CALNAME:rtodo",
+    ] {
+        push_line(&mut out, line);
+    }
+    let stamp = format_utc(stamp);
+    for todo in todos {
+        write_todo(&mut out, todo, &stamp, zone);
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn write_todo<Tz: TimeZone>(out: &mut String, todo: &TodoRecord, stamp: &str, zone: &Tz) {
+    let utc = |at: &str| {
+        DateTime::parse_from_rfc3339(at)
+            .ok()
+            .map(|at| format_utc(at.with_timezone(&Utc)))
+    };
+    let mut lines = vec![
+        "BEGIN:VTODO".to_owned(),
+        format!("UID:{}", todo.uid),
+        format!("DTSTAMP:{stamp}"),
+    ];
+    if let Some(at) = utc(&todo.created_at) {
+        lines.push(format!("CREATED:{at}"));
+    }
+    if let Some(at) = utc(&todo.updated_at) {
+        lines.push(format!("LAST-MODIFIED:{at}"));
+    }
+    lines.push(format!("SUMMARY:{}", escape(&todo.text)));
+    if let Some(notes) = &todo.notes {
+        lines.push(format!("DESCRIPTION:{}", escape(notes)));
+    }
+    let due = todo.due_at.as_deref().and_then(utc);
+    if let Ok(day) = NaiveDate::parse_from_str(&todo.scheduled_date, "%Y-%m-%d") {
+        if due.is_none() {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+        } else if let Some(start) = zone
+            .from_local_datetime(&day.and_time(NaiveTime::MIN))
+            .earliest()
+        {
+            lines.push(format!("DTSTART:{}", format_utc(start.with_timezone(&Utc))));
+        }
+    }
+    if let Some(due) = due {
+        lines.push(format!("DUE:{due}"));
+    }
+    if todo.completed {
+        lines.push("STATUS:COMPLETED".to_owned());
+        if let Some(at) = todo.completed_at.as_deref().and_then(utc) {
+            lines.push(format!("COMPLETED:{at}"));
+        }
+    } else {
+        lines.push("STATUS:NEEDS-ACTION".to_owned());
+    }
+    if let Some((_, value)) = PRIORITIES.iter().find(|(p, _)| *p == todo.priority) {
+        lines.push(format!("PRIORITY:{value}"));
+    }
+    if !todo.tags.is_empty() {
+        let tags: Vec<String> = todo.tags.iter().map(|tag| escape(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if let Some(parent) = &todo.parent {
+        lines.push(format!("RELATED-TO:{parent}"));
+    }
+    if let Some(rule) = &todo.recurrence {
+        lines.push(format!("RRULE:{rule}"));
+    }
+    lines.push("END:VTODO".to_owned());
+    for line in &lines {
+        push_line(out, line);
+    }
+}
+
+/// Appends a content line, folded after [`LINE_LIMIT`] octets without
+/// splitting a character.
+fn push_line(out: &mut String, line: &str) {
+    let mut start = 0;
+    let mut limit = LINE_LIMIT;
+    for (i, c) in line.char_indices() {
+        if i + c.len_utf8() - start > limit {
+            out.push_str(&line[start..i]);
+            out.push_str("\r\n ");
+            start = i;
+            // The leading space counts towards the limit.
+            limit = LINE_LIMIT - 1;
+        }
+    }
+    out.push_str(&line[start..]);
+    out.push_str("\r\n");
+}
+
+fn format_utc(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reads the `VTODO`s of a calendar created on `today`, with floating times
+/// local to `zone`. Overrides of single instances of a repeating `VTODO`,
+/// which share its `UID`, are skipped.
+pub fn parse_calendar<Tz: TimeZone>(
+    text: &str,
+    today: NaiveDate,
+    zone: &Tz,
+) -> Result<Vec<TodoRecord>> {
+    let lines = unfold(text);
+    let is_calendar = lines
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"));
+    if !is_calendar {
+        return Err(Error::InvalidCalendar);
+    }
+
+    let mut todos = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    // Components open inside the current VTODO, such as alarms.
+    let mut depth = 0;
+    for line in &lines {
+        let Some(property) = Property::parse(line) else {
+            continue;
+        };
+        match (property.name.as_str(), &mut current) {
+            ("BEGIN", None) if property.value.eq_ignore_ascii_case("VTODO") => {
+                current = Some(Vec::new());
+            }
+            ("BEGIN", Some(_)) => depth += 1,
+            ("END", Some(_)) if depth > 0 => depth -= 1,
+            ("END", Some(properties)) => {
+                todos.extend(parse_todo(properties, today, zone));
+                current = None;
+            }
+            (_, Some(properties)) if depth == 0 => properties.push(property),
+            _ => {}
+        }
+    }
+    Ok(todos)
+}
+
+fn parse_todo<Tz: TimeZone>(
+    properties: &[Property],
+    today: NaiveDate,
+    zone: &Tz,
+) -> Option<TodoRecord> {
+    let find = |name: &str| properties.iter().find(|p| p.name == name);
+    let when = |name: &str| find(name).and_then(|p| When::parse(p, zone));
+    if find("RECURRENCE-ID").is_some() {
+        return None;
+    }
+    let summary = unescape(&find("SUMMARY")?.value);
+    let text = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut todo = TodoRecord {
+        uid: find("UID")
+            .map(|p| p.value.trim().to_owned())
+            .unwrap_or_default(),
+        text,
+        notes: find("DESCRIPTION")
+            .map(|p| unescape(&p.value))
+            .filter(|notes| !notes.trim().is_empty()),
+        ..TodoRecord::default()
+    };
+    let completed = when("COMPLETED");
+    let due = when("DUE");
+    let (hour, minute) = DUE_TIME;
+    let end_of_day = NaiveTime::from_hms_opt(hour, minute, 0)?;
+    let at = |when: When, time| when.at(zone, time).map(timestamp);
+
+    todo.completed = completed.is_some()
+        || find("STATUS").is_some_and(|p| p.value.trim().eq_ignore_ascii_case("COMPLETED"));
+    todo.completed_at = completed.and_then(|c| at(c, NaiveTime::MIN));
+    todo.due_at = due.and_then(|d| at(d, end_of_day));
+    todo.scheduled_date = when("DTSTART")
+        .or(due)
+        .or(completed)
+        .map_or(today, |w| w.day(zone))
+        .to_string();
+    todo.created_at = when("CREATED")
+        .and_then(|c| at(c, NaiveTime::MIN))
+        .unwrap_or_default();
+    // Without a method, DTSTAMP is when the todo was last changed.
+    todo.updated_at = when("LAST-MODIFIED")
+        .or_else(|| when("DTSTAMP"))
+        .and_then(|m| at(m, NaiveTime::MIN))
+        .unwrap_or_default();
+    todo.priority = find("PRIORITY")
+        .and_then(|p| p.value.trim().parse().ok())
+        .map_or(Priority::None, priority_of);
+    todo.tags = properties
+        .iter()
+        .filter(|p| p.name == "CATEGORIES")
+        .flat_map(|p| split_list(&p.value))
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("-"))
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    todo.parent = properties
+        .iter()
+        .find(|p| {
+            p.name == "RELATED-TO"
+                && p.param("RELTYPE")
+                    .is_none_or(|kind| kind.eq_ignore_ascii_case("PARENT"))
+        })
+        .map(|p| p.value.trim().to_owned());
+    todo.recurrence = find("RRULE")
+        .and_then(|p| p.value.trim().parse::<Rule>().ok())
+        .map(|rule| rule.to_string());
+    if todo.recurrence.is_some() {
+        todo.recurrence_start = Some(todo.scheduled_date.clone());
+    }
+    Some(todo)
+}
+
+/// `PRIORITY` 1 to 9, where 1 is the highest and 0 means none.
+fn priority_of(value: u8) -> Priority {
+    match value {
+        1 => Priority::Urgent,
+        2..=4 => Priority::High,
+        5 => Priority::Medium,
+        6..=9 => Priority::Low,
+        _ => Priority::None,
+    }
+}
+
+/// Joins folded lines back together.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// A content line: `NAME;PARAM=value:value`.
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = split_unquoted(line, ';', ':');
+        let (value, head) = parts.split_last_mut()?;
+        let (name, params) = head.split_first()?;
+        let params = params
+            .iter()
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                Some((key.to_ascii_uppercase(), value.trim_matches('"').to_owned()))
+            })
+            .collect();
+        Some(Self {
+            name: name.trim().to_ascii_uppercase(),
+            params,
+            value: std::mem::take(value),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Splits the name and parameters of a content line at `separator` until
+/// the first `end` outside quotes; whatever follows that is the last part.
+/// Empty when there is no `end`.
+fn split_unquoted(line: &str, separator: char, end: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                part.push(c);
+            }
+            c if c == end && !quoted => {
+                parts.push(part);
+                parts.push(line[i + c.len_utf8()..].to_owned());
+                return parts;
+            }
+            c if c == separator && !quoted => parts.push(std::mem::take(&mut part)),
+            _ => part.push(c),
+        }
+    }
+    Vec::new()
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+/// The items of a comma-separated text list such as `CATEGORIES`.
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                item.push(c);
+                item.extend(chars.next());
+            }
+            ',' => items.push(unescape(&std::mem::take(&mut item))),
+            _ => item.push(c),
+        }
+    }
+    items.push(unescape(&item));
+    items
+}
+
+/// A `DATE` or `DATE-TIME` value.
+#[derive(Debug, Clone, Copy)]
+enum When {
+    Date(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+impl When {
+    fn parse<Tz: TimeZone>(property: &Property, zone: &Tz) -> Option<Self> {
+        let value = property.value.trim();
+        let is_date = property
+            .param("VALUE")
+            .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"));
+        if is_date || value.len() == 8 {
+            return NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .map(Self::Date);
+        }
+        if let Some(utc) = value.strip_suffix('Z') {
+            let at = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            return Some(Self::At(at.and_utc()));
+        }
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        let at = zone.from_local_datetime(&local).earliest()?;
+        Some(Self::At(at.with_timezone(&Utc)))
+    }
+
+    /// The local day this falls on.
+    fn day<Tz: TimeZone>(self, zone: &Tz) -> NaiveDate {
+        match self {
+            Self::Date(date) => date,
+            Self::At(at) => at.with_timezone(zone).date_naive(),
+        }
+    }
+
+    /// This instant, or `time` on this day for a date.
+    fn at<Tz: TimeZone>(self, zone: &Tz, time: NaiveTime) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Date(date) => {
+                let at = zone.from_local_datetime(&date.and_time(time)).earliest()?;
+                Some(at.with_timezone(&Utc))
+            }
+            Self::At(at) => Some(at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    fn stamp() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, 8, 0, 0).unwrap()
+    }
+
+    fn calendar(vtodo: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{vtodo}END:VCALENDAR\r\n")
+    }
+
+    fn todo() -> TodoRecord {
+        TodoRecord {
+            uid: "0b6e1a4c-5d2f-4c3b-9a8e-7f6d5c4b3a21".to_owned(),
+            text: "Renew passport; bring photos, forms".to_owned(),
+            notes: Some("Office opens at 8\nBring cash".to_owned()),
+            completed: true,
+            scheduled_date: "2026-10-19".to_owned(),
+            due_at: Some("2026-10-20T21:59:00.000Z".to_owned()),
+            created_at: "2026-10-12T06:30:00.000Z".to_owned(),
+            updated_at: "2026-10-18T07:15:00.000Z".to_owned(),
+            completed_at: Some("2026-10-18T07:15:00.000Z".to_owned()),
+            priority: Priority::High,
+            parent: Some("c1d2e3f4-0000-4000-8000-000000000001".to_owned()),
+            tags: vec!["@errands".to_owned(), "admin".to_owned()],
+            ..TodoRecord::default()
+        }
+    }
+
+    #[test]
+    fn writes_vtodos() {
+        let ics = write_calendar(&[todo()], stamp(), &Berlin);
+        assert!(ics.ends_with("END:VTODO\r\nEND:VCALENDAR\r\n"));
+        let body = ics.split("BEGIN:VTODO\r\n").nth(1).unwrap();
+        assert_eq!(
+            body,
+            "UID:0b6e1a4c-5d2f-4c3b-9a8e-7f6d5c4b3a21\r\n\
+             DTSTAMP:20261018T080000Z\r\n\
+             CREATED:20261012T063000Z\r\n\
+             LAST-MODIFIED:20261018T071500Z\r\n\
+             SUMMARY:Renew passport\\; bring photos\\, forms\r\n\
+             DESCRIPTION:Office opens at 8\\nBring cash\r\n\
+             DTSTART:20261018T220000Z\r\n\
+             DUE:20261020T215900Z\r\n\
+             STATUS:COMPLETED\r\n\
+             COMPLETED:20261018T071500Z\r\n\
+             PRIORITY:3\r\n\
+             CATEGORIES:@errands,admin\r\n\
+             RELATED-TO:c1d2e3f4-0000-4000-8000-000000000001\r\n\
+             END:VTODO\r\n\
+             END:VCALENDAR\r\n"
+        );
+
+        let open = TodoRecord {
+            completed: false,
+            due_at: None,
+            completed_at: None,
+            recurrence: Some("FREQ=WEEKLY;BYDAY=MO".to_owned()),
+            ..todo()
+        };
+        let ics = write_calendar(&[open], stamp(), &Berlin);
+        assert!(ics.contains("\r\nDTSTART;VALUE=DATE:20261019\r\nSTATUS:NEEDS-ACTION\r\n"));
+        assert!(ics.contains("\r\nRRULE:FREQ=WEEKLY;BYDAY=MO\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_between_characters() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "ä".repeat(60));
+        push_line(&mut out, &line);
+        let lines: Vec<&str> = out.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.len() <= LINE_LIMIT));
+        assert_eq!(unfold(&out), vec![line]);
+    }
+
+    #[test]
+    fn round_trips_written_todos() {
+        let mut todos = vec![todo()];
+        todos.push(TodoRecord {
+            uid: "c1d2e3f4-0000-4000-8000-000000000001".to_owned(),
+            text: "Trip".to_owned(),
+            scheduled_date: "2026-10-17".to_owned(),
+            updated_at: "2026-10-17T10:00:00.000Z".to_owned(),
+            recurrence: Some("FREQ=MONTHLY;BYMONTHDAY=-1".to_owned()),
+            recurrence_start: Some("2026-10-17".to_owned()),
+            ..TodoRecord::default()
+        });
+        let ics = write_calendar(&todos, stamp(), &Berlin);
+        let parsed = parse_calendar(&ics, today(), &Berlin).unwrap();
+        assert_eq!(parsed, todos);
+    }
+
+    #[test]
+    fn reads_todos_of_other_applications() {
+        let ics = calendar(
+            "BEGIN:VEVENT\r\nUID:meeting\r\nSUMMARY:Stand-up\r\nEND:VEVENT\r\n\
+             BEGIN:VTODO\r\n\
+             UID:abc@example.com\r\n\
+             DTSTAMP:20261001T120000Z\r\n\
+             SUMMARY;LANGUAGE=en:Call the \"bank\"\\, again\r\n\
+             DUE;VALUE=DATE:20261025\r\n\
+             PRIORITY:9\r\n\
+             CATEGORIES:Work Stuff,home\r\n\
+             CATEGORIES:urgent\r\n\
+             RELATED-TO;RELTYPE=SIBLING:other@example.com\r\n\
+             RRULE:FREQ=YEARLY;BYMONTH=3\r\n\
+             BEGIN:VALARM\r\nACTION:DISPLAY\r\nSUMMARY:Not the todo\r\nEND:VALARM\r\n\
+             END:VTODO\r\n\
+             BEGIN:VTODO\r\n\
+             UID:abc@example.com\r\n\
+             RECURRENCE-ID:20261101T090000Z\r\n\
+             SUMMARY:Moved instance\r\n\
+             END:VTODO\r\n\
+             BEGIN:VTODO\r\nUID:untitled\r\nSUMMARY: \r\nEND:VTODO\r\n\
+             BEGIN:VTODO\r\n\
+             SUMMARY:Done\r\n\
+             STATUS:COMPLETED\r\n\
+             DTSTART;TZID=Europe/Berlin:20261016T0900\r\n\
+             COMPLETED:20261016T101500Z\r\n\
+             END:VTODO\r\n",
+        );
+        let todos = parse_calendar(&ics, today(), &Berlin).unwrap();
+        assert_eq!(todos.len(), 2);
+
+        let call = &todos[0];
+        assert_eq!(call.uid, "abc@example.com");
+        assert_eq!(call.text, "Call the \"bank\", again");
+        assert_eq!(call.scheduled_date, "2026-10-25");
+        assert_eq!(call.due_at.as_deref(), Some("2026-10-25T22:59:00.000Z"));
+        assert_eq!(call.updated_at, "2026-10-01T12:00:00.000Z");
+        assert_eq!(call.priority, Priority::Low);
+        assert_eq!(call.tags, ["Work-Stuff", "home", "urgent"]);
+        assert_eq!(call.parent, None);
+        assert_eq!(call.recurrence, None);
+
+        // The malformed start time leaves the completion day.
+        let done = &todos[1];
+        assert_eq!(done.uid, "");
+        assert!(done.completed);
+        assert_eq!(done.scheduled_date, "2026-10-16");
+        assert_eq!(
+            done.completed_at.as_deref(),
+            Some("2026-10-16T10:15:00.000Z")
+        );
+        assert_eq!(done.created_at, "");
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            parse_calendar("UID:x\r\nSUMMARY:y\r\n", today(), &Berlin),
+            Err(Error::InvalidCalendar)
+        ));
+        assert!(
+            parse_calendar("\nBEGIN:VCALENDAR\nEND:VCALENDAR\n", today(), &Berlin)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Ordered schema migrations tracked with `PRAGMA user_version`.
+//!
+//! A migration's version is its 1-based position in [`MIGRATIONS`], so new
+//! migrations are only ever appended. Each one runs in its own transaction
+//! together with the `user_version` bump, leaving the database either fully
+//! migrated to that step or untouched.
+
+use rusqlite::Connection;
+use tracing::info;
+
+use super::{Error, Result};
+
+struct Migration {
+    name: &'static str,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        // Databases created by the webview before migrations existed already
+        // have this table, hence the IF NOT EXISTS guards.
+        name: "create_todos",
+        sql: "
+        CREATE TABLE IF NOT EXISTS todos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            text TEXT NOT NULL,
+            completed BOOLEAN NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            sort_order INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_todos_completed_sort_created
+            ON todos (completed, sort_order, created_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_todos_date_completed_sort
+            ON todos (date(created_at), completed, sort_order);
+        UPDATE todos SET sort_order = id WHERE sort_order = 0;
+    ",
+    },
+    Migration {
+        name: "create_settings",
+        sql: "
+        CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        ) WITHOUT ROWID;
+    ",
+    },
+    Migration {
+        // `created_at` used to double as the day a todo is listed under, so
+        // todos added to another day got a fake midnight timestamp. The
+        // timestamps are UTC, so the day is read in local time the way
+        // `days::day_of` files todos. The real creation and completion times
+        // of existing rows are unknown; they keep that timestamp and get no
+        // `completed_at`. The sequence is carried over so deleted ids are
+        // never handed out again.
+        name: "split_todo_dates",
+        sql: "
+        CREATE TABLE todos_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            text TEXT NOT NULL,
+            completed BOOLEAN NOT NULL DEFAULT 0,
+            scheduled_date TEXT NOT NULL,
+            due_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            completed_at TEXT,
+            sort_order INTEGER NOT NULL DEFAULT 0
+        );
+        INSERT INTO todos_new
+            (id, text, completed, scheduled_date, created_at, updated_at, sort_order)
+        SELECT id, text, completed, date(created_at, 'localtime'), created_at, created_at,
+               sort_order
+        FROM todos;
+        UPDATE sqlite_sequence
+        SET seq = MAX(seq, (SELECT seq FROM sqlite_sequence WHERE name = 'todos'))
+        WHERE name = 'todos_new';
+        DROP TABLE todos;
+        ALTER TABLE todos_new RENAME TO todos;
+        CREATE INDEX idx_todos_completed_sort_created
+            ON todos (completed, sort_order, created_at DESC);
+        CREATE INDEX idx_todos_scheduled_completed_sort
+            ON todos (scheduled_date, completed, sort_order);
+        CREATE INDEX idx_todos_open_due
+            ON todos (due_at) WHERE due_at IS NOT NULL AND completed = 0;
+    ",
+    },
+    Migration {
+        name: "add_carry_over",
+        sql: "
+        ALTER TABLE todos ADD COLUMN original_date TEXT;
+        ALTER TABLE todos ADD COLUMN carried_from INTEGER
+            REFERENCES todos (id) ON DELETE SET NULL;
+        CREATE INDEX idx_todos_carried_from
+            ON todos (carried_from) WHERE carried_from IS NOT NULL;
+    ",
+    },
+    Migration {
+        name: "create_tags",
+        sql: "
+        CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT
+        );
+        CREATE TABLE todo_tags (
+            todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+            PRIMARY KEY (todo_id, tag_id)
+        ) WITHOUT ROWID;
+        CREATE INDEX idx_todo_tags_tag ON todo_tags (tag_id, todo_id);
+    ",
+    },
+    Migration {
+        name: "create_projects",
+        sql: "
+        CREATE TABLE projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            icon TEXT,
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            archived BOOLEAN NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        );
+        ALTER TABLE todos ADD COLUMN project_id INTEGER
+            REFERENCES projects (id) ON DELETE SET NULL;
+        ALTER TABLE todos ADD COLUMN project_order INTEGER NOT NULL DEFAULT 0;
+        CREATE INDEX idx_todos_project_completed_order
+            ON todos (project_id, completed, project_order) WHERE project_id IS NOT NULL;
+    ",
+    },
+    Migration {
+        name: "add_priority",
+        sql: "
+        ALTER TABLE todos ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+    ",
+    },
+    Migration {
+        name: "add_subtasks",
+        sql: "
+        ALTER TABLE todos ADD COLUMN parent_id INTEGER
+            REFERENCES todos (id) ON DELETE CASCADE;
+        CREATE INDEX idx_todos_parent_completed_sort
+            ON todos (parent_id, completed, sort_order) WHERE parent_id IS NOT NULL;
+    ",
+    },
+    Migration {
+        name: "add_notes",
+        sql: "
+        ALTER TABLE todos ADD COLUMN notes TEXT;
+    ",
+    },
+    Migration {
+        // `blob` names a file in the attachments folder; several rows may
+        // share one when the same content is attached twice.
+        name: "create_attachments",
+        sql: "
+        CREATE TABLE attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            todo_id INTEGER NOT NULL REFERENCES todos (id) ON DELETE CASCADE,
+            name TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            blob TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_attachments_todo ON attachments (todo_id);
+        CREATE INDEX idx_attachments_blob ON attachments (blob);
+    ",
+    },
+    Migration {
+        // `recurrence_start` anchors intervals such as "every 2 weeks".
+        name: "add_recurrence",
+        sql: "
+        ALTER TABLE todos ADD COLUMN recurrence TEXT;
+        ALTER TABLE todos ADD COLUMN recurrence_start TEXT;
+        ALTER TABLE todos ADD COLUMN recurs_from INTEGER
+            REFERENCES todos (id) ON DELETE SET NULL;
+        CREATE INDEX idx_todos_recurs_from
+            ON todos (recurs_from) WHERE recurs_from IS NOT NULL;
+    ",
+    },
+    Migration {
+        // A reminder is pending while `reminded_at` is null.
+        name: "add_reminders",
+        sql: "
+        ALTER TABLE todos ADD COLUMN remind_at TEXT;
+        ALTER TABLE todos ADD COLUMN reminded_at TEXT;
+        CREATE INDEX idx_todos_pending_reminders
+            ON todos (remind_at) WHERE remind_at IS NOT NULL AND reminded_at IS NULL;
+    ",
+    },
+    Migration {
+        // Full-text index for `search`, one row per todo with the same
+        // rowid. `tags` holds the todo's tag names separated by spaces.
+        name: "create_search",
+        sql: "
+        CREATE VIRTUAL TABLE todos_fts USING fts5 (
+            text, notes, tags,
+            tokenize = 'unicode61 remove_diacritics 2',
+            prefix = '2 3'
+        );
+        INSERT INTO todos_fts (rowid, text, notes, tags)
+            SELECT id, text, notes,
+                   (SELECT group_concat(tags.name, ' ') FROM todo_tags
+                    JOIN tags ON tags.id = todo_tags.tag_id
+                    WHERE todo_tags.todo_id = todos.id)
+            FROM todos;
+
+        CREATE TRIGGER todos_fts_insert AFTER INSERT ON todos BEGIN
+            INSERT INTO todos_fts (rowid, text, notes, tags)
+                VALUES (new.id, new.text, new.notes, NULL);
+        END;
+        CREATE TRIGGER todos_fts_update AFTER UPDATE OF text, notes ON todos BEGIN
+            UPDATE todos_fts SET text = new.text, notes = new.notes WHERE rowid = new.id;
+        END;
+        CREATE TRIGGER todos_fts_delete AFTER DELETE ON todos BEGIN
+            DELETE FROM todos_fts WHERE rowid = old.id;
+        END;
+
+        CREATE TRIGGER todo_tags_fts_insert AFTER INSERT ON todo_tags BEGIN
+            UPDATE todos_fts SET tags =
+                (SELECT group_concat(tags.name, ' ') FROM todo_tags
+                 JOIN tags ON tags.id = todo_tags.tag_id
+                 WHERE todo_tags.todo_id = new.todo_id)
+            WHERE rowid = new.todo_id;
+        END;
+        CREATE TRIGGER todo_tags_fts_delete AFTER DELETE ON todo_tags BEGIN
+            UPDATE todos_fts SET tags =
+                (SELECT group_concat(tags.name, ' ') FROM todo_tags
+                 JOIN tags ON tags.id = todo_tags.tag_id
+                 WHERE todo_tags.todo_id = old.todo_id)
+            WHERE rowid = old.todo_id;
+        END;
+        CREATE TRIGGER tags_fts_rename AFTER UPDATE OF name ON tags BEGIN
+            UPDATE todos_fts SET tags =
+                (SELECT group_concat(tags.name, ' ') FROM todo_tags
+                 JOIN tags ON tags.id = todo_tags.tag_id
+                 WHERE todo_tags.todo_id = todos_fts.rowid)
+            WHERE rowid IN (SELECT todo_id FROM todo_tags WHERE tag_id = new.id);
+        END;
+    ",
+    },
+    Migration {
+        // A random UUID per todo that survives export and import, unlike
+        // `id`. The trigger fills it in for every insert that leaves it out.
+        name: "add_uids",
+        sql: "
+        ALTER TABLE todos ADD COLUMN uid TEXT;
+        UPDATE todos SET uid = lower(printf('%s-%s-4%s-%s%s-%s',
+            hex(randomblob(4)), hex(randomblob(2)), substr(hex(randomblob(2)), 2),
+            substr('89ab', 1 + abs(random() % 4), 1), substr(hex(randomblob(2)), 2),
+            hex(randomblob(6))));
+        CREATE UNIQUE INDEX idx_todos_uid ON todos (uid);
+        CREATE TRIGGER todos_uid AFTER INSERT ON todos WHEN new.uid IS NULL BEGIN
+            UPDATE todos SET uid = lower(printf('%s-%s-4%s-%s%s-%s',
+                hex(randomblob(4)), hex(randomblob(2)), substr(hex(randomblob(2)), 2),
+                substr('89ab', 1 + abs(random() % 4), 1), substr(hex(randomblob(2)), 2),
+                hex(randomblob(6))))
+            WHERE id = new.id;
+        END;
+    ",
+    },
+];
+
+/// Schema version written by the newest migration this build knows.
+pub const LATEST_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Brings the schema up to [`LATEST_VERSION`] and returns the versions that
+/// were applied.
+pub fn run(conn: &mut Connection) -> Result<Vec<u32>> {
+    let current: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if current > LATEST_VERSION {
+        return Err(Error::SchemaTooNew {
+            found: current,
+            supported: LATEST_VERSION,
+        });
+    }
+
+    let mut applied = Vec::new();
+    for (version, migration) in (1..).zip(MIGRATIONS).skip(current as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.sql)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+        info!(version, name = migration.name, "applied migration");
+        applied.push(version);
+    }
+
+    if applied.is_empty() {
+        info!(version = current, "database schema is up to date");
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Local};
+
+    use super::*;
+
+    fn user_version(conn: &Connection) -> u32 {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn applies_every_migration_once_in_order() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(
+            run(&mut conn).unwrap(),
+            (1..=LATEST_VERSION).collect::<Vec<_>>()
+        );
+        assert_eq!(user_version(&conn), LATEST_VERSION);
+        assert!(run(&mut conn).unwrap().is_empty());
+        assert_eq!(user_version(&conn), LATEST_VERSION);
+    }
+
+    /// The local day of a UTC timestamp, which is what the backfill files
+    /// old todos under.
+    fn local_day(at: &str) -> String {
+        DateTime::parse_from_rfc3339(at)
+            .unwrap()
+            .with_timezone(&Local)
+            .date_naive()
+            .to_string()
+    }
+
+    #[test]
+    fn upgrades_a_webview_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE todos (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 text TEXT NOT NULL,
+                 completed BOOLEAN NOT NULL DEFAULT 0,
+                 created_at TEXT NOT NULL,
+                 sort_order INTEGER NOT NULL DEFAULT 0
+             );
+             INSERT INTO todos (text, completed, created_at) VALUES
+                 ('Buy milk', 1, '2026-10-19T12:00:00.000Z'),
+                 ('Call the bank', 0, '2026-10-20T23:30:00.000Z'),
+                 ('Deleted', 0, '2026-10-20T12:00:00.000Z');
+             DELETE FROM todos WHERE text = 'Deleted';",
+        )
+        .unwrap();
+        assert_eq!(run(&mut conn).unwrap().len(), LATEST_VERSION as usize);
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT text, scheduled_date, sort_order, uid IS NOT NULL FROM todos ORDER BY id",
+            )
+            .unwrap();
+        let rows: Vec<(String, String, i64, bool)> = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [
+                (
+                    "Buy milk".to_owned(),
+                    local_day("2026-10-19T12:00:00.000Z"),
+                    1,
+                    true
+                ),
+                (
+                    "Call the bank".to_owned(),
+                    local_day("2026-10-20T23:30:00.000Z"),
+                    2,
+                    true
+                )
+            ]
+        );
+        // Ids of deleted todos are not handed out again.
+        let id: i64 = conn
+            .query_row(
+                "INSERT INTO todos (text, scheduled_date, created_at, updated_at)
+                 VALUES ('New', '2026-10-20', '', '') RETURNING id",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(id, 4);
+    }
+
+    #[test]
+    fn refuses_a_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", LATEST_VERSION + 1)
+            .unwrap();
+        assert!(matches!(
+            run(&mut conn),
+            Err(Error::SchemaTooNew { found, supported })
+                if found == LATEST_VERSION + 1 && supported == LATEST_VERSION
+        ));
+        assert_eq!(user_version(&conn), LATEST_VERSION + 1);
+    }
+}
@@ -0,0 +1,296 @@
+//! Markdown notes attached to todos.
+//!
+//! Notes are stored as the Markdown the user typed. [`render`] turns them
+//! into HTML that is safe to insert into the webview and collects the links
+//! they contain, which are the only URLs the app opens on a todo's behalf.
+
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use rusqlite::params;
+use serde::Serialize;
+use url::Url;
+
+use super::{get_todo, now, Error, Result, Store, Todo};
+
+/// Schemes a link may use to be rendered and opened.
+const LINK_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RenderedNotes {
+    /// Sanitized HTML; scripts, styles, images and unsafe URLs are removed.
+    pub html: String,
+    /// Absolute http(s) and mailto links in order of appearance, without
+    /// duplicates.
+    pub links: Vec<String>,
+}
+
+impl Store {
+    /// Replaces a todo's notes; `None` or blank text removes them.
+    #[tracing::instrument(skip(self, notes))]
+    pub fn set_notes(&self, id: i64, notes: Option<&str>) -> Result<Todo> {
+        let notes = notes.filter(|n| !n.trim().is_empty());
+        let conn = self.conn();
+        let changed = conn.execute(
+            "UPDATE todos SET notes = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id, notes, now()],
+        )?;
+        if changed == 0 {
+            return Err(Error::NotFound(id));
+        }
+        get_todo(&conn, id)
+    }
+
+    pub fn render_notes(&self, id: i64) -> Result<RenderedNotes> {
+        let todo = self.get_todo(id)?;
+        Ok(render(todo.notes.as_deref().unwrap_or_default()))
+    }
+}
+
+/// Renders Markdown to sanitized HTML. Bare http(s) URLs in the text become
+/// links as well.
+pub fn render(markdown: &str) -> RenderedNotes {
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+    let mut events = Vec::new();
+    let mut links = Vec::new();
+    // Text inside links and code is left alone.
+    let mut verbatim = 0usize;
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Link {
+                link_type,
+                ref dest_url,
+                ..
+            }) => {
+                let url = match link_type {
+                    LinkType::Email => format!("mailto:{dest_url}"),
+                    _ => dest_url.to_string(),
+                };
+                push_link(&mut links, &url);
+                verbatim += 1;
+                events.push(event);
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                verbatim += 1;
+                events.push(event);
+            }
+            Event::End(TagEnd::Link | TagEnd::CodeBlock) => {
+                verbatim = verbatim.saturating_sub(1);
+                events.push(event);
+            }
+            Event::Text(text) if verbatim == 0 => autolink(text, &mut events, &mut links),
+            event => events.push(event),
+        }
+    }
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+    let html = ammonia::Builder::default()
+        .url_schemes(LINK_SCHEMES.into())
+        .url_relative(ammonia::UrlRelative::Deny)
+        .rm_tags(["img"])
+        // Task list checkboxes. Any other input raw HTML asks for becomes a
+        // disabled checkbox too, never a field that takes text.
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["checked"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "")
+        .link_rel(Some("noopener noreferrer"))
+        .clean(&unsafe_html)
+        .to_string();
+    RenderedNotes { html, links }
+}
+
+/// Whether `url` is one of the links `markdown` renders, and so may be
+/// opened.
+pub fn links_to(markdown: &str, url: &str) -> bool {
+    render(markdown).links.iter().any(|link| link == url)
+}
+
+/// Splits bare URLs out of a text event into link events.
+fn autolink<'a>(text: CowStr<'a>, events: &mut Vec<Event<'a>>, links: &mut Vec<String>) {
+    let mut rest: &str = &text;
+    let mut found = false;
+    while let Some((start, end)) = find_url(rest) {
+        found = true;
+        let url = &rest[start..end];
+        if start > 0 {
+            events.push(Event::Text(rest[..start].to_owned().into()));
+        }
+        push_link(links, url);
+        events.push(Event::Start(Tag::Link {
+            link_type: LinkType::Autolink,
+            dest_url: url.to_owned().into(),
+            title: CowStr::Borrowed(""),
+            id: CowStr::Borrowed(""),
+        }));
+        events.push(Event::Text(url.to_owned().into()));
+        events.push(Event::End(TagEnd::Link));
+        rest = &rest[end..];
+    }
+    if !found {
+        events.push(Event::Text(text));
+    } else if !rest.is_empty() {
+        events.push(Event::Text(rest.to_owned().into()));
+    }
+}
+
+/// Byte range of the first bare http(s) URL in `text`. Trailing punctuation
+/// is left out, so "see https://example.com." links without the period.
+fn find_url(text: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    while let Some(found) = text[offset..].find("http") {
+        let start = offset + found;
+        let candidate = &text[start..];
+        let len = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'))
+            .unwrap_or(candidate.len());
+        let url = candidate[..len].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '\'']);
+        let at_word_start = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        if at_word_start
+            && (url.starts_with("https://") || url.starts_with("http://"))
+            && Url::parse(url).is_ok_and(|u| u.has_host())
+        {
+            return Some((start, start + url.len()));
+        }
+        offset = start + 4;
+    }
+    None
+}
+
+fn push_link(links: &mut Vec<String>, url: &str) {
+    let allowed = Url::parse(url).is_ok_and(|u| LINK_SCHEMES.contains(&u.scheme()));
+    if allowed && !links.iter().any(|link| link == url) {
+        links.push(url.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts the rendered `html` holds none of `needles`.
+    fn assert_stripped(html: &str, needles: &[&str]) {
+        for needle in needles {
+            assert!(!html.contains(needle), "{needle:?} left in {html:?}");
+        }
+    }
+
+    #[test]
+    fn strips_scripts_and_embeds() {
+        let notes = render(
+            "Hi <script>alert(1)</script>\n\n\
+             <img src=x onerror=alert(2)>\n\n\
+             ![logo](https://example.com/logo.png)\n\n\
+             <iframe src=\"https://example.com\"></iframe>\n\n\
+             <style>body { display: none }</style>",
+        );
+        assert_stripped(
+            &notes.html,
+            &[
+                "<script", "alert", "<img", "onerror", "<iframe", "<style", "display",
+            ],
+        );
+        assert!(notes.html.contains("Hi"));
+    }
+
+    #[test]
+    fn strips_unsafe_links() {
+        let notes = render(
+            "[run](javascript:alert(1)) [page](data:text/html;base64,PHNjcmlwdD4=) \
+             [vb](vbscript:msgbox) [local](/etc/passwd) <a href=\"javascript:alert(2)\">raw</a>",
+        );
+        assert_stripped(
+            &notes.html,
+            &["javascript:", "data:", "vbscript:", "/etc/passwd"],
+        );
+        assert!(notes.links.is_empty());
+    }
+
+    #[test]
+    fn strips_injected_attributes() {
+        let notes = render(
+            "<a href=\"https://example.com\" onclick=\"alert(1)\" style=\"position: fixed\">x</a>\n\n\
+             <p title=\"t\" onmouseover=\"alert(2)\">y</p>\n\n\
+             [z](https://example.com/\"onmouseover=\"alert(3))",
+        );
+        assert_stripped(
+            &notes.html,
+            &["onclick", "onmouseover=\"", "style=", "alert(1)"],
+        );
+        assert!(notes
+            .html
+            .contains("<a href=\"https://example.com\" rel=\"noopener noreferrer\">x</a>"));
+    }
+
+    #[test]
+    fn collects_links_in_order() {
+        let notes = render(
+            "See https://example.com/a. Then [mail me](mailto:me@example.com), \
+             <https://example.com/b> and https://example.com/a again.\n\n\
+             `https://example.com/code` and xhttps://example.com/c stay text.",
+        );
+        assert_eq!(
+            notes.links,
+            [
+                "https://example.com/a",
+                "mailto:me@example.com",
+                "https://example.com/b"
+            ]
+        );
+        assert!(notes.html.contains(
+            "<a href=\"https://example.com/a\" rel=\"noopener noreferrer\">https://example.com/a</a>."
+        ));
+    }
+
+    #[test]
+    fn keeps_task_list_checkboxes() {
+        let html = render("- [x] Pack\n- [ ] Leave").html;
+        assert_eq!(html.matches("<input").count(), 2);
+        assert_eq!(html.matches("type=\"checkbox\"").count(), 2);
+        assert_eq!(html.matches("disabled").count(), 2);
+        assert_eq!(html.matches("checked").count(), 1);
+    }
+
+    #[test]
+    fn strips_form_fields() {
+        let html = render(
+            "<input type=\"text\" value=\"name\">\n\n\
+             <input type=\"password\" name=\"pw\" autofocus>\n\n\
+             Inline <input type=submit formaction=\"https://example.com\"> \
+             <input type=\"checkbox\">",
+        )
+        .html;
+        assert_stripped(
+            &html,
+            &[
+                "text",
+                "password",
+                "submit",
+                "value",
+                "name=",
+                "autofocus",
+                "formaction",
+            ],
+        );
+        assert_eq!(html.matches("<input").count(), 4);
+        assert_eq!(html.matches("type=\"checkbox\"").count(), 4);
+        assert_eq!(html.matches("disabled").count(), 4);
+    }
+
+    #[test]
+    fn opens_only_links_in_the_notes() {
+        let notes =
+            "Tickets at https://example.com/tickets and [map](https://maps.example.com)\n\n\
+                     [run](javascript:alert(1)) `https://example.com/code`";
+        assert!(links_to(notes, "https://example.com/tickets"));
+        assert!(links_to(notes, "https://maps.example.com"));
+        assert!(!links_to(notes, "https://example.com/other"));
+        assert!(!links_to(notes, "https://example.com/tickets/../admin"));
+        assert!(!links_to(notes, "javascript:alert(1)"));
+        assert!(!links_to(notes, "https://example.com/code"));
+        assert!(!links_to("", "https://example.com"));
+    }
+}
@@ -0,0 +1,312 @@
+//! Todo priorities and the optional priority-first ordering of lists.
+//!
+//! `sort_order` always holds the manual drag order. In [`SortMode::Priority`]
+//! lists are sorted by priority first and by that manual order within each
+//! priority, so switching modes never loses an arrangement.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{get_todo, now, put_setting, setting, Error, Result, Store, Todo};
+
+/// Settings key holding the [`SortMode`].
+pub const SORT_SETTING: &str = "sort_mode";
+
+/// Stored as 0 (none) to 4 (urgent), so higher sorts first with `DESC`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::Low,
+        Self::Medium,
+        Self::High,
+        Self::Urgent,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Urgent => "urgent",
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let trimmed = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_str().eq_ignore_ascii_case(trimmed))
+            .ok_or_else(|| Error::InvalidPriority(value.to_owned()))
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql for Priority {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok((*self as i64).into())
+    }
+}
+
+impl FromSql for Priority {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let level = value.as_i64()?;
+        usize::try_from(level)
+            .ok()
+            .and_then(|level| Self::ALL.get(level).copied())
+            .ok_or(FromSqlError::OutOfRange(level))
+    }
+}
+
+/// How todo lists are ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Manual drag order only.
+    #[default]
+    Manual,
+    /// Higher priorities first, manual order within a priority.
+    Priority,
+}
+
+impl SortMode {
+    /// `ORDER BY` clause for a list whose manual position is in `position`.
+    /// Incomplete todos always come before completed ones.
+    pub(super) fn order_by(self, position: &str) -> String {
+        let priority = match self {
+            Self::Manual => "",
+            Self::Priority => "priority DESC, ",
+        };
+        format!("ORDER BY completed ASC, {priority}{position} ASC, created_at DESC")
+    }
+
+    /// Puts ids given in manual order into the order the list will show
+    /// them in. Sorting is stable, so a drag keeps its effect within a
+    /// priority while a todo dropped among another priority snaps back to
+    /// its own group.
+    pub(super) fn arrange(self, todos: &mut [(i64, Priority)]) {
+        if self == Self::Priority {
+            todos.sort_by_key(|&(_, priority)| std::cmp::Reverse(priority));
+        }
+    }
+}
+
+impl FromStr for SortMode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "manual" => Ok(Self::Manual),
+            "priority" => Ok(Self::Priority),
+            _ => Err(Error::InvalidSortMode(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Manual => "manual",
+            Self::Priority => "priority",
+        })
+    }
+}
+
+impl Store {
+    #[tracing::instrument(skip(self))]
+    pub fn set_priority(&self, id: i64, priority: Priority) -> Result<Todo> {
+        let conn = self.conn();
+        let changed = conn.execute(
+            "UPDATE todos SET priority = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id, priority, now()],
+        )?;
+        if changed == 0 {
+            return Err(Error::NotFound(id));
+        }
+        get_todo(&conn, id)
+    }
+
+    pub fn sort_mode(&self) -> Result<SortMode> {
+        sort_mode(&self.conn())
+    }
+
+    pub fn set_sort_mode(&self, mode: SortMode) -> Result<()> {
+        put_setting(&self.conn(), SORT_SETTING, &mode.to_string())
+    }
+}
+
+/// The configured [`SortMode`]; an unreadable value falls back to manual.
+pub(super) fn sort_mode(conn: &Connection) -> Result<SortMode> {
+    Ok(setting(conn, SORT_SETTING)?
+        .and_then(|mode| mode.parse().ok())
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::NewTodo;
+
+    const DAY: &str = "2026-10-20";
+
+    /// Adds todos named after their priority, in the given order.
+    fn day(store: &Store, todos: &[(&str, Priority)]) -> Vec<Todo> {
+        todos
+            .iter()
+            .map(|&(text, priority)| {
+                let todo = NewTodo {
+                    priority,
+                    ..todo(text, DAY)
+                };
+                add(store, todo)
+            })
+            .collect()
+    }
+
+    fn listed(store: &Store) -> Vec<String> {
+        store
+            .todos_by_date(DAY)
+            .unwrap()
+            .into_iter()
+            .map(|todo| todo.text)
+            .collect()
+    }
+
+    #[test]
+    fn arranges_by_priority_keeping_manual_order() {
+        let mut todos = [
+            (1, Priority::Low),
+            (2, Priority::High),
+            (3, Priority::None),
+            (4, Priority::High),
+            (5, Priority::Low),
+        ];
+        SortMode::Manual.arrange(&mut todos);
+        assert_eq!(todos.map(|(id, _)| id), [1, 2, 3, 4, 5]);
+        SortMode::Priority.arrange(&mut todos);
+        assert_eq!(todos.map(|(id, _)| id), [2, 4, 1, 5, 3]);
+    }
+
+    #[test]
+    fn orders_by_priority_only_when_asked() {
+        assert_eq!(
+            SortMode::Manual.order_by("sort_order"),
+            "ORDER BY completed ASC, sort_order ASC, created_at DESC"
+        );
+        assert_eq!(
+            SortMode::Priority.order_by("project_order"),
+            "ORDER BY completed ASC, priority DESC, project_order ASC, created_at DESC"
+        );
+    }
+
+    #[test]
+    fn switching_modes_keeps_the_manual_order() {
+        let store = store();
+        let todos = day(
+            &store,
+            &[
+                ("a", Priority::None),
+                ("b", Priority::High),
+                ("c", Priority::Low),
+                ("d", Priority::High),
+            ],
+        );
+        store.set_completed(todos[1].id, true).unwrap();
+        assert_eq!(listed(&store), ["a", "c", "d", "b"]);
+        store.set_sort_mode(SortMode::Priority).unwrap();
+        assert_eq!(store.sort_mode().unwrap(), SortMode::Priority);
+        assert_eq!(listed(&store), ["d", "c", "a", "b"]);
+        store.set_sort_mode(SortMode::Manual).unwrap();
+        assert_eq!(listed(&store), ["a", "c", "d", "b"]);
+    }
+
+    #[test]
+    fn reorders_in_manual_mode() {
+        let store = store();
+        let todos = day(
+            &store,
+            &[
+                ("a", Priority::None),
+                ("b", Priority::High),
+                ("c", Priority::Low),
+                ("d", Priority::High),
+            ],
+        );
+        store.set_completed(todos[3].id, true).unwrap();
+        let ids: Vec<i64> = todos.iter().rev().map(|todo| todo.id).collect();
+        store.reorder_todos(&[&ids[..], &[999]].concat()).unwrap();
+        // A completed todo dragged to the top still goes below the rest.
+        assert_eq!(listed(&store), ["c", "b", "a", "d"]);
+    }
+
+    #[test]
+    fn reorders_within_priorities_in_priority_mode() {
+        let store = store();
+        let todos = day(
+            &store,
+            &[
+                ("a", Priority::None),
+                ("b", Priority::High),
+                ("c", Priority::Low),
+                ("d", Priority::High),
+            ],
+        );
+        store.set_sort_mode(SortMode::Priority).unwrap();
+        assert_eq!(listed(&store), ["b", "d", "c", "a"]);
+
+        // "a" dragged to the top snaps back to its group; "d" dragged above
+        // "b" stays there.
+        let [a, b, c, d] = [0, 1, 2, 3].map(|i| todos[i].id);
+        store.reorder_todos(&[a, d, b, c]).unwrap();
+        assert_eq!(listed(&store), ["d", "b", "c", "a"]);
+        // The manual order follows what the list showed.
+        store.set_sort_mode(SortMode::Manual).unwrap();
+        assert_eq!(listed(&store), ["d", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn parses_priorities_and_modes() {
+        assert_eq!(" HIGH ".parse::<Priority>().unwrap(), Priority::High);
+        assert!(matches!(
+            "critical".parse::<Priority>(),
+            Err(Error::InvalidPriority(_))
+        ));
+        assert!(matches!(
+            "random".parse::<SortMode>(),
+            Err(Error::InvalidSortMode(_))
+        ));
+        let store = store();
+        put_setting(&store.conn(), SORT_SETTING, "random").unwrap();
+        assert_eq!(store.sort_mode().unwrap(), SortMode::Manual);
+        assert!(matches!(
+            store.set_priority(999, Priority::High),
+            Err(Error::NotFound(999))
+        ));
+    }
+}
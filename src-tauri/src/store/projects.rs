@@ -0,0 +1,572 @@
+//! Projects: named lists that group todos independently of the day they are
+//! scheduled on.
+//!
+//! A todo belongs to at most one project and has its own position within it
+//! (`project_order`), separate from its position on its day, so reordering
+//! one view never disturbs the other.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+use super::attachments;
+use super::tags::{json_list, parse_color};
+use super::{now, priority, query_todos, Error, Result, Store, Todo};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    /// `#rrggbb`, or `None` for the default color.
+    pub color: Option<String>,
+    /// Short free-form icon, typically an emoji.
+    pub icon: Option<String>,
+    pub sort_order: i64,
+    pub archived: bool,
+    pub created_at: String,
+}
+
+impl Project {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            color: row.get("color")?,
+            icon: row.get("icon")?,
+            sort_order: row.get("sort_order")?,
+            archived: row.get("archived")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSummary {
+    #[serde(flatten)]
+    pub project: Project,
+    pub active_count: usize,
+    pub completed_count: usize,
+}
+
+/// What [`Store::delete_project`] does with the project's todos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectDeletion {
+    /// Delete them along with the project.
+    Cascade,
+    /// Move them to another project, or out of any project with `None`.
+    Reassign(Option<i64>),
+}
+
+const PROJECT_ORDER_BY: &str = "ORDER BY completed ASC, project_order ASC, created_at DESC";
+
+impl Store {
+    /// Projects in their manual order with their todo counts. Archived
+    /// projects are left out unless asked for.
+    pub fn projects(&self, include_archived: bool) -> Result<Vec<ProjectSummary>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT projects.*,
+                 COUNT(todos.id) FILTER (WHERE todos.completed = 0) AS active_count,
+                 COUNT(todos.id) FILTER (WHERE todos.completed = 1) AS completed_count
+             FROM projects LEFT JOIN todos ON todos.project_id = projects.id
+             WHERE ?1 OR NOT projects.archived
+             GROUP BY projects.id
+             ORDER BY projects.sort_order, projects.id",
+        )?;
+        let projects = stmt
+            .query_map([include_archived], |row| {
+                Ok(ProjectSummary {
+                    project: Project::from_row(row)?,
+                    active_count: row.get("active_count")?,
+                    completed_count: row.get("completed_count")?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(projects)
+    }
+
+    /// Looks a project up by its case-insensitive name.
+    pub fn project_by_name(&self, name: &str) -> Result<Project> {
+        let conn = self.conn();
+        conn.query_row(
+            "SELECT * FROM projects WHERE name = ?1",
+            [name.trim()],
+            Project::from_row,
+        )
+        .optional()?
+        .ok_or_else(|| Error::ProjectNotFound(name.trim().to_owned()))
+    }
+
+    /// Creates a project after the existing ones.
+    #[tracing::instrument(skip(self))]
+    pub fn create_project(
+        &self,
+        name: &str,
+        color: Option<&str>,
+        icon: Option<&str>,
+    ) -> Result<Project> {
+        let name = parse_name(name)?;
+        let color = color.map(parse_color).transpose()?;
+        let icon = parse_icon(icon);
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        ensure_unique(&tx, &name, None)?;
+        let project = tx.query_row(
+            "INSERT INTO projects (name, color, icon, sort_order, created_at)
+             VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM projects), ?4)
+             RETURNING *",
+            params![name, color, icon, now()],
+            Project::from_row,
+        )?;
+        tx.commit()?;
+        Ok(project)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn rename_project(&self, id: i64, name: &str) -> Result<Project> {
+        let name = parse_name(name)?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        ensure_unique(&tx, &name, Some(id))?;
+        let project = update_project(&tx, id, "name = ?2", params![id, name])?;
+        tx.commit()?;
+        Ok(project)
+    }
+
+    /// Sets a project's color and icon; `None` resets either to the default.
+    pub fn set_project_style(
+        &self,
+        id: i64,
+        color: Option<&str>,
+        icon: Option<&str>,
+    ) -> Result<Project> {
+        let color = color.map(parse_color).transpose()?;
+        let conn = self.conn();
+        update_project(
+            &conn,
+            id,
+            "color = ?2, icon = ?3",
+            params![id, color, parse_icon(icon)],
+        )
+    }
+
+    /// Archived projects keep their todos but drop out of [`Store::projects`]
+    /// by default.
+    #[tracing::instrument(skip(self))]
+    pub fn archive_project(&self, id: i64, archived: bool) -> Result<Project> {
+        let conn = self.conn();
+        update_project(&conn, id, "archived = ?2", params![id, archived])
+    }
+
+    /// Rewrites the project order to match `ids`. Unknown ids are ignored.
+    pub fn reorder_projects(&self, ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare("UPDATE projects SET sort_order = ?1 WHERE id = ?2")?;
+            for (position, id) in ids.iter().enumerate() {
+                stmt.execute(params![position as i64, id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Deletes a project, either with its todos or after moving them
+    /// elsewhere. Returns how many todos were deleted or moved.
+    #[tracing::instrument(skip(self))]
+    pub fn delete_project(&self, id: i64, todos: ProjectDeletion) -> Result<usize> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        find_project(&tx, id)?;
+        let affected = match todos {
+            ProjectDeletion::Cascade => {
+                tx.execute("DELETE FROM todos WHERE project_id = ?1", [id])?
+            }
+            ProjectDeletion::Reassign(target) => {
+                let ids = project_todo_ids(&tx, id)?;
+                assign(&tx, &ids, target)?;
+                ids.len()
+            }
+        };
+        tx.execute("DELETE FROM projects WHERE id = ?1", [id])?;
+        tx.commit()?;
+        if todos == ProjectDeletion::Cascade {
+            attachments::collect_garbage(&conn, self.attachments_dir.as_deref())?;
+        }
+        Ok(affected)
+    }
+
+    /// Todos of a project across all days, in the project's own order.
+    pub fn project_todos(&self, id: i64) -> Result<Vec<Todo>> {
+        let conn = self.conn();
+        find_project(&conn, id)?;
+        let order_by = priority::sort_mode(&conn)?.order_by("project_order");
+        query_todos(
+            &conn,
+            &format!("SELECT * FROM todos WHERE project_id = ?1 {order_by}"),
+            [id],
+        )
+    }
+
+    /// Moves todos to the end of another project, or out of any project with
+    /// `None`. Their scheduled days do not change.
+    #[tracing::instrument(skip(self))]
+    pub fn move_todos_to_project(&self, ids: &[i64], project_id: Option<i64>) -> Result<Vec<Todo>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        for &id in ids {
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM todos WHERE id = ?1)",
+                [id],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                return Err(Error::NotFound(id));
+            }
+        }
+        assign(&tx, ids, project_id)?;
+        let todos = query_todos(
+            &tx,
+            "SELECT * FROM todos WHERE id IN (SELECT value FROM json_each(?1))
+             ORDER BY project_order",
+            [json_list(ids)],
+        )?;
+        tx.commit()?;
+        Ok(todos)
+    }
+
+    /// Rewrites a project's todo order to match `ids`, keeping incomplete
+    /// todos ahead of completed ones and grouping by priority like
+    /// [`Store::reorder_todos`] does. Ids of todos
+    /// outside the project are ignored.
+    pub fn reorder_project_todos(&self, id: i64, ids: &[i64]) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        find_project(&tx, id)?;
+        let mut incomplete = Vec::new();
+        let mut completed = Vec::new();
+        {
+            let mut stmt = tx.prepare(
+                "SELECT completed, priority FROM todos WHERE id = ?1 AND project_id = ?2",
+            )?;
+            for &todo_id in ids {
+                match stmt
+                    .query_row(params![todo_id, id], |row| {
+                        Ok((row.get::<_, bool>(0)?, row.get(1)?))
+                    })
+                    .optional()?
+                {
+                    Some((true, priority)) => completed.push((todo_id, priority)),
+                    Some((false, priority)) => incomplete.push((todo_id, priority)),
+                    None => {}
+                }
+            }
+            let mode = priority::sort_mode(&tx)?;
+            mode.arrange(&mut incomplete);
+            mode.arrange(&mut completed);
+            let mut stmt = tx.prepare("UPDATE todos SET project_order = ?1 WHERE id = ?2")?;
+            for (position, (todo_id, _)) in incomplete.iter().chain(&completed).enumerate() {
+                stmt.execute(params![position as i64, todo_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Position after the last todo of a project, failing if the project does
+/// not exist.
+pub(super) fn next_order(conn: &Connection, project_id: i64) -> Result<i64> {
+    find_project(conn, project_id)?;
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(project_order), 0) + 1 FROM todos WHERE project_id = ?1",
+        [project_id],
+        |row| row.get(0),
+    )?)
+}
+
+/// Puts todos at the end of `project_id` in the given order.
+fn assign(conn: &Connection, ids: &[i64], project_id: Option<i64>) -> Result<()> {
+    let mut order = match project_id {
+        Some(project_id) => next_order(conn, project_id)?,
+        None => 0,
+    };
+    let now = now();
+    let mut stmt = conn.prepare_cached(
+        "UPDATE todos SET project_id = ?2, project_order = ?3, updated_at = ?4 WHERE id = ?1",
+    )?;
+    for id in ids {
+        stmt.execute(params![id, project_id, order, now])?;
+        if project_id.is_some() {
+            order += 1;
+        }
+    }
+    Ok(())
+}
+
+fn project_todo_ids(conn: &Connection, id: i64) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id FROM todos WHERE project_id = ?1 {PROJECT_ORDER_BY}"
+    ))?;
+    let ids = stmt.query_map([id], |row| row.get(0))?;
+    Ok(ids.collect::<rusqlite::Result<_>>()?)
+}
+
+fn find_project(conn: &Connection, id: i64) -> Result<Project> {
+    conn.query_row(
+        "SELECT * FROM projects WHERE id = ?1",
+        [id],
+        Project::from_row,
+    )
+    .optional()?
+    .ok_or_else(|| Error::ProjectNotFound(id.to_string()))
+}
+
+fn update_project(
+    conn: &Connection,
+    id: i64,
+    assignments: &str,
+    params: impl rusqlite::Params,
+) -> Result<Project> {
+    conn.query_row(
+        &format!("UPDATE projects SET {assignments} WHERE id = ?1 RETURNING *"),
+        params,
+        Project::from_row,
+    )
+    .optional()?
+    .ok_or_else(|| Error::ProjectNotFound(id.to_string()))
+}
+
+/// Refuses `name` when a project other than `except` already has it.
+fn ensure_unique(conn: &Connection, name: &str, except: Option<i64>) -> Result<()> {
+    let taken: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM projects WHERE name = ?1 AND id IS NOT ?2)",
+        params![name, except],
+        |row| row.get(0),
+    )?;
+    if taken {
+        return Err(Error::ProjectExists(name.to_owned()));
+    }
+    Ok(())
+}
+
+fn parse_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::EmptyProjectName);
+    }
+    Ok(name.to_owned())
+}
+
+fn parse_icon(icon: Option<&str>) -> Option<String> {
+    icon.map(str::trim)
+        .filter(|icon| !icon.is_empty())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::NewTodo;
+
+    const DAY: &str = "2026-10-20";
+
+    fn in_project(store: &Store, project: &Project, text: &str) -> Todo {
+        let todo = NewTodo {
+            project_id: Some(project.id),
+            ..todo(text, DAY)
+        };
+        add(store, todo)
+    }
+
+    fn texts(todos: &[Todo]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.text.as_str()).collect()
+    }
+
+    fn counts(store: &Store, include_archived: bool) -> Vec<(String, usize, usize)> {
+        store
+            .projects(include_archived)
+            .unwrap()
+            .into_iter()
+            .map(|summary| {
+                let ProjectSummary {
+                    project,
+                    active_count,
+                    completed_count,
+                } = summary;
+                (project.name, active_count, completed_count)
+            })
+            .collect()
+    }
+
+    /// "Travel" with two open todos and a done one, and "Home" with one.
+    fn projects(store: &Store) -> (Project, Project) {
+        let travel = store.create_project("Travel", None, None).unwrap();
+        let home = store.create_project("Home", None, None).unwrap();
+        in_project(store, &travel, "Book hotel");
+        let tickets = in_project(store, &travel, "Buy tickets");
+        in_project(store, &travel, "Pack");
+        in_project(store, &home, "Fix roof");
+        store.set_completed(tickets.id, true).unwrap();
+        add(store, todo("Call the bank", DAY));
+        (travel, home)
+    }
+
+    #[test]
+    fn counts_open_and_done_todos() {
+        let store = store();
+        let (travel, _) = projects(&store);
+        let garden = store.create_project("Garden", None, None).unwrap();
+        assert_eq!(
+            counts(&store, false),
+            [
+                ("Travel".to_owned(), 2, 1),
+                ("Home".to_owned(), 1, 0),
+                ("Garden".to_owned(), 0, 0)
+            ]
+        );
+        store.archive_project(travel.id, true).unwrap();
+        assert_eq!(counts(&store, false).len(), 2);
+        assert_eq!(counts(&store, true).len(), 3);
+        store.reorder_projects(&[garden.id, travel.id]).unwrap();
+        assert_eq!(counts(&store, true)[0].0, "Garden");
+    }
+
+    #[test]
+    fn deleting_with_cascade_deletes_the_todos() {
+        let store = store();
+        let (travel, home) = projects(&store);
+        assert_eq!(
+            store
+                .delete_project(travel.id, ProjectDeletion::Cascade)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            texts(&store.todos_by_date(DAY).unwrap()),
+            ["Fix roof", "Call the bank"]
+        );
+        assert_eq!(counts(&store, true), [("Home".to_owned(), 1, 0)]);
+        assert!(matches!(
+            store.delete_project(travel.id, ProjectDeletion::Cascade),
+            Err(Error::ProjectNotFound(_))
+        ));
+        assert!(matches!(
+            store.delete_project(home.id, ProjectDeletion::Reassign(Some(travel.id))),
+            Err(Error::ProjectNotFound(_))
+        ));
+        assert_eq!(counts(&store, true), [("Home".to_owned(), 1, 0)]);
+    }
+
+    #[test]
+    fn deleting_with_reassign_appends_the_todos() {
+        let store = store();
+        let (travel, home) = projects(&store);
+        assert_eq!(
+            store
+                .delete_project(travel.id, ProjectDeletion::Reassign(Some(home.id)))
+                .unwrap(),
+            3
+        );
+        let todos = store.project_todos(home.id).unwrap();
+        assert_eq!(
+            texts(&todos),
+            ["Fix roof", "Book hotel", "Pack", "Buy tickets"]
+        );
+        let orders: Vec<i64> = todos
+            .iter()
+            .map(|todo| {
+                store
+                    .conn()
+                    .query_row(
+                        "SELECT project_order FROM todos WHERE id = ?1",
+                        [todo.id],
+                        |row| row.get(0),
+                    )
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(orders, [1, 2, 3, 4]);
+        assert_eq!(counts(&store, true), [("Home".to_owned(), 3, 1)]);
+    }
+
+    #[test]
+    fn deleting_with_reassign_to_none_keeps_the_todos() {
+        let store = store();
+        let (travel, _) = projects(&store);
+        assert_eq!(
+            store
+                .delete_project(travel.id, ProjectDeletion::Reassign(None))
+                .unwrap(),
+            3
+        );
+        let day = store.todos_by_date(DAY).unwrap();
+        assert_eq!(day.len(), 5);
+        let loose = day.iter().filter(|todo| todo.project_id.is_none()).count();
+        assert_eq!(loose, 4);
+    }
+
+    #[test]
+    fn moves_and_reorders_project_todos() {
+        let store = store();
+        let (travel, home) = projects(&store);
+        let day = store.todos_by_date(DAY).unwrap();
+        let bank = day.iter().find(|todo| todo.project_id.is_none()).unwrap();
+        let moved = store
+            .move_todos_to_project(&[bank.id], Some(travel.id))
+            .unwrap();
+        assert_eq!(moved[0].project_id, Some(travel.id));
+        assert_eq!(
+            texts(&store.project_todos(travel.id).unwrap()),
+            ["Book hotel", "Pack", "Call the bank", "Buy tickets"]
+        );
+
+        let ids: Vec<i64> = store
+            .project_todos(travel.id)
+            .unwrap()
+            .iter()
+            .rev()
+            .map(|todo| todo.id)
+            .collect();
+        // The done todo stays last, and the one from another project is
+        // ignored.
+        let roof = store.project_todos(home.id).unwrap()[0].id;
+        store
+            .reorder_project_todos(travel.id, &[&ids[..], &[roof]].concat())
+            .unwrap();
+        assert_eq!(
+            texts(&store.project_todos(travel.id).unwrap()),
+            ["Call the bank", "Pack", "Book hotel", "Buy tickets"]
+        );
+        assert_eq!(texts(&store.project_todos(home.id).unwrap()), ["Fix roof"]);
+        assert!(matches!(
+            store.move_todos_to_project(&[999], Some(travel.id)),
+            Err(Error::NotFound(999))
+        ));
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let store = store();
+        let (travel, _) = projects(&store);
+        assert!(matches!(
+            store.create_project(" travel ", None, None),
+            Err(Error::ProjectExists(_))
+        ));
+        assert!(matches!(
+            store.rename_project(travel.id, "HOME"),
+            Err(Error::ProjectExists(_))
+        ));
+        assert!(matches!(
+            store.create_project("  ", None, None),
+            Err(Error::EmptyProjectName)
+        ));
+        assert_eq!(
+            store.rename_project(travel.id, "Trips").unwrap().name,
+            "Trips"
+        );
+        assert_eq!(store.project_by_name("trips").unwrap().id, travel.id);
+    }
+}
@@ -0,0 +1,684 @@
+//! Natural-language quick-add: "Pay rent tomorrow 9am #home !high every
+//! month +Finance" becomes the todo "Pay rent", due tomorrow at 9:00, tagged
+//! `home`, high priority, repeating monthly, in the Finance project.
+//!
+//! [`parse`] takes the words it recognizes out of the text and reports
+//! where they were, so the add box can highlight them before submitting.
+//! Anything else stays in the text, including `+name` when no project has
+//! that name. Input made only of recognized words, such as "Monday", is
+//! taken literally.
+
+use std::str::FromStr;
+
+use chrono::{
+    Datelike, Days, Month, Months, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc, Weekday,
+};
+use serde::Serialize;
+
+use super::{parse_date, timestamp, NewTodo, Priority, Project, Result, Rule, Store, Todo};
+
+/// Most words a repeat phrase such as "every 2 weeks on monday and
+/// thursday" may span.
+const MAX_RULE_WORDS: usize = 8;
+
+/// What [`parse`] found in quick-add input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuickAdd {
+    /// The input without the recognized parts.
+    pub text: String,
+    /// `YYYY-MM-DD` day named in the input, or the first day of its repeat
+    /// rule.
+    pub date: Option<String>,
+    /// UTC RFC 3339 deadline from a time in the input, on `date` or else on
+    /// the day the todo is added to.
+    pub due_at: Option<String>,
+    /// `#tag` names without the `#`, and `@context` names with their `@`.
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub recurrence: Option<Rule>,
+    pub project_id: Option<i64>,
+    /// Name of the project as stored, however it was written.
+    pub project: Option<String>,
+    /// Recognized parts in input order.
+    pub spans: Vec<Span>,
+}
+
+/// A recognized part of the input. Offsets count UTF-16 code units, as
+/// JavaScript string positions do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SpanKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanKind {
+    Date,
+    Time,
+    Tag,
+    Priority,
+    Recurrence,
+    Project,
+}
+
+impl From<QuickAdd> for NewTodo {
+    fn from(parsed: QuickAdd) -> Self {
+        Self {
+            text: parsed.text,
+            date: parsed.date,
+            due_at: parsed.due_at,
+            project_id: parsed.project_id,
+            priority: parsed.priority.unwrap_or_default(),
+            tags: parsed.tags,
+            recurrence: parsed.recurrence.map(|rule| rule.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+impl Store {
+    /// Parses quick-add `input` against today and the active projects.
+    /// `day` (YYYY-MM-DD) is where the todo goes when the input names no
+    /// day; today when `None`.
+    pub fn parse_quick_add(&self, input: &str, day: Option<&str>) -> Result<QuickAdd> {
+        let today = parse_date(&self.today()?)?;
+        let day = day.map(parse_date).transpose()?.unwrap_or(today);
+        let projects: Vec<Project> = self
+            .projects(false)?
+            .into_iter()
+            .map(|summary| summary.project)
+            .collect();
+        Ok(parse(input, today, day, &projects, &chrono::Local))
+    }
+
+    /// Adds a todo from quick-add input; see [`Store::parse_quick_add`].
+    #[tracing::instrument(skip(self, input))]
+    pub fn quick_add(&self, input: &str, day: Option<&str>) -> Result<Todo> {
+        let mut new = NewTodo::from(self.parse_quick_add(input, day)?);
+        if new.date.is_none() {
+            new.date = day.map(str::to_owned);
+        }
+        self.create_todo(&new)
+    }
+}
+
+/// Parses quick-add `input` typed on `today`. Relative days count from
+/// `today`; `day` is the day a todo with a time but no day is due on.
+/// Times are local to `zone`.
+pub fn parse<Tz: TimeZone>(
+    input: &str,
+    today: NaiveDate,
+    day: NaiveDate,
+    projects: &[Project],
+    zone: &Tz,
+) -> QuickAdd {
+    let mut parser = Parser::new(input);
+    let recurrence = parser.recurrence();
+    let mut tags = Vec::new();
+    let mut priority = None;
+    let mut project = None;
+    for i in 0..parser.tokens.len() {
+        if parser.used[i] {
+            continue;
+        }
+        let word = parser.tokens[i].word;
+        let kind = if let Some(name) = word.strip_prefix('#').filter(|n| is_name(n)) {
+            tags.push(name.to_owned());
+            SpanKind::Tag
+        } else if word.starts_with('@') && is_name(&word[1..]) {
+            tags.push(word.to_owned());
+            SpanKind::Tag
+        } else if let Some(value) = word.strip_prefix('!').filter(|_| priority.is_none()) {
+            let Ok(value) = Priority::from_str(value) else {
+                continue;
+            };
+            priority = Some(value);
+            SpanKind::Priority
+        } else if let Some(name) = word.strip_prefix('+').filter(|_| project.is_none()) {
+            let Some(found) = projects
+                .iter()
+                .find(|p| !name_key(name).is_empty() && name_key(&p.name) == name_key(name))
+            else {
+                continue;
+            };
+            project = Some(found);
+            SpanKind::Project
+        } else {
+            continue;
+        };
+        parser.take(i, 1, kind);
+    }
+    let date = parser.find(SpanKind::Date, |words| date_at(words, today));
+    let time = parser.find(SpanKind::Time, time_at);
+
+    let text = parser.remaining_text();
+    if text.is_empty() {
+        return QuickAdd::literal(input);
+    }
+    let mut date = date;
+    if let (None, Some(rule)) = (date, &recurrence) {
+        if !rule.from_completion {
+            date = rule.occurrences(day).next();
+        }
+    }
+    let due_at = time.and_then(|time| {
+        let local = date.unwrap_or(day).and_time(time);
+        // A time skipped by a daylight saving change moves past the gap.
+        let at = zone.from_local_datetime(&local).earliest().or_else(|| {
+            zone.from_local_datetime(&(local + TimeDelta::hours(1)))
+                .earliest()
+        })?;
+        Some(timestamp(at.with_timezone(&Utc)))
+    });
+    QuickAdd {
+        text,
+        date: date.map(|d| d.to_string()),
+        due_at,
+        tags,
+        priority,
+        recurrence,
+        project_id: project.map(|p| p.id),
+        project: project.map(|p| p.name.clone()),
+        spans: parser.spans(),
+    }
+}
+
+impl QuickAdd {
+    fn literal(input: &str) -> Self {
+        Self {
+            text: input.trim().to_owned(),
+            date: None,
+            due_at: None,
+            tags: Vec::new(),
+            priority: None,
+            recurrence: None,
+            project_id: None,
+            project: None,
+            spans: Vec::new(),
+        }
+    }
+}
+
+struct Token<'a> {
+    /// Byte offsets of `word` in the input.
+    start: usize,
+    end: usize,
+    /// The whitespace-separated word without trailing punctuation.
+    word: &'a str,
+    lower: String,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token<'a>>,
+    /// Recognized runs of tokens as (first, count, kind).
+    taken: Vec<(usize, usize, SpanKind)>,
+    used: Vec<bool>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut rest = input;
+        while let Some(offset) = rest.find(|c: char| !c.is_whitespace()) {
+            let start = input.len() - rest.len() + offset;
+            let len = input[start..]
+                .find(char::is_whitespace)
+                .unwrap_or(input.len() - start);
+            let raw = &input[start..start + len];
+            let word = raw.trim_end_matches([',', '.', ';']);
+            let word = if word.is_empty() { raw } else { word };
+            tokens.push(Token {
+                start,
+                end: start + word.len(),
+                word,
+                lower: word.to_lowercase(),
+            });
+            rest = &input[start + len..];
+        }
+        let used = vec![false; tokens.len()];
+        Self {
+            input,
+            tokens,
+            taken: Vec::new(),
+            used,
+        }
+    }
+
+    fn is_free(&self, first: usize, count: usize) -> bool {
+        first + count <= self.used.len() && !self.used[first..first + count].contains(&true)
+    }
+
+    fn take(&mut self, first: usize, count: usize, kind: SpanKind) {
+        self.used[first..first + count].fill(true);
+        self.taken.push((first, count, kind));
+    }
+
+    /// Takes the longest repeat phrase, preferring earlier ones.
+    fn recurrence(&mut self) -> Option<Rule> {
+        let starts = |word: &str| {
+            matches!(
+                word,
+                "every" | "daily" | "weekly" | "monthly" | "yearly" | "annually"
+            ) || word.parse::<u32>().is_ok()
+        };
+        for first in 0..self.tokens.len() {
+            if !starts(&self.tokens[first].lower) {
+                continue;
+            }
+            let longest = MAX_RULE_WORDS.min(self.tokens.len() - first);
+            for count in (1..=longest).rev() {
+                let phrase: Vec<&str> = self.tokens[first..first + count]
+                    .iter()
+                    .map(|t| t.lower.as_str())
+                    .collect();
+                if let Ok(rule) = phrase.join(" ").parse::<Rule>() {
+                    self.take(first, count, SpanKind::Recurrence);
+                    return Some(rule);
+                }
+            }
+        }
+        None
+    }
+
+    /// Takes the first run of free tokens `matcher` recognizes; it gets the
+    /// lowercase words from a token on and returns how many it used.
+    fn find<T>(
+        &mut self,
+        kind: SpanKind,
+        matcher: impl Fn(&[&str]) -> Option<(usize, T)>,
+    ) -> Option<T> {
+        let words: Vec<&str> = self.tokens.iter().map(|t| t.lower.as_str()).collect();
+        let (first, count, value) = (0..words.len()).find_map(|first| {
+            let (count, value) = matcher(&words[first..])?;
+            self.is_free(first, count).then_some((first, count, value))
+        })?;
+        self.take(first, count, kind);
+        Some(value)
+    }
+
+    fn remaining_text(&self) -> String {
+        let words: Vec<&str> = self
+            .tokens
+            .iter()
+            .zip(&self.used)
+            .filter(|(_, used)| !**used)
+            .map(|(token, _)| {
+                // Keep trailing punctuation that was only trimmed for matching.
+                let end = self.input[token.start..]
+                    .find(char::is_whitespace)
+                    .map_or(self.input.len(), |len| token.start + len);
+                &self.input[token.start..end]
+            })
+            .collect();
+        words.join(" ")
+    }
+
+    fn spans(&self) -> Vec<Span> {
+        let utf16 = |byte: usize| self.input[..byte].encode_utf16().count();
+        let mut spans: Vec<Span> = self
+            .taken
+            .iter()
+            .map(|&(first, count, kind)| Span {
+                start: utf16(self.tokens[first].start),
+                end: utf16(self.tokens[first + count - 1].end),
+                kind,
+            })
+            .collect();
+        spans.sort_by_key(|span| span.start);
+        spans
+    }
+}
+
+pub(super) fn is_name(name: &str) -> bool {
+    name.chars().any(char::is_alphanumeric)
+}
+
+/// Compares project names ignoring case, spaces and punctuation, so
+/// `+side-project` finds "Side Project".
+pub(super) fn name_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A day at the start of `words`: "today", "tomorrow", "next week", a
+/// weekday, "in 3 days", "2026-10-21", "oct 21" or "21 october 2027", each
+/// optionally after "on". Weekdays are the next one after today.
+fn date_at(words: &[&str], today: NaiveDate) -> Option<(usize, NaiveDate)> {
+    let next_weekday = |weekday: Weekday| {
+        let ahead =
+            (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+        today + Days::new(if ahead == 0 { 7 } else { ahead.into() })
+    };
+    match *words {
+        ["today" | "tod", ..] => return Some((1, today)),
+        ["tomorrow" | "tmr" | "tmrw", ..] => return Some((1, today.succ_opt()?)),
+        ["next", "week", ..] => return Some((2, next_weekday(Weekday::Mon))),
+        ["next" | "on", day, ..] if weekday(day, true).is_some() => {
+            return Some((2, next_weekday(weekday(day, true)?)));
+        }
+        [day, ..] if weekday(day, false).is_some() => {
+            return Some((1, next_weekday(weekday(day, false)?)));
+        }
+        ["in", count, unit, ..] => {
+            let count: u32 = match count {
+                "a" | "an" | "one" => 1,
+                count => count.parse().ok()?,
+            };
+            let date = match unit.trim_end_matches('s') {
+                "day" => today.checked_add_days(Days::new(count.into())),
+                "week" => today.checked_add_days(Days::new(u64::from(count) * 7)),
+                "month" => today.checked_add_months(Months::new(count)),
+                "year" => today.checked_add_months(Months::new(count.checked_mul(12)?)),
+                _ => None,
+            }?;
+            return Some((3, date));
+        }
+        ["on", ref rest @ ..] => {
+            return calendar_date(rest, today).map(|(count, date)| (count + 1, date));
+        }
+        _ => {}
+    }
+    calendar_date(words, today)
+}
+
+/// "2026-10-21", "oct 21", "october 21st, 2027" or "21 oct". Without a year
+/// the next such day is meant.
+fn calendar_date(words: &[&str], today: NaiveDate) -> Option<(usize, NaiveDate)> {
+    let first = *words.first()?;
+    if let Ok(date) = parse_date(first) {
+        return Some((1, date));
+    }
+    let (month, day) = match (month(first), words.get(1).copied()) {
+        (Some(month), Some(day)) => (month, day_of_month(day)?),
+        (None, Some(second)) => (month(second)?, day_of_month(first)?),
+        _ => return None,
+    };
+    let year = words
+        .get(2)
+        .filter(|year| year.len() == 4)
+        .and_then(|year| year.parse::<i32>().ok());
+    match year {
+        Some(year) => {
+            NaiveDate::from_ymd_opt(year, month.number_from_month(), day).map(|date| (3, date))
+        }
+        None => {
+            let this_year = NaiveDate::from_ymd_opt(today.year(), month.number_from_month(), day);
+            let date = match this_year {
+                Some(date) if date >= today => date,
+                _ => (today.year() + 1..=today.year() + 4).find_map(|year| {
+                    NaiveDate::from_ymd_opt(year, month.number_from_month(), day)
+                })?,
+            };
+            Some((2, date))
+        }
+    }
+}
+
+/// A time at the start of `words`: "9am", "9:30 pm", "21:00" or "noon",
+/// optionally after "at". A bare hour such as "at 5" is left alone, since
+/// it is as often a count ("look at 3 options") as a time, and when it is a
+/// time there is no telling morning from evening.
+fn time_at(words: &[&str]) -> Option<(usize, NaiveTime)> {
+    if let ["at", ref rest @ ..] = *words {
+        let (count, time) = time_at(rest)?;
+        return Some((count + 1, time));
+    }
+    let first = *words.first()?;
+    if first == "noon" {
+        return Some((1, NaiveTime::from_hms_opt(12, 0, 0)?));
+    }
+    for (suffix, offset) in [("am", 0), ("pm", 12)] {
+        if let Some(clock) = first.strip_suffix(suffix).filter(|c| !c.is_empty()) {
+            return Some((1, twelve_hour(clock, offset)?));
+        }
+        if words.get(1) == Some(&suffix) {
+            return Some((2, twelve_hour(first, offset)?));
+        }
+    }
+    let (hour, minute) = first.split_once(':')?;
+    if minute.len() != 2 {
+        return None;
+    }
+    Some((
+        1,
+        NaiveTime::from_hms_opt(hour.parse().ok()?, minute.parse().ok()?, 0)?,
+    ))
+}
+
+fn twelve_hour(clock: &str, offset: u32) -> Option<NaiveTime> {
+    let (hour, minute): (u32, u32) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse().ok()?, minute.parse().ok()?),
+        Some(_) => return None,
+        None => (clock.parse().ok()?, 0),
+    };
+    if !(1..=12).contains(&hour) {
+        return None;
+    }
+    NaiveTime::from_hms_opt(hour % 12 + offset, minute, 0)
+}
+
+/// Weekday names; three-letter abbreviations only when `short` allows them,
+/// since words like "sat" and "sun" mean other things on their own.
+fn weekday(word: &str, short: bool) -> Option<Weekday> {
+    if word.len() < 6 && !short {
+        return None;
+    }
+    word.parse().ok()
+}
+
+fn month(word: &str) -> Option<Month> {
+    match word {
+        "sept" => Some(Month::September),
+        word => word.parse().ok(),
+    }
+}
+
+/// "21", "21st", "2nd" and so on.
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn project(id: i64, name: &str) -> Project {
+        Project {
+            id,
+            name: name.to_owned(),
+            color: None,
+            icon: None,
+            sort_order: id,
+            archived: false,
+            created_at: String::new(),
+        }
+    }
+
+    /// Parses `input` typed on Sunday 18 October 2026, in UTC.
+    fn quick(input: &str) -> QuickAdd {
+        let today = date("2026-10-18");
+        let projects = [project(1, "Finance"), project(2, "Side Project")];
+        parse(input, today, today, &projects, &Utc)
+    }
+
+    /// The recognized parts of `input` as text.
+    fn spans(input: &str) -> Vec<(SpanKind, String)> {
+        let units: Vec<u16> = input.encode_utf16().collect();
+        quick(input)
+            .spans
+            .iter()
+            .map(|s| (s.kind, String::from_utf16(&units[s.start..s.end]).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_everything_at_once() {
+        let input = "Pay rent tomorrow 9am #home !high every month +Finance";
+        let parsed = quick(input);
+        assert_eq!(parsed.text, "Pay rent");
+        assert_eq!(parsed.date.as_deref(), Some("2026-10-19"));
+        assert_eq!(parsed.due_at.as_deref(), Some("2026-10-19T09:00:00.000Z"));
+        assert_eq!(parsed.tags, ["home"]);
+        assert_eq!(parsed.priority, Some(Priority::High));
+        assert_eq!(parsed.recurrence.unwrap().to_string(), "FREQ=MONTHLY");
+        assert_eq!(parsed.project_id, Some(1));
+        assert_eq!(parsed.project.as_deref(), Some("Finance"));
+        assert_eq!(
+            spans(input),
+            [
+                (SpanKind::Date, "tomorrow".to_owned()),
+                (SpanKind::Time, "9am".to_owned()),
+                (SpanKind::Tag, "#home".to_owned()),
+                (SpanKind::Priority, "!high".to_owned()),
+                (SpanKind::Recurrence, "every month".to_owned()),
+                (SpanKind::Project, "+Finance".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_text_is_left_alone() {
+        let parsed = quick("Buy 3 apples, and milk!");
+        assert_eq!(parsed, QuickAdd::literal("Buy 3 apples, and milk!"));
+    }
+
+    #[test]
+    fn only_recognized_words_are_taken_literally() {
+        assert_eq!(quick("Monday").text, "Monday");
+        assert_eq!(quick("#home").tags, Vec::<String>::new());
+    }
+
+    #[test]
+    fn days() {
+        let day = |input: &str| quick(input).date;
+        assert_eq!(day("Call mom today").as_deref(), Some("2026-10-18"));
+        assert_eq!(day("Call mom tmrw").as_deref(), Some("2026-10-19"));
+        // Weekdays are the next one, a week ahead on the same weekday.
+        assert_eq!(day("Call mom friday").as_deref(), Some("2026-10-23"));
+        assert_eq!(day("Call mom Sunday").as_deref(), Some("2026-10-25"));
+        assert_eq!(day("Call mom on sat").as_deref(), Some("2026-10-24"));
+        assert_eq!(day("Call mom next week").as_deref(), Some("2026-10-19"));
+        assert_eq!(day("Call mom in 2 weeks").as_deref(), Some("2026-11-01"));
+        assert_eq!(day("Call mom in a month").as_deref(), Some("2026-11-18"));
+        assert_eq!(day("Call mom 2026-12-24").as_deref(), Some("2026-12-24"));
+        assert_eq!(day("Call mom on Dec 24th").as_deref(), Some("2026-12-24"));
+        assert_eq!(day("Call mom 3 March").as_deref(), Some("2027-03-03"));
+        assert_eq!(day("Call mom feb 29").as_deref(), Some("2028-02-29"));
+        assert_eq!(
+            day("Call mom october 18, 2027").as_deref(),
+            Some("2027-10-18")
+        );
+        // Abbreviations alone are words, and only the first day counts.
+        assert_eq!(quick("Sit in the sun").date, None);
+        assert_eq!(quick("Plan today and tomorrow").text, "Plan and tomorrow");
+    }
+
+    #[test]
+    fn times() {
+        let due = |input: &str| quick(input).due_at;
+        assert_eq!(
+            due("Run 6:30am").as_deref(),
+            Some("2026-10-18T06:30:00.000Z")
+        );
+        assert_eq!(
+            due("Run at 7 pm").as_deref(),
+            Some("2026-10-18T19:00:00.000Z")
+        );
+        assert_eq!(
+            due("Run at 7:00").as_deref(),
+            Some("2026-10-18T07:00:00.000Z")
+        );
+        assert_eq!(due("Run 12am").as_deref(), Some("2026-10-18T00:00:00.000Z"));
+        assert_eq!(
+            due("Run at noon").as_deref(),
+            Some("2026-10-18T12:00:00.000Z")
+        );
+        assert_eq!(
+            due("Run 21:15 friday").as_deref(),
+            Some("2026-10-23T21:15:00.000Z")
+        );
+        assert_eq!(due("Run 13pm"), None);
+        assert_eq!(quick("Run at the park").text, "Run at the park");
+        // A bare hour is not a time.
+        assert_eq!(
+            quick("Look at 3 options"),
+            QuickAdd::literal("Look at 3 options")
+        );
+        assert_eq!(quick("Meet at 5").due_at, None);
+        assert_eq!(quick("Meet at 5 friday").text, "Meet at 5");
+    }
+
+    #[test]
+    fn times_are_local() {
+        let zone = FixedOffset::east_opt(2 * 3600).unwrap();
+        let today = date("2026-10-18");
+        let parsed = parse("Stand-up 9am", today, date("2026-10-20"), &[], &zone);
+        // Without a day the time is on the day the todo is added to.
+        assert_eq!(parsed.date, None);
+        assert_eq!(parsed.due_at.as_deref(), Some("2026-10-20T07:00:00.000Z"));
+    }
+
+    #[test]
+    fn repeat_phrases_take_precedence_over_days() {
+        let parsed = quick("Gym every monday and thursday 7am");
+        assert_eq!(parsed.text, "Gym");
+        assert_eq!(
+            parsed.recurrence.unwrap().to_string(),
+            "FREQ=WEEKLY;BYDAY=MO,TH"
+        );
+        // The series starts on its first day.
+        assert_eq!(parsed.date.as_deref(), Some("2026-10-19"));
+        assert_eq!(parsed.due_at.as_deref(), Some("2026-10-19T07:00:00.000Z"));
+
+        let parsed = quick("Water plants 3 days after completion");
+        assert_eq!(parsed.text, "Water plants");
+        assert!(parsed.recurrence.unwrap().from_completion);
+        assert_eq!(parsed.date, None);
+    }
+
+    #[test]
+    fn markers() {
+        let parsed = quick("Call @phone about #Taxes, !urgent !low +side-project +Unknown");
+        assert_eq!(parsed.text, "Call about !low +Unknown");
+        assert_eq!(parsed.tags, ["@phone", "Taxes"]);
+        assert_eq!(parsed.priority, Some(Priority::Urgent));
+        assert_eq!(parsed.project.as_deref(), Some("Side Project"));
+        assert_eq!(quick("Fix bug # in parser").text, "Fix bug # in parser");
+    }
+
+    #[test]
+    fn spans_count_utf16_units() {
+        let input = "🎉 Party 🎂 tomorrow #fun";
+        assert_eq!(
+            quick(input).spans,
+            [
+                Span {
+                    start: 12,
+                    end: 20,
+                    kind: SpanKind::Date
+                },
+                Span {
+                    start: 21,
+                    end: 25,
+                    kind: SpanKind::Tag
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,1342 @@
+//! Repeating todos.
+//!
+//! A repeating todo carries a [`Rule`], written in a subset of the RFC 5545
+//! RRULE syntax or as a short English phrase such as "every weekday", and the
+//! day its series started. Each instance is an ordinary todo; the next one
+//! is materialized when the current one is completed, or when a new day
+//! starts and the current one was left behind. The new instance points back
+//! with `recurs_from`, so an instance is only ever repeated once and the
+//! newest instance is the one that carries the series forward. A missed
+//! instance is an unfinished todo like any other and is carried over
+//! according to the carry-over setting.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Offset, TimeZone, Utc, Weekday};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Serializer};
+use tracing::debug;
+
+use super::{get_todo, now, parse_date, projects, timestamp, today, Error, Result, Store, Todo};
+
+/// How far ahead [`Store::future_dates`] projects repeating todos.
+pub const PROJECTION_DAYS: u64 = 60;
+
+/// Series that never match again stop being expanded after this many years.
+const MAX_YEARS: i32 = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "DAILY",
+            Self::Weekly => "WEEKLY",
+            Self::Monthly => "MONTHLY",
+            Self::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A weekday in `BYDAY`, optionally the nth (or nth from last when
+/// negative) of its month, as in `-1FR` for the last Friday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub nth: Option<i8>,
+    pub weekday: Weekday,
+}
+
+/// When a repeating todo comes back.
+///
+/// Supported RRULE parts are `FREQ`, `INTERVAL`, `BYDAY`, `BYMONTHDAY`,
+/// `COUNT`, `UNTIL` and `WKST=MO`, plus `X-FROM=COMPLETION` for rules that
+/// count from the day an instance is completed instead of following a fixed
+/// schedule. Months without the requested day, such as the 31st in April,
+/// are skipped as RFC 5545 prescribes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    /// Day of the month, negative counting from the end (-1 is the last).
+    pub by_month_day: Option<i8>,
+    /// Occurrences in the whole series, counted from its first day.
+    pub count: Option<u32>,
+    /// Last day an occurrence may fall on.
+    pub until: Option<NaiveDate>,
+    /// The next instance comes `interval` periods after completion.
+    pub from_completion: bool,
+}
+
+impl Rule {
+    fn new(frequency: Frequency, interval: u32) -> Self {
+        Self {
+            frequency,
+            interval,
+            by_day: Vec::new(),
+            by_month_day: None,
+            count: None,
+            until: None,
+            from_completion: false,
+        }
+    }
+
+    /// Days of a series that starts on `start`, in order, beginning with
+    /// `start` itself when it matches the rule. Rules that repeat from
+    /// completion have no fixed days and yield nothing.
+    pub fn occurrences(&self, start: NaiveDate) -> Occurrences<'_> {
+        Occurrences {
+            rule: self,
+            start,
+            period: 0,
+            pending: Vec::new(),
+            emitted: 0,
+            done: self.from_completion,
+        }
+    }
+
+    /// First day of the series starting on `start` that comes after `day`.
+    pub fn next_after(&self, start: NaiveDate, day: NaiveDate) -> Option<NaiveDate> {
+        self.occurrences(start).find(|&d| d > day)
+    }
+
+    /// Day of the next instance when the current one is completed on `day`,
+    /// for rules that repeat from completion. A month later than the 31st
+    /// is the last day of a shorter month.
+    pub fn after_completion(&self, day: NaiveDate) -> Option<NaiveDate> {
+        let next = match self.frequency {
+            Frequency::Daily => day.checked_add_days(Days::new(self.interval.into())),
+            Frequency::Weekly => day.checked_add_days(Days::new(7 * u64::from(self.interval))),
+            Frequency::Monthly => day.checked_add_months(Months::new(self.interval)),
+            Frequency::Yearly => day.checked_add_months(Months::new(12 * self.interval)),
+        }?;
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+
+    /// Candidate days of the `period`th period after the one containing
+    /// `start`, in order, before filtering by `start`, `UNTIL` and `COUNT`.
+    /// `None` once the periods run more than [`MAX_YEARS`] past `start`.
+    fn period_days(&self, start: NaiveDate, period: u32) -> Option<Vec<NaiveDate>> {
+        let step = period.checked_mul(self.interval)?;
+        let within = |day: NaiveDate| (day.year() <= start.year() + MAX_YEARS).then_some(day);
+        let mut days = match self.frequency {
+            Frequency::Daily => {
+                let day = within(start.checked_add_days(Days::new(step.into()))?)?;
+                if self.by_day.is_empty() || self.by_day.iter().any(|b| b.weekday == day.weekday())
+                {
+                    vec![day]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let monday = start
+                    .checked_sub_days(Days::new(start.weekday().num_days_from_monday().into()))?
+                    .checked_add_days(Days::new(7 * u64::from(step)))
+                    .and_then(within)?;
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![start.weekday()]
+                } else {
+                    self.by_day.iter().map(|b| b.weekday).collect()
+                };
+                weekdays
+                    .into_iter()
+                    .map(|w| monday + Days::new(w.num_days_from_monday().into()))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let first = within(start.with_day(1)?.checked_add_months(Months::new(step))?)?;
+                self.month_days(first, start.day())
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(step).ok()?)?;
+                within(NaiveDate::from_ymd_opt(year, 1, 1)?)?;
+                NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+        days.sort_unstable();
+        days.dedup();
+        Some(days)
+    }
+
+    /// Matching days of the month starting on `first`.
+    fn month_days(&self, first: NaiveDate, start_day: u32) -> Vec<NaiveDate> {
+        let length = days_in_month(first);
+        let month_day = |n: i8| -> Option<NaiveDate> {
+            let day = if n > 0 {
+                u32::from(n.unsigned_abs())
+            } else {
+                (length + 1).checked_sub(u32::from(n.unsigned_abs()))?
+            };
+            (1..=length)
+                .contains(&day)
+                .then(|| first + Days::new((day - 1).into()))
+        };
+        if self.by_day.is_empty() {
+            let day = match self.by_month_day {
+                Some(n) => month_day(n),
+                None => first.with_day(start_day),
+            };
+            return day.into_iter().collect();
+        }
+
+        let mut days = Vec::new();
+        for by_day in &self.by_day {
+            let offset = (7 + by_day.weekday.num_days_from_monday()
+                - first.weekday().num_days_from_monday())
+                % 7;
+            let all: Vec<NaiveDate> = (offset..length)
+                .step_by(7)
+                .map(|d| first + Days::new(d.into()))
+                .collect();
+            match by_day.nth {
+                None => days.extend(all),
+                Some(n) if n > 0 => days.extend(all.get(usize::from(n.unsigned_abs()) - 1)),
+                Some(n) => days.extend(
+                    all.len()
+                        .checked_sub(usize::from(n.unsigned_abs()))
+                        .and_then(|i| all.get(i)),
+                ),
+            }
+        }
+        if let Some(n) = self.by_month_day {
+            let wanted = month_day(n);
+            days.retain(|&day| Some(day) == wanted);
+        }
+        days
+    }
+
+    fn validate(self, source: &str) -> Result<Self> {
+        let invalid = || Error::InvalidRecurrence(source.to_owned());
+        if self.interval == 0 || self.count == Some(0) {
+            return Err(invalid());
+        }
+        let ordinals = self.by_day.iter().any(|b| b.nth.is_some());
+        let valid = match self.frequency {
+            Frequency::Daily | Frequency::Weekly => !ordinals && self.by_month_day.is_none(),
+            Frequency::Monthly => true,
+            Frequency::Yearly => self.by_day.is_empty() && self.by_month_day.is_none(),
+        } && !(self.from_completion
+            && (!self.by_day.is_empty() || self.by_month_day.is_some() || self.count.is_some()))
+            && self
+                .by_day
+                .iter()
+                .all(|b| b.nth.is_none_or(|n| n != 0 && (-5..=5).contains(&n)))
+            && self
+                .by_month_day
+                .is_none_or(|n| n != 0 && (-31..=31).contains(&n));
+        if valid {
+            Ok(self)
+        } else {
+            Err(invalid())
+        }
+    }
+
+    fn parse_rrule(value: &str) -> Result<Self> {
+        let invalid = || Error::InvalidRecurrence(value.to_owned());
+        let body = value.trim();
+        let body = body
+            .get(..6)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("RRULE:"))
+            .map_or(body, |_| &body[6..]);
+        let mut frequency = None;
+        let mut rule = Rule::new(Frequency::Daily, 1);
+        for part in body.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, val) = part.split_once('=').ok_or_else(invalid)?;
+            let val = val.trim();
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(invalid()),
+                    })
+                }
+                "INTERVAL" => rule.interval = val.parse().map_err(|_| invalid())?,
+                "COUNT" => rule.count = Some(val.parse().map_err(|_| invalid())?),
+                "UNTIL" => {
+                    let date = val.get(..8).ok_or_else(invalid)?;
+                    rule.until =
+                        Some(NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| invalid())?);
+                }
+                "BYMONTHDAY" => rule.by_month_day = Some(val.parse().map_err(|_| invalid())?),
+                "BYDAY" => {
+                    rule.by_day = val
+                        .split(',')
+                        .map(|day| parse_by_day(day).ok_or_else(invalid))
+                        .collect::<Result<_>>()?
+                }
+                "WKST" if val.eq_ignore_ascii_case("MO") => {}
+                "X-FROM" if val.eq_ignore_ascii_case("COMPLETION") => rule.from_completion = true,
+                _ => return Err(invalid()),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(invalid)?;
+        rule.validate(value)
+    }
+
+    /// Parses phrases such as "every weekday", "every 2 weeks on monday",
+    /// "monthly on the last friday", "every month on the 15th" and
+    /// "3 days after completion".
+    fn parse_phrase(value: &str) -> Result<Self> {
+        let invalid = || Error::InvalidRecurrence(value.to_owned());
+        let lower = value.trim().to_lowercase().replace(',', " ");
+        let words: Vec<&str> = lower
+            .split_whitespace()
+            .filter(|w| !matches!(*w, "and" | "the"))
+            .collect();
+
+        // "N units after completion"
+        if let [count, unit, "after", "completion" | "completed" | "done"] = words[..] {
+            let interval = count.parse().map_err(|_| invalid())?;
+            let mut rule = Rule::new(parse_unit(unit).ok_or_else(invalid)?, interval);
+            rule.from_completion = true;
+            return rule.validate(value);
+        }
+
+        let (mut rule, rest) = match words[..] {
+            ["daily", ref rest @ ..] => (Rule::new(Frequency::Daily, 1), rest),
+            ["weekly", ref rest @ ..] => (Rule::new(Frequency::Weekly, 1), rest),
+            ["monthly", ref rest @ ..] => (Rule::new(Frequency::Monthly, 1), rest),
+            ["yearly" | "annually", ref rest @ ..] => (Rule::new(Frequency::Yearly, 1), rest),
+            ["every", "other", unit, ref rest @ ..] => {
+                (Rule::new(parse_unit(unit).ok_or_else(invalid)?, 2), rest)
+            }
+            ["every", "weekday" | "weekdays", ref rest @ ..] => {
+                let mut rule = Rule::new(Frequency::Weekly, 1);
+                rule.by_day = WEEKDAYS
+                    .iter()
+                    .map(|&weekday| ByDay { nth: None, weekday })
+                    .collect();
+                (rule, rest)
+            }
+            ["every", count, unit, ref rest @ ..] if count.parse::<u32>().is_ok() => {
+                let frequency = parse_unit(unit).ok_or_else(invalid)?;
+                (
+                    Rule::new(frequency, count.parse().map_err(|_| invalid())?),
+                    rest,
+                )
+            }
+            ["every", unit, ref rest @ ..] if parse_unit(unit).is_some() => {
+                (Rule::new(parse_unit(unit).ok_or_else(invalid)?, 1), rest)
+            }
+            // "every monday and thursday"
+            ["every", ref days @ ..] => {
+                let mut rule = Rule::new(Frequency::Weekly, 1);
+                rule.by_day = days
+                    .iter()
+                    .map(|day| parse_weekday(day).map(|weekday| ByDay { nth: None, weekday }))
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?;
+                (rule, &[][..])
+            }
+            _ => return Err(invalid()),
+        };
+
+        match rest {
+            [] => {}
+            ["on", days @ ..] if rule.frequency == Frequency::Weekly && !days.is_empty() => {
+                rule.by_day = days
+                    .iter()
+                    .map(|day| parse_weekday(day).map(|weekday| ByDay { nth: None, weekday }))
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?;
+            }
+            ["on", "last", "day"] if rule.frequency == Frequency::Monthly => {
+                rule.by_month_day = Some(-1);
+            }
+            ["on", nth, day] if rule.frequency == Frequency::Monthly => {
+                let nth = parse_ordinal(nth).ok_or_else(invalid)?;
+                rule.by_day = vec![ByDay {
+                    nth: Some(nth),
+                    weekday: parse_weekday(day).ok_or_else(invalid)?,
+                }];
+            }
+            ["on", day] if rule.frequency == Frequency::Monthly => {
+                rule.by_month_day = Some(parse_ordinal(day).ok_or_else(invalid)?);
+            }
+            _ => return Err(invalid()),
+        }
+        rule.validate(value)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    /// Accepts RRULE syntax, recognized by its `FREQ=` part, or a phrase.
+    fn from_str(value: &str) -> Result<Self> {
+        if value.to_ascii_uppercase().contains("FREQ=") {
+            Self::parse_rrule(value)
+        } else {
+            Self::parse_phrase(value)
+        }
+    }
+}
+
+/// Canonical RRULE text, which is how rules are stored.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            f.write_str(";BYDAY=")?;
+            for (i, by_day) in self.by_day.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                if let Some(nth) = by_day.nth {
+                    write!(f, "{nth}")?;
+                }
+                f.write_str(weekday_code(by_day.weekday))?;
+            }
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={day}")?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if self.from_completion {
+            f.write_str(";X-FROM=COMPLETION")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl ToSql for Rule {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromSql for Rule {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|e: Error| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+/// Iterator returned by [`Rule::occurrences`].
+pub struct Occurrences<'a> {
+    rule: &'a Rule,
+    start: NaiveDate,
+    period: u32,
+    /// Days of the current period still to be yielded, last first.
+    pending: Vec<NaiveDate>,
+    emitted: u32,
+    done: bool,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = NaiveDate;
+
+    fn next(&mut self) -> Option<NaiveDate> {
+        while !self.done {
+            if let Some(day) = self.pending.pop() {
+                if self.rule.until.is_some_and(|until| day > until) {
+                    self.done = true;
+                    break;
+                }
+                self.emitted += 1;
+                if self.rule.count.is_some_and(|count| self.emitted >= count) {
+                    self.done = true;
+                }
+                return Some(day);
+            }
+            match self.rule.period_days(self.start, self.period) {
+                Some(mut days) => {
+                    days.retain(|&day| day >= self.start);
+                    days.reverse();
+                    self.pending = days;
+                    self.period += 1;
+                }
+                None => self.done = true,
+            }
+        }
+        None
+    }
+}
+
+impl Store {
+    /// Makes a todo repeat by `rule`, starting from its current day, or stops
+    /// it repeating with `None`. Only top-level todos can repeat; their
+    /// subtasks come back with them.
+    #[tracing::instrument(skip(self))]
+    pub fn set_recurrence(&self, id: i64, rule: Option<&str>) -> Result<Todo> {
+        let rule = rule.map(str::parse::<Rule>).transpose()?;
+        let conn = self.conn();
+        let todo = get_todo(&conn, id)?;
+        if rule.is_some() && todo.parent_id.is_some() {
+            return Err(Error::RecurringSubtask);
+        }
+        conn.execute(
+            "UPDATE todos SET recurrence = ?2, recurrence_start = ?3, updated_at = ?4
+             WHERE id = ?1",
+            params![id, rule, rule.as_ref().map(|_| &todo.scheduled_date), now()],
+        )?;
+        get_todo(&conn, id)
+    }
+}
+
+/// Weekdays in "every weekday".
+const WEEKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// `MO`, `2TU` or `-1FR`.
+fn parse_by_day(value: &str) -> Option<ByDay> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    let code = value.get(split..)?.to_ascii_uppercase();
+    let weekday = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .find(|&w| weekday_code(w) == code)?;
+    let nth = match &value[..split] {
+        "" => None,
+        n => Some(n.trim_start_matches('+').parse().ok()?),
+    };
+    Some(ByDay { nth, weekday })
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    let day = value.trim_end_matches('s');
+    let weekday: Weekday = day.parse().ok()?;
+    Some(weekday)
+}
+
+fn parse_unit(value: &str) -> Option<Frequency> {
+    match value {
+        "day" | "days" => Some(Frequency::Daily),
+        "week" | "weeks" => Some(Frequency::Weekly),
+        "month" | "months" => Some(Frequency::Monthly),
+        "year" | "years" => Some(Frequency::Yearly),
+        _ => None,
+    }
+}
+
+/// "first" to "fifth", "last", "second last", or a number with or without
+/// an English suffix ("15th").
+fn parse_ordinal(value: &str) -> Option<i8> {
+    let n = match value {
+        "first" => 1,
+        "second" => 2,
+        "third" => 3,
+        "fourth" => 4,
+        "fifth" => 5,
+        "last" => -1,
+        _ => value
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .parse()
+            .ok()?,
+    };
+    Some(n)
+}
+
+fn days_in_month(first: NaiveDate) -> u32 {
+    let next = first + Months::new(1);
+    next.signed_duration_since(first).num_days() as u32
+}
+
+/// Moves a deadline or reminder time along with its todo from day `from` to day `to`,
+/// keeping its wall-clock time in `zone` across daylight saving changes. A
+/// time that does not exist on the new day, skipped by a spring-forward
+/// change, moves forward by the size of the gap; an ambiguous one takes the
+/// earlier instant.
+pub fn shift_due<Tz: TimeZone>(
+    due_at: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    zone: &Tz,
+) -> Option<String> {
+    let due = DateTime::parse_from_rfc3339(due_at)
+        .ok()?
+        .with_timezone(zone);
+    let local = due.naive_local() + (to - from);
+    let shifted = zone.from_local_datetime(&local).earliest().or_else(|| {
+        // Inside a gap: the same instant offset as before the change.
+        let before = zone
+            .offset_from_local_datetime(&(local - chrono::TimeDelta::days(1)))
+            .earliest()?;
+        Some(zone.from_utc_datetime(&(local - before.fix())))
+    })?;
+    Some(timestamp(shifted.with_timezone(&Utc)))
+}
+
+/// Materializes the instance after todo `id` if it repeats and has not
+/// repeated yet, returning the new todo's id. A completed instance repeats
+/// on its next day; an unfinished one only when `today` has moved past it,
+/// and never for rules that count from completion.
+pub(super) fn advance(conn: &Connection, id: i64, today: &str) -> Result<Option<i64>> {
+    let row = conn
+        .query_row(
+            "SELECT recurrence, recurrence_start, scheduled_date, due_at, remind_at, completed
+             FROM todos AS t
+             WHERE id = ?1 AND recurrence IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM todos WHERE recurs_from = t.id)",
+            [id],
+            |row| {
+                Ok((
+                    row.get::<_, Rule>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, bool>(5)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((rule, start, day, due_at, remind_at, completed)) = row else {
+        return Ok(None);
+    };
+    let today = parse_date(today)?;
+    let day = parse_date(&day)?;
+    let next = if rule.from_completion {
+        if completed {
+            rule.after_completion(today)
+        } else {
+            None
+        }
+    } else if completed || day < today {
+        let start = start.as_deref().map(parse_date).transpose()?.unwrap_or(day);
+        rule.occurrences(start).find(|&d| d > day && d >= today)
+    } else {
+        None
+    };
+    let Some(next) = next else {
+        return Ok(None);
+    };
+
+    let due_at = due_at.and_then(|due| shift_due(&due, day, next, &chrono::Local));
+    let remind_at = remind_at.and_then(|at| shift_due(&at, day, next, &chrono::Local));
+    let next_day = next.to_string();
+    let sort_order: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(sort_order), 0) + 1 FROM todos
+         WHERE completed = 0 AND scheduled_date = ?1 AND parent_id IS NULL",
+        [&next_day],
+        |row| row.get(0),
+    )?;
+    let project_id: Option<i64> =
+        conn.query_row("SELECT project_id FROM todos WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })?;
+    let project_order = match project_id {
+        Some(project_id) => projects::next_order(conn, project_id)?,
+        None => 0,
+    };
+    let now = now();
+    conn.execute(
+        "INSERT INTO todos
+             (text, notes, completed, scheduled_date, due_at, remind_at, project_id,
+              project_order, priority, recurrence, recurrence_start, recurs_from,
+              created_at, updated_at, sort_order)
+         SELECT text, notes, 0, ?2, ?3, ?4, project_id, ?5, priority, recurrence,
+                recurrence_start, id, ?6, ?6, ?7
+         FROM todos WHERE id = ?1",
+        params![
+            id,
+            next_day,
+            due_at,
+            remind_at,
+            project_order,
+            now,
+            sort_order
+        ],
+    )?;
+    let instance = conn.last_insert_rowid();
+    copy_extras(conn, id, instance)?;
+    copy_subtasks(conn, id, instance, &next_day, &now)?;
+    debug!(id, instance, day = %next_day, "repeated todo");
+    Ok(Some(instance))
+}
+
+/// Repeats every unfinished instance left on a day before `today`. Returns
+/// how many new instances were created.
+pub(super) fn advance_stale(conn: &Connection, today: &str) -> Result<usize> {
+    let ids: Vec<i64> = {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM todos AS t
+             WHERE recurrence IS NOT NULL AND completed = 0 AND scheduled_date < ?1
+               AND NOT EXISTS (SELECT 1 FROM todos WHERE recurs_from = t.id)
+             ORDER BY scheduled_date, sort_order",
+        )?;
+        let ids = stmt.query_map([today], |row| row.get(0))?;
+        ids.collect::<rusqlite::Result<_>>()?
+    };
+    let mut created = 0;
+    for id in ids {
+        created += usize::from(advance(conn, id, today)?.is_some());
+    }
+    Ok(created)
+}
+
+/// Days after today, up to [`PROJECTION_DAYS`] ahead, on which the newest
+/// instance of a series will have repeated. Rules that count from completion
+/// cannot be projected.
+pub(super) fn projected_dates(conn: &Connection) -> Result<Vec<String>> {
+    let today = parse_date(&today(conn)?)?;
+    let horizon = today + Days::new(PROJECTION_DAYS);
+    let mut stmt = conn.prepare_cached(
+        "SELECT recurrence, recurrence_start, scheduled_date FROM todos AS t
+         WHERE recurrence IS NOT NULL
+           AND NOT EXISTS (SELECT 1 FROM todos WHERE recurs_from = t.id)",
+    )?;
+    let series = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Rule>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut dates = Vec::new();
+    for series in series {
+        let (rule, start, day) = series?;
+        let day = parse_date(&day)?;
+        let start = start.as_deref().map(parse_date).transpose()?.unwrap_or(day);
+        dates.extend(
+            rule.occurrences(start)
+                .skip_while(|&d| d <= day || d <= today)
+                .take_while(|&d| d <= horizon)
+                .map(|d| d.to_string()),
+        );
+    }
+    Ok(dates)
+}
+
+/// Gives instance `to` the tags and attachments of `from`.
+fn copy_extras(conn: &Connection, from: i64, to: i64) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO todo_tags (todo_id, tag_id)
+         SELECT ?2, tag_id FROM todo_tags WHERE todo_id = ?1",
+    )?
+    .execute(params![from, to])?;
+    conn.prepare_cached(
+        "INSERT INTO attachments (todo_id, name, size, blob, created_at)
+         SELECT ?2, name, size, blob, created_at FROM attachments WHERE todo_id = ?1",
+    )?
+    .execute(params![from, to])?;
+    Ok(())
+}
+
+/// Copies every subtask of `from` below `to`, unfinished again.
+fn copy_subtasks(conn: &Connection, from: i64, to: i64, day: &str, now: &str) -> Result<()> {
+    let children: Vec<i64> = {
+        let mut stmt = conn.prepare_cached(
+            "SELECT id FROM todos WHERE parent_id = ?1 ORDER BY sort_order, created_at",
+        )?;
+        let ids = stmt.query_map([from], |row| row.get(0))?;
+        ids.collect::<rusqlite::Result<_>>()?
+    };
+    for (position, child) in (0_i64..).zip(children) {
+        conn.prepare_cached(
+            "INSERT INTO todos
+                 (text, notes, completed, scheduled_date, priority, parent_id, created_at,
+                  updated_at, sort_order)
+             SELECT text, notes, 0, ?2, priority, ?3, ?4, ?4, ?5 FROM todos WHERE id = ?1",
+        )?
+        .execute(params![child, day, to, now, position])?;
+        let copy = conn.last_insert_rowid();
+        copy_extras(conn, child, copy)?;
+        copy_subtasks(conn, child, copy, day, now)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::{America::New_York, Europe::Berlin};
+
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::{NewTodo, TodoUpdate};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn rule(s: &str) -> Rule {
+        s.parse().unwrap()
+    }
+
+    /// The first `n` occurrences of `rule` from `start`, as strings.
+    fn expand(rule_text: &str, start: &str, n: usize) -> Vec<String> {
+        rule(rule_text)
+            .occurrences(date(start))
+            .take(n)
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn phrases_parse_to_canonical_rrules() {
+        let cases = [
+            ("every day", "FREQ=DAILY"),
+            ("daily", "FREQ=DAILY"),
+            ("every 3 days", "FREQ=DAILY;INTERVAL=3"),
+            ("every weekday", "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR"),
+            ("every 2 weeks on Monday", "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO"),
+            ("every other week", "FREQ=WEEKLY;INTERVAL=2"),
+            ("every monday and thursday", "FREQ=WEEKLY;BYDAY=MO,TH"),
+            ("weekly on tue, fri", "FREQ=WEEKLY;BYDAY=TU,FR"),
+            ("monthly on the last Friday", "FREQ=MONTHLY;BYDAY=-1FR"),
+            ("every month on the 2nd tuesday", "FREQ=MONTHLY;BYDAY=2TU"),
+            ("monthly on the 15th", "FREQ=MONTHLY;BYMONTHDAY=15"),
+            ("every month on the last day", "FREQ=MONTHLY;BYMONTHDAY=-1"),
+            ("every month", "FREQ=MONTHLY"),
+            ("yearly", "FREQ=YEARLY"),
+            (
+                "3 days after completion",
+                "FREQ=DAILY;INTERVAL=3;X-FROM=COMPLETION",
+            ),
+            ("1 month after completion", "FREQ=MONTHLY;X-FROM=COMPLETION"),
+        ];
+        for (phrase, rrule) in cases {
+            assert_eq!(rule(phrase).to_string(), rrule, "{phrase}");
+        }
+    }
+
+    #[test]
+    fn rrules_round_trip() {
+        for text in [
+            "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO",
+            "FREQ=MONTHLY;BYDAY=-1FR",
+            "FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13",
+            "FREQ=MONTHLY;BYMONTHDAY=-2;COUNT=4",
+            "FREQ=YEARLY;UNTIL=20301231",
+            "FREQ=WEEKLY;INTERVAL=3;X-FROM=COMPLETION",
+        ] {
+            assert_eq!(rule(text).to_string(), text);
+        }
+        // Prefix, case, WKST and a date-time UNTIL are accepted.
+        assert_eq!(
+            rule("RRULE:freq=monthly;wkst=MO;byday=+2tu;until=20261231T235959Z").to_string(),
+            "FREQ=MONTHLY;BYDAY=2TU;UNTIL=20261231"
+        )
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for text in [
+            "",
+            "sometimes",
+            "every 0 days",
+            "every fortnight",
+            "every 2 weeks on someday",
+            "FREQ=HOURLY",
+            "INTERVAL=2",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=WEEKLY;BYDAY=-1FR",
+            "FREQ=MONTHLY;BYDAY=6MO",
+            "FREQ=MONTHLY;BYDAY=0MO",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=YEARLY;BYDAY=MO",
+            "FREQ=WEEKLY;WKST=SU",
+            "FREQ=DAILY;BYSETPOS=1",
+            "FREQ=WEEKLY;BYDAY=MO;X-FROM=COMPLETION",
+            "FREQ=MONTHLY;UNTIL=2026",
+        ] {
+            assert!(text.parse::<Rule>().is_err(), "{text:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn every_weekday_skips_weekends_across_month_ends() {
+        // Friday 30 January 2026 to Tuesday 3 February.
+        assert_eq!(
+            expand("every weekday", "2026-01-30", 3),
+            ["2026-01-30", "2026-02-02", "2026-02-03"]
+        );
+        // Starting on a Saturday waits for Monday.
+        assert_eq!(
+            expand("every weekday", "2026-02-28", 2),
+            ["2026-03-02", "2026-03-03"]
+        );
+        // The daily form with a weekday filter gives the same days.
+        assert_eq!(
+            expand("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", "2026-01-30", 3),
+            ["2026-01-30", "2026-02-02", "2026-02-03"]
+        );
+    }
+
+    #[test]
+    fn every_two_weeks_counts_from_the_start_week() {
+        // Wednesday 14 October: that week's Monday has passed, so the first
+        // occurrence is two weeks later, and the series crosses the year end.
+        assert_eq!(
+            expand("every 2 weeks on monday", "2026-10-14", 6),
+            [
+                "2026-10-26",
+                "2026-11-09",
+                "2026-11-23",
+                "2026-12-07",
+                "2026-12-21",
+                "2027-01-04"
+            ]
+        );
+        // Several days a week come out in date order.
+        assert_eq!(
+            expand("FREQ=WEEKLY;INTERVAL=2;BYDAY=FR,MO", "2026-12-28", 4),
+            ["2026-12-28", "2027-01-01", "2027-01-11", "2027-01-15"]
+        );
+        // Without BYDAY a weekly rule keeps the start's weekday.
+        assert_eq!(
+            expand("weekly", "2026-02-26", 2),
+            ["2026-02-26", "2026-03-05"]
+        );
+    }
+
+    #[test]
+    fn last_friday_of_each_month() {
+        assert_eq!(
+            expand("monthly on the last friday", "2026-01-01", 6),
+            [
+                "2026-01-30",
+                "2026-02-27",
+                "2026-03-27",
+                "2026-04-24",
+                "2026-05-29",
+                "2026-06-26"
+            ]
+        );
+        // Starting after this month's last Friday moves to the next month.
+        assert_eq!(
+            expand("monthly on the last friday", "2026-01-31", 1),
+            ["2026-02-27"]
+        );
+    }
+
+    #[test]
+    fn nth_weekday_skips_months_without_one() {
+        // Months with five Mondays in 2026: March, June, August, November.
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYDAY=5MO", "2026-01-01", 4),
+            ["2026-03-30", "2026-06-29", "2026-08-31", "2026-11-30"]
+        );
+        assert_eq!(
+            expand("every month on the second tuesday", "2026-12-01", 2),
+            ["2026-12-08", "2027-01-12"]
+        );
+        // Friday the 13th.
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13", "2026-01-01", 3),
+            ["2026-02-13", "2026-03-13", "2026-11-13"]
+        );
+    }
+
+    #[test]
+    fn monthly_on_the_31st_skips_short_months() {
+        assert_eq!(
+            expand("every month", "2026-01-31", 7),
+            [
+                "2026-01-31",
+                "2026-03-31",
+                "2026-05-31",
+                "2026-07-31",
+                "2026-08-31",
+                "2026-10-31",
+                "2026-12-31"
+            ]
+        );
+        assert_eq!(
+            expand("monthly on the 30th", "2026-01-15", 3),
+            ["2026-01-30", "2026-03-30", "2026-04-30"]
+        );
+    }
+
+    #[test]
+    fn last_day_of_month_follows_month_lengths_and_leap_years() {
+        assert_eq!(
+            expand("every month on the last day", "2027-12-15", 5),
+            [
+                "2027-12-31",
+                "2028-01-31",
+                "2028-02-29",
+                "2028-03-31",
+                "2028-04-30"
+            ]
+        );
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYMONTHDAY=-2", "2026-02-01", 2),
+            ["2026-02-27", "2026-03-30"]
+        );
+        // Every third month from November crosses into February.
+        assert_eq!(
+            expand("FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=-1", "2026-11-30", 3),
+            ["2026-11-30", "2027-02-28", "2027-05-31"]
+        );
+    }
+
+    #[test]
+    fn yearly_on_february_29th_only_in_leap_years() {
+        assert_eq!(
+            expand("yearly", "2028-02-29", 3),
+            ["2028-02-29", "2032-02-29", "2036-02-29"]
+        );
+        assert_eq!(
+            expand("yearly", "2026-12-31", 2),
+            ["2026-12-31", "2027-12-31"]
+        );
+    }
+
+    #[test]
+    fn count_and_until_end_the_series() {
+        assert_eq!(
+            expand("FREQ=DAILY;COUNT=3", "2026-10-30", 10),
+            ["2026-10-30", "2026-10-31", "2026-11-01"]
+        );
+        assert_eq!(
+            expand("FREQ=WEEKLY;UNTIL=20261115", "2026-10-31", 10),
+            ["2026-10-31", "2026-11-07", "2026-11-14"]
+        );
+        // COUNT counts from the series start, not from a later day.
+        let weekly = rule("FREQ=WEEKLY;COUNT=2");
+        assert_eq!(
+            weekly.next_after(date("2026-01-01"), date("2026-01-01")),
+            Some(date("2026-01-08"))
+        );
+        assert_eq!(
+            weekly.next_after(date("2026-01-01"), date("2026-01-08")),
+            None
+        );
+    }
+
+    #[test]
+    fn rules_that_never_match_end() {
+        // April has no 31st, and the series only ever lands in April.
+        assert_eq!(
+            expand("FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=31", "2026-04-01", 1).len(),
+            0
+        );
+        assert_eq!(expand("3 days after completion", "2026-04-01", 1).len(), 0);
+    }
+
+    #[test]
+    fn after_completion_counts_from_the_completion_day() {
+        assert_eq!(
+            rule("3 days after completion").after_completion(date("2026-12-30")),
+            Some(date("2027-01-02"))
+        );
+        assert_eq!(
+            rule("2 weeks after completion").after_completion(date("2026-02-20")),
+            Some(date("2026-03-06"))
+        );
+        // Months are clamped to the end of shorter months.
+        let monthly = rule("1 month after completion");
+        assert_eq!(
+            monthly.after_completion(date("2026-01-31")),
+            Some(date("2026-02-28"))
+        );
+        assert_eq!(
+            monthly.after_completion(date("2028-01-31")),
+            Some(date("2028-02-29"))
+        );
+        assert_eq!(
+            rule("1 year after completion").after_completion(date("2028-02-29")),
+            Some(date("2029-02-28"))
+        );
+        assert_eq!(
+            rule("FREQ=DAILY;INTERVAL=5;UNTIL=20260105;X-FROM=COMPLETION")
+                .after_completion(date("2026-01-01")),
+            None
+        );
+    }
+
+    #[test]
+    fn days_are_unaffected_by_dst_changes() {
+        // US clocks change on 8 March and 1 November 2026, European ones on
+        // 29 March and 25 October; occurrences are calendar days regardless.
+        assert_eq!(
+            expand("every day", "2026-03-07", 3),
+            ["2026-03-07", "2026-03-08", "2026-03-09"]
+        );
+        assert_eq!(
+            expand("every day", "2026-10-31", 3),
+            ["2026-10-31", "2026-11-01", "2026-11-02"]
+        );
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYDAY=-1SU", "2026-03-01", 2),
+            ["2026-03-29", "2026-04-26"]
+        );
+    }
+
+    #[test]
+    fn deadlines_keep_their_local_time_across_dst() {
+        let shift = |due: &str, from: &str, to: &str, zone| {
+            shift_due(due, date(from), date(to), &zone).unwrap()
+        };
+        // 09:00 EST is 14:00 UTC; 09:00 EDT after spring forward is 13:00.
+        assert_eq!(
+            shift(
+                "2026-03-07T14:00:00.000Z",
+                "2026-03-07",
+                "2026-03-09",
+                New_York
+            ),
+            "2026-03-09T13:00:00.000Z"
+        );
+        // And back again after falling back.
+        assert_eq!(
+            shift(
+                "2026-10-31T13:00:00.000Z",
+                "2026-10-31",
+                "2026-11-02",
+                New_York
+            ),
+            "2026-11-02T14:00:00.000Z"
+        );
+        // Berlin: 08:00 CET is 07:00 UTC, 08:00 CEST is 06:00.
+        assert_eq!(
+            shift(
+                "2026-03-28T07:00:00.000Z",
+                "2026-03-28",
+                "2026-03-29",
+                Berlin
+            ),
+            "2026-03-29T06:00:00.000Z"
+        );
+        // A deadline on a later day than the todo keeps that distance.
+        assert_eq!(
+            shift(
+                "2026-01-31T17:00:00.000Z",
+                "2026-01-30",
+                "2026-02-27",
+                Berlin
+            ),
+            "2026-02-28T17:00:00.000Z"
+        );
+    }
+
+    #[test]
+    fn deadlines_in_skipped_or_repeated_hours() {
+        // 02:30 does not exist on 8 March in New York; it becomes 03:30 EDT.
+        assert_eq!(
+            shift_due(
+                "2026-03-07T07:30:00.000Z",
+                date("2026-03-07"),
+                date("2026-03-08"),
+                &New_York
+            )
+            .unwrap(),
+            "2026-03-08T07:30:00.000Z"
+        );
+        // 01:30 happens twice on 1 November; the first (EDT) one is used.
+        assert_eq!(
+            shift_due(
+                "2026-10-31T05:30:00.000Z",
+                date("2026-10-31"),
+                date("2026-11-01"),
+                &New_York
+            )
+            .unwrap(),
+            "2026-11-01T05:30:00.000Z"
+        );
+        assert_eq!(
+            shift_due(
+                "not a time",
+                date("2026-10-31"),
+                date("2026-11-01"),
+                &New_York
+            ),
+            None
+        );
+    }
+
+    /// A todo on `date` repeating by `rule`.
+    fn repeating(text: &str, date: &str, rule: &str) -> NewTodo {
+        NewTodo {
+            tags: vec!["home".to_owned()],
+            recurrence: Some(rule.to_owned()),
+            ..todo(text, date)
+        }
+    }
+
+    fn next_of(store: &Store, id: i64) -> Option<Todo> {
+        let conn = store.conn();
+        let next: Option<i64> = conn
+            .query_row("SELECT id FROM todos WHERE recurs_from = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .unwrap();
+        next.map(|id| get_todo(&conn, id).unwrap())
+    }
+
+    #[test]
+    fn completing_an_instance_materializes_the_next_once() {
+        let store = store();
+        let today = date(&store.today().unwrap());
+        let tomorrow = (today + Days::new(1)).to_string();
+        let todo = add(&store, repeating("Stand-up", &tomorrow, "every day"));
+        let child = store
+            .create_todo(&NewTodo {
+                text: "Notes".to_owned(),
+                parent_id: Some(todo.id),
+                ..NewTodo::default()
+            })
+            .unwrap();
+        store.set_completed(child.id, true).unwrap();
+
+        let done = store.toggle_todo(todo.id).unwrap();
+        assert!(done.completed);
+        let next = next_of(&store, todo.id).expect("next instance");
+        assert_eq!(next.scheduled_date, (today + Days::new(2)).to_string());
+        assert!(!next.completed);
+        assert_eq!(next.recurrence, todo.recurrence);
+        assert_eq!(next.tags, todo.tags);
+        // Subtasks come back unfinished.
+        let tree = store.todo_tree(&next.scheduled_date).unwrap();
+        assert_eq!(tree[0].children.len(), 1);
+        assert!(!tree[0].children[0].todo.completed);
+
+        // Toggling the old instance again does not repeat it twice.
+        store.toggle_todo(todo.id).unwrap();
+        store
+            .update_todo(
+                todo.id,
+                TodoUpdate {
+                    completed: Some(true),
+                    ..TodoUpdate::default()
+                },
+            )
+            .unwrap();
+        let count: i64 = store
+            .conn()
+            .query_row(
+                "SELECT COUNT(*) FROM todos WHERE recurs_from = ?1",
+                [todo.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn after_completion_rules_count_from_today() {
+        let store = store();
+        let today = date(&store.today().unwrap());
+        let todo = add(
+            &store,
+            repeating("Water plants", "2020-01-01", "3 days after completion"),
+        );
+        store.set_completed(todo.id, true).unwrap();
+        let next = next_of(&store, todo.id).unwrap();
+        assert_eq!(next.scheduled_date, (today + Days::new(3)).to_string());
+    }
+
+    #[test]
+    fn new_day_repeats_instances_left_behind() {
+        let store = store();
+        let today = store.today().unwrap();
+        let daily = add(&store, repeating("Stretch", "2020-01-01", "every day"));
+        let after = add(
+            &store,
+            repeating("Descale", "2020-01-01", "1 week after completion"),
+        );
+        let conn = store.conn();
+        assert_eq!(advance_stale(&conn, &today).unwrap(), 1);
+        assert_eq!(advance_stale(&conn, &today).unwrap(), 0);
+        drop(conn);
+        // The daily series catches up to today; the missed instance stays.
+        assert_eq!(next_of(&store, daily.id).unwrap().scheduled_date, today);
+        assert!(!store.get_todo(daily.id).unwrap().completed);
+        // Completion-based rules wait for completion.
+        assert!(next_of(&store, after.id).is_none());
+    }
+
+    #[test]
+    fn future_dates_include_projections() {
+        let store = store();
+        let today = date(&store.today().unwrap());
+        let start = today + Days::new(1);
+        let todo = add(
+            &store,
+            repeating("Review", &start.to_string(), "every 2 weeks"),
+        );
+        let expected: Vec<String> = (0..)
+            .map(|i| start + Days::new(14 * i))
+            .take_while(|&d| d <= today + Days::new(PROJECTION_DAYS))
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(store.future_dates().unwrap(), expected);
+
+        store.set_recurrence(todo.id, None).unwrap();
+        assert_eq!(store.future_dates().unwrap(), [start.to_string()]);
+    }
+
+    #[test]
+    fn subtasks_cannot_repeat() {
+        let store = store();
+        let parent = store.add_todo("Trip", None).unwrap();
+        let child = store
+            .create_todo(&NewTodo {
+                text: "Pack".to_owned(),
+                parent_id: Some(parent.id),
+                ..NewTodo::default()
+            })
+            .unwrap();
+        assert!(matches!(
+            store.set_recurrence(child.id, Some("every day")),
+            Err(Error::RecurringSubtask)
+        ));
+        assert!(matches!(
+            store.set_recurrence(parent.id, Some("now and then")),
+            Err(Error::InvalidRecurrence(_))
+        ));
+    }
+}
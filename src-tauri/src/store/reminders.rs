@@ -0,0 +1,79 @@
+//! Reminder times on todos.
+//!
+//! `remind_at` holds the moment to notify as a UTC RFC 3339 timestamp, like
+//! `due_at`, and `reminded_at` when the notification went out. A reminder is
+//! pending while its todo is unfinished and `reminded_at` is null, so one
+//! that fell due while the app was closed or the machine asleep is still
+//! pending the next time the scheduler looks, and it fires then.
+
+use chrono::{Duration, Utc};
+use rusqlite::params;
+
+use super::{get_todo, now, parse_due, query_todos, timestamp, Error, Result, Store, Todo};
+
+impl Store {
+    /// Sets or clears the reminder time; see [`parse_due`](super::parse_due)
+    /// for accepted formats. A new time is pending even if an earlier
+    /// reminder already fired.
+    #[tracing::instrument(skip(self))]
+    pub fn set_reminder(&self, id: i64, remind_at: Option<&str>) -> Result<Todo> {
+        let remind_at = remind_at.map(parse_due).transpose()?;
+        let conn = self.conn();
+        let changed = conn.execute(
+            "UPDATE todos SET remind_at = ?2, reminded_at = NULL, updated_at = ?3
+             WHERE id = ?1",
+            params![id, remind_at, now()],
+        )?;
+        if changed == 0 {
+            return Err(Error::NotFound(id));
+        }
+        get_todo(&conn, id)
+    }
+
+    /// Moves a todo's reminder to `minutes` from now, pending again.
+    #[tracing::instrument(skip(self))]
+    pub fn snooze_reminder(&self, id: i64, minutes: u32) -> Result<Todo> {
+        if minutes == 0 {
+            return Err(Error::InvalidSnooze(minutes));
+        }
+        let remind_at = timestamp(Utc::now() + Duration::minutes(minutes.into()));
+        self.set_reminder(id, Some(&remind_at))
+    }
+
+    /// Reminders that have not fired yet, soonest first.
+    pub fn pending_reminders(&self) -> Result<Vec<Todo>> {
+        let conn = self.conn();
+        query_todos(
+            &conn,
+            "SELECT * FROM todos
+             WHERE remind_at IS NOT NULL AND reminded_at IS NULL AND completed = 0
+             ORDER BY remind_at ASC, id ASC",
+            [],
+        )
+    }
+
+    /// Marks every pending reminder whose time has come as sent and returns
+    /// their todos, oldest reminder first. Each reminder is handed out once,
+    /// even with several callers.
+    pub fn take_due_reminders(&self) -> Result<Vec<Todo>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let now = now();
+        let todos = query_todos(
+            &tx,
+            "SELECT * FROM todos
+             WHERE remind_at <= ?1 AND reminded_at IS NULL AND completed = 0
+             ORDER BY remind_at ASC, id ASC",
+            [&now],
+        )?;
+        if !todos.is_empty() {
+            tx.execute(
+                "UPDATE todos SET reminded_at = ?1
+                 WHERE remind_at <= ?1 AND reminded_at IS NULL AND completed = 0",
+                [&now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(todos)
+    }
+}
@@ -0,0 +1,243 @@
+//! Reports of the todos of a range of days, such as last week for a
+//! stand-up, as CSV with one row per todo or as a Markdown checklist:
+//!
+//! ```text
+//! ## Monday 2026-10-12
+//!
+//! - [x] Renew passport
+//!   - [x] Fill in the form
+//! - [ ] Call the bank
+//! ```
+//!
+//! Both list the days in order and each day's todos in list order, with
+//! subtasks after their parent. Times are local.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::Serialize;
+
+use super::{parse_date, Error, Result, Store, TodoNode};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// `day,text,completed,completed_at` rows.
+    #[default]
+    Csv,
+    /// A `- [x] todo` checklist under a heading per day.
+    Markdown,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "csv" => Ok(Self::Csv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(Error::InvalidReportFormat(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Csv => "csv",
+            Self::Markdown => "markdown",
+        })
+    }
+}
+
+/// The todos of one day, with their subtasks nested below them.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportDay {
+    pub day: String,
+    pub todos: Vec<TodoNode>,
+}
+
+impl Store {
+    /// Every day from `from` to `to`, both included, that has todos.
+    pub fn todos_between(&self, from: &str, to: &str) -> Result<Vec<ReportDay>> {
+        let (from, to) = (parse_date(from)?, parse_date(to)?);
+        if from > to {
+            return Err(Error::InvalidDateRange {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
+        }
+        let days: Vec<String> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare_cached(
+                "SELECT DISTINCT scheduled_date FROM todos
+                 WHERE scheduled_date BETWEEN ?1 AND ?2
+                 ORDER BY scheduled_date",
+            )?;
+            let days = stmt.query_map([from.to_string(), to.to_string()], |row| row.get(0))?;
+            days.collect::<rusqlite::Result<_>>()?
+        };
+        days.into_iter()
+            .map(|day| {
+                Ok(ReportDay {
+                    todos: self.todo_tree(&day)?,
+                    day,
+                })
+            })
+            .collect()
+    }
+
+    /// The todos from `from` to `to`, both included, as a report.
+    #[tracing::instrument(skip(self))]
+    pub fn report(&self, from: &str, to: &str, format: ReportFormat) -> Result<String> {
+        let days = self.todos_between(from, to)?;
+        Ok(match format {
+            ReportFormat::Csv => write_csv(&days, &Local),
+            ReportFormat::Markdown => write_markdown(&days),
+        })
+    }
+}
+
+/// One row per todo after a header, with completion times local to `zone`.
+pub fn write_csv<Tz: TimeZone>(days: &[ReportDay], zone: &Tz) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    let mut out = String::from("day,text,completed,completed_at\r\n");
+    for day in days {
+        for_each(&day.todos, 0, &mut |node, _| {
+            let todo = &node.todo;
+            let completed_at = todo
+                .completed_at
+                .as_deref()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| at.with_timezone(zone).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            let row = [
+                day.day.as_str(),
+                &todo.text,
+                if todo.completed { "true" } else { "false" },
+                &completed_at,
+            ];
+            let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            out.push_str(&fields.join(","));
+            out.push_str("\r\n");
+        });
+    }
+    out
+}
+
+/// A checklist per day under a heading, subtasks indented below their
+/// parent.
+pub fn write_markdown(days: &[ReportDay]) -> String {
+    let mut sections = Vec::new();
+    for day in days {
+        let heading = match NaiveDate::parse_from_str(&day.day, "%Y-%m-%d") {
+            Ok(date) => date.format("## %A %Y-%m-%d").to_string(),
+            Err(_) => format!("## {}", day.day),
+        };
+        let mut section = format!("{heading}\n\n");
+        for_each(&day.todos, 0, &mut |node, depth| {
+            let mark = if node.todo.completed { 'x' } else { ' ' };
+            section.push_str(&format!(
+                "{}- [{mark}] {}\n",
+                "  ".repeat(depth),
+                node.todo.text
+            ));
+        });
+        sections.push(section);
+    }
+    sections.join("\n")
+}
+
+/// Visits todos depth-first, parents before their subtasks.
+fn for_each(nodes: &[TodoNode], depth: usize, visit: &mut impl FnMut(&TodoNode, usize)) {
+    for node in nodes {
+        visit(node, depth);
+        for_each(&node.children, depth + 1, visit);
+    }
+}
+
+/// Quotes a field when it holds a comma, quote or line break. A field a
+/// spreadsheet would read as a formula gets a leading `'` so it opens as
+/// the text that was typed.
+fn csv_field(field: &str) -> String {
+    if field.starts_with(['=', '+', '-', '@']) {
+        format!("\"'{}\"", field.replace('"', "\"\""))
+    } else if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::NewTodo;
+
+    /// A week with a trip and its subtask on Monday, a todo on Wednesday and
+    /// one just outside the range.
+    fn week(store: &Store) -> Vec<ReportDay> {
+        let trip = add(store, todo("Plan trip", "2026-10-12"));
+        let hotel = NewTodo {
+            parent_id: Some(trip.id),
+            ..todo("Book hotel, \"central\"", "2026-10-12")
+        };
+        let hotel = add(store, hotel);
+        add(store, todo("Call the bank", "2026-10-14"));
+        add(store, todo("Next week", "2026-10-19"));
+        store.set_completed(hotel.id, true).unwrap();
+        let mut days = store.todos_between("2026-10-12", "2026-10-18").unwrap();
+        let subtask = &mut days[0].todos[0].children[0].todo;
+        subtask.completed_at = Some("2026-10-13T07:05:00.000Z".to_owned());
+        days
+    }
+
+    #[test]
+    fn lists_days_in_the_range() {
+        let store = store();
+        let days = week(&store);
+        let listed: Vec<&str> = days.iter().map(|d| d.day.as_str()).collect();
+        assert_eq!(listed, ["2026-10-12", "2026-10-14"]);
+        assert!(matches!(
+            store.todos_between("2026-10-18", "2026-10-12"),
+            Err(Error::InvalidDateRange { .. })
+        ));
+    }
+
+    #[test]
+    fn writes_csv_rows() {
+        let store = store();
+        add(&store, todo("=SUM(A1)", "2026-10-16"));
+        add(&store, todo("@home \"now\"", "2026-10-16"));
+        assert_eq!(
+            write_csv(&week(&store), &Berlin),
+            "day,text,completed,completed_at\r\n\
+             2026-10-12,Plan trip,false,\r\n\
+             2026-10-12,\"Book hotel, \"\"central\"\"\",true,2026-10-13 09:05\r\n\
+             2026-10-14,Call the bank,false,\r\n\
+             2026-10-16,\"'=SUM(A1)\",false,\r\n\
+             2026-10-16,\"'@home \"\"now\"\"\",false,\r\n"
+        );
+    }
+
+    #[test]
+    fn writes_markdown_checklists() {
+        let store = store();
+        assert_eq!(
+            write_markdown(&week(&store)),
+            "## Monday 2026-10-12\n\
+             \n\
+             - [ ] Plan trip\n  \
+             - [x] Book hotel, \"central\"\n\
+             \n\
+             ## Wednesday 2026-10-14\n\
+             \n\
+             - [ ] Call the bank\n"
+        );
+    }
+}
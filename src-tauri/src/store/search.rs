@@ -0,0 +1,311 @@
+//! Full-text search over every day's todos.
+//!
+//! `todos_fts` is an FTS5 table keyed by todo id holding each todo's text,
+//! notes and tag names. Triggers on `todos`, `todo_tags` and `tags` keep it
+//! current, so nothing outside the schema has to remember to update it.
+//!
+//! User input is never passed to FTS5 as is: [`fts_query`] quotes every word
+//! so punctuation cannot form operators, treats bare words as prefixes so
+//! results show up while typing, and keeps `"quoted phrases"` exact.
+
+use rusqlite::params;
+use serde::{Serialize, Serializer};
+
+use super::{get_todo, Result, Store, Todo};
+
+/// Most hits a search returns when no limit is given.
+pub const DEFAULT_LIMIT: usize = 50;
+
+// Marks around matched terms in FTS5 output, replaced when rendering.
+const OPEN: char = '\u{2}';
+const CLOSE: char = '\u{3}';
+
+/// Words of context around a match in a notes snippet.
+const SNIPPET_TOKENS: u32 = 16;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub todo: Todo,
+    /// The todo's text with the matched terms marked.
+    pub title: Highlighted,
+    /// Excerpt of the notes around the best match, when the notes matched.
+    pub snippet: Option<Highlighted>,
+}
+
+/// Text with matched terms marked. Serializes as HTML with each match in a
+/// `<mark>` element and everything else escaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlighted(String);
+
+impl Highlighted {
+    /// The text with each match wrapped in `open` and `close`.
+    pub fn marked(&self, open: &str, close: &str) -> String {
+        self.0.replace(OPEN, open).replace(CLOSE, close)
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::with_capacity(self.0.len());
+        for c in self.0.chars() {
+            match c {
+                OPEN => html.push_str("<mark>"),
+                CLOSE => html.push_str("</mark>"),
+                '&' => html.push_str("&amp;"),
+                '<' => html.push_str("&lt;"),
+                '>' => html.push_str("&gt;"),
+                '"' => html.push_str("&quot;"),
+                '\'' => html.push_str("&#39;"),
+                c => html.push(c),
+            }
+        }
+        html
+    }
+
+    fn has_match(&self) -> bool {
+        self.0.contains(OPEN)
+    }
+}
+
+impl Serialize for Highlighted {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_html())
+    }
+}
+
+impl Store {
+    /// Todos of any day matching `query`, best match first. Words match
+    /// anything starting with them, `"quoted phrases"` match exactly, and
+    /// every word or phrase has to match. Matches in the text weigh more
+    /// than matches in tags, which weigh more than matches in notes.
+    #[tracing::instrument(skip(self))]
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT rowid,
+                    highlight(todos_fts, 0, ?3, ?4),
+                    snippet(todos_fts, 1, ?3, ?4, '…', ?5)
+             FROM todos_fts
+             WHERE todos_fts MATCH ?1 AND rank MATCH 'bm25(10.0, 1.0, 4.0)'
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(
+                params![
+                    query,
+                    limit as i64,
+                    OPEN.to_string(),
+                    CLOSE.to_string(),
+                    SNIPPET_TOKENS
+                ],
+                |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?)),
+            )?
+            .collect::<rusqlite::Result<Vec<(i64, String, Option<String>)>>>()?;
+        rows.into_iter()
+            .map(|(id, title, snippet)| {
+                Ok(SearchHit {
+                    todo: get_todo(&conn, id)?,
+                    title: Highlighted(title),
+                    snippet: snippet.map(Highlighted).filter(Highlighted::has_match),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Turns user input into an FTS5 query, or `None` when it has nothing to
+/// search for.
+fn fts_query(input: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let (term, prefix, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                let tail = quoted.get(end + 1..).unwrap_or("");
+                // `"phrase"*` makes the last word a prefix.
+                match tail.strip_prefix('*') {
+                    Some(tail) => (&quoted[..end], true, tail),
+                    None => (&quoted[..end], false, tail),
+                }
+            }
+            None => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '"')
+                    .unwrap_or(rest.len());
+                (rest[..end].trim_end_matches('*'), true, &rest[end..])
+            }
+        };
+        if term.chars().any(char::is_alphanumeric) {
+            let star = if prefix { "*" } else { "" };
+            terms.push(format!("\"{}\"{star}", term.replace('"', "\"\"")));
+        }
+        rest = tail.trim_start();
+    }
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::TodoUpdate;
+
+    const DAY: &str = "2026-10-20";
+
+    fn found(store: &Store, query: &str) -> Vec<String> {
+        store
+            .search(query, DEFAULT_LIMIT)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.todo.text)
+            .collect()
+    }
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn follows_text_and_notes_edits() {
+        let store = store();
+        let todo = add(&store, todo("Buy milk", DAY));
+        assert_eq!(found(&store, "mil"), ["Buy milk"]);
+
+        let update = TodoUpdate {
+            text: Some("Buy oat drink".to_owned()),
+            ..TodoUpdate::default()
+        };
+        store.update_todo(todo.id, update).unwrap();
+        assert!(found(&store, "milk").is_empty());
+        assert_eq!(found(&store, "oat"), ["Buy oat drink"]);
+
+        store
+            .set_notes(todo.id, Some("Ask whether the **lease** covers it"))
+            .unwrap();
+        let hits = store.search("lease", DEFAULT_LIMIT).unwrap();
+        let snippet = hits[0].snippet.as_ref().unwrap();
+        assert!(snippet.marked("[", "]").contains("[lease]"));
+        store.set_notes(todo.id, None).unwrap();
+        assert!(found(&store, "lease").is_empty());
+
+        store.delete_todo(todo.id).unwrap();
+        assert!(found(&store, "oat").is_empty());
+    }
+
+    #[test]
+    fn follows_tag_changes() {
+        let store = store();
+        let todo = add(&store, todo("Buy milk", DAY));
+        store.tag_todo(todo.id, &tags(&["groceries"])).unwrap();
+        assert_eq!(found(&store, "grocer"), ["Buy milk"]);
+
+        store.rename_tag("groceries", "shopping").unwrap();
+        assert!(found(&store, "groceries").is_empty());
+        assert_eq!(found(&store, "shopping"), ["Buy milk"]);
+
+        store.merge_tags(&tags(&["shopping"]), "errands").unwrap();
+        assert!(found(&store, "shopping").is_empty());
+        assert_eq!(found(&store, "errands"), ["Buy milk"]);
+
+        store.untag_todo(todo.id, &tags(&["errands"])).unwrap();
+        assert!(found(&store, "errands").is_empty());
+        store.tag_todo(todo.id, &tags(&["dairy"])).unwrap();
+        store.delete_tag("dairy").unwrap();
+        assert!(found(&store, "dairy").is_empty());
+    }
+
+    #[test]
+    fn ranks_text_above_notes_and_highlights() {
+        let store = store();
+        let notes = add(&store, todo("Call the bank", DAY));
+        store
+            .set_notes(notes.id, Some("Ask about the <b>passport</b> form"))
+            .unwrap();
+        add(&store, todo("Renew passport & visa", DAY));
+
+        let hits = store.search("passport", DEFAULT_LIMIT).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].todo.text, "Renew passport & visa");
+        assert_eq!(
+            hits[0].title.to_html(),
+            "Renew <mark>passport</mark> &amp; visa"
+        );
+        assert!(hits[0].snippet.is_none());
+        assert_eq!(hits[1].title.marked("[", "]"), "Call the bank");
+        assert!(hits[1]
+            .snippet
+            .as_ref()
+            .unwrap()
+            .to_html()
+            .contains("&lt;b&gt;<mark>passport</mark>&lt;/b&gt;"));
+        assert_eq!(store.search("passport", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn matches_words_as_prefixes_and_phrases_exactly() {
+        let store = store();
+        add(&store, todo("Book the train to Berlin", DAY));
+        add(&store, todo("Train for the marathon", DAY));
+        assert_eq!(found(&store, "book trai").len(), 1);
+        assert_eq!(found(&store, "train").len(), 2);
+        assert_eq!(found(&store, "\"the train\""), ["Book the train to Berlin"]);
+        assert!(found(&store, "\"the trai\"").is_empty());
+        assert_eq!(found(&store, "\"the trai\"*"), ["Book the train to Berlin"]);
+    }
+
+    #[test]
+    fn builds_quoted_queries() {
+        assert_eq!(fts_query("milk").as_deref(), Some("\"milk\"*"));
+        assert_eq!(
+            fts_query("oat  \"whole milk\" 2%").as_deref(),
+            Some("\"oat\"* \"whole milk\" \"2%\"*")
+        );
+        assert_eq!(fts_query("a\"b").as_deref(), Some("\"a\"* \"b\""));
+        assert_eq!(fts_query("NEAR(a").as_deref(), Some("\"NEAR(a\"*"));
+        for blank in ["", "   ", "*", "\"\"", "- * ( )", "\""] {
+            assert_eq!(fts_query(blank), None, "{blank:?}");
+        }
+    }
+
+    #[test]
+    fn hostile_input_never_breaks_the_query() {
+        let store = store();
+        add(&store, todo("Stay NEAR the station, not -far- away", DAY));
+        for query in [
+            "\"",
+            "\"unbalanced",
+            "unbalanced\"",
+            "\"\"\"",
+            "a\"b\"c",
+            "*",
+            "**milk**",
+            "station*",
+            "-station",
+            "station -far",
+            "NEAR",
+            "NEAR(station away)",
+            "station NEAR away",
+            "station OR away",
+            "station AND NOT away",
+            "^station",
+            "text:station",
+            "{text notes}: station",
+            "(station",
+            "station)",
+            "'station'",
+            "station;DROP TABLE todos",
+            "\u{2}station\u{3}",
+            "ünïcödé",
+        ] {
+            assert!(store.search(query, DEFAULT_LIMIT).is_ok(), "{query:?}");
+        }
+        // Operators are searched for as words.
+        assert_eq!(found(&store, "NEAR").len(), 1);
+        assert_eq!(found(&store, "-far").len(), 1);
+        assert_eq!(found(&store, "station NOT away").len(), 1);
+        assert!(found(&store, "station OR train").is_empty());
+    }
+}
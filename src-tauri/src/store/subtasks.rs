@@ -0,0 +1,420 @@
+//! Subtasks: todos nested under another todo, to any depth.
+//!
+//! A subtask lives on its parent's day and follows it when the parent is
+//! carried over or moved. Day lists only contain top-level todos, each with
+//! a [`Progress`] rollup of its direct subtasks; [`Store::todo_tree`] returns
+//! the whole hierarchy. `sort_order` of a subtask is its position among its
+//! siblings.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::tags::json_list;
+use super::{
+    get_todo, now, parse_date, priority, query_todos, renumber, setting, write_order, Error,
+    Result, Store, Todo, ORDER_BY,
+};
+
+/// Settings key holding the [`Completion`] behavior.
+pub const COMPLETION_SETTING: &str = "subtask_completion";
+
+/// What completing a todo does to its subtasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Completion {
+    /// Complete every unfinished subtask, at any depth.
+    #[default]
+    Cascade,
+    /// Leave subtasks as they are.
+    Independent,
+}
+
+impl FromStr for Completion {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim() {
+            "cascade" => Ok(Self::Cascade),
+            "independent" => Ok(Self::Independent),
+            _ => Err(Error::InvalidSubtaskCompletion(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Cascade => "cascade",
+            Self::Independent => "independent",
+        })
+    }
+}
+
+/// How many of a todo's direct subtasks are done, shown as "3/5".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// A todo with its subtasks, in list order.
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoNode {
+    #[serde(flatten)]
+    pub todo: Todo,
+    pub children: Vec<TodoNode>,
+}
+
+impl Store {
+    /// Top-level todos of `date` with all their subtasks nested below them.
+    pub fn todo_tree(&self, date: &str) -> Result<Vec<TodoNode>> {
+        let date = parse_date(date)?;
+        let conn = self.conn();
+        let order_by = priority::sort_mode(&conn)?.order_by("sort_order");
+        let roots = query_todos(
+            &conn,
+            &format!(
+                "SELECT * FROM todos WHERE scheduled_date = ?1 AND parent_id IS NULL {order_by}"
+            ),
+            [date.to_string()],
+        )?;
+        let root_ids: Vec<i64> = roots.iter().map(|t| t.id).collect();
+        let descendants = query_todos(
+            &conn,
+            &format!(
+                "WITH RECURSIVE subtree (id) AS (
+                     SELECT id FROM todos WHERE parent_id IN (SELECT value FROM json_each(?1))
+                     UNION ALL
+                     SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id
+                 )
+                 SELECT * FROM todos WHERE id IN subtree {order_by}"
+            ),
+            [json_list(&root_ids)],
+        )?;
+        let mut children: HashMap<i64, Vec<Todo>> = HashMap::new();
+        for todo in descendants {
+            let parent = todo.parent_id.expect("descendants have a parent");
+            children.entry(parent).or_default().push(todo);
+        }
+        Ok(roots
+            .into_iter()
+            .map(|todo| build(todo, &mut children))
+            .collect())
+    }
+
+    /// Moves a todo and its subtasks under `parent`, or to the top level of
+    /// its day with `None`, at `position` among its new siblings (the end
+    /// when `None`). The subtree takes on the new parent's day. Repeating
+    /// todos cannot become subtasks.
+    #[tracing::instrument(skip(self))]
+    pub fn move_todo(&self, id: i64, parent: Option<i64>, position: Option<usize>) -> Result<Todo> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let todo = get_todo(&tx, id)?;
+        let day = match parent {
+            Some(_) if todo.recurrence.is_some() => return Err(Error::RecurringSubtask),
+            Some(parent) => {
+                if parent == id || is_descendant(&tx, parent, id)? {
+                    return Err(Error::InvalidParent(id));
+                }
+                get_todo(&tx, parent)?.scheduled_date
+            }
+            None => todo.scheduled_date.clone(),
+        };
+
+        let mut siblings = sibling_ids(&tx, parent, &day)?;
+        siblings.retain(|&sibling| sibling != id);
+        let position = position.unwrap_or(siblings.len()).min(siblings.len());
+        siblings.insert(position, id);
+
+        tx.execute(
+            "UPDATE todos SET parent_id = ?2, scheduled_date = ?3, updated_at = ?4
+             WHERE id = ?1",
+            params![id, parent, day, now()],
+        )?;
+        tx.execute(
+            "WITH RECURSIVE subtree (id) AS (
+                 SELECT id FROM todos WHERE parent_id = ?1
+                 UNION ALL
+                 SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id
+             )
+             UPDATE todos SET scheduled_date = ?2 WHERE id IN subtree",
+            params![id, day],
+        )?;
+        write_order(&tx, &siblings)?;
+        // Incomplete todos stay ahead of completed ones.
+        renumber(&tx, &day, parent)?;
+        let todo = get_todo(&tx, id)?;
+        tx.commit()?;
+        Ok(todo)
+    }
+}
+
+fn build(todo: Todo, children: &mut HashMap<i64, Vec<Todo>>) -> TodoNode {
+    let kids = children.remove(&todo.id).unwrap_or_default();
+    TodoNode {
+        children: kids.into_iter().map(|kid| build(kid, children)).collect(),
+        todo,
+    }
+}
+
+/// Ids of the todos sharing `parent`, or of the top-level todos of `day`,
+/// in manual order.
+pub(super) fn sibling_ids(conn: &Connection, parent: Option<i64>, day: &str) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT id FROM todos
+         WHERE parent_id IS ?1 AND (?1 IS NOT NULL OR scheduled_date = ?2)
+         {ORDER_BY}"
+    ))?;
+    let ids = stmt.query_map(params![parent, day], |row| row.get(0))?;
+    Ok(ids.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Whether `id` sits somewhere below `ancestor`.
+fn is_descendant(conn: &Connection, id: i64, ancestor: i64) -> Result<bool> {
+    Ok(conn.query_row(
+        "WITH RECURSIVE ancestors (id) AS (
+             SELECT parent_id FROM todos WHERE id = ?1
+             UNION ALL
+             SELECT todos.parent_id FROM todos JOIN ancestors ON todos.id = ancestors.id
+         )
+         SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = ?2)",
+        params![id, ancestor],
+        |row| row.get(0),
+    )?)
+}
+
+/// Position after the last incomplete subtask of `parent`, failing if the
+/// parent does not exist. Also returns the parent's day.
+pub(super) fn next_child(conn: &Connection, parent: i64) -> Result<(String, i64)> {
+    let day: String = conn
+        .query_row(
+            "SELECT scheduled_date FROM todos WHERE id = ?1",
+            [parent],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or(Error::NotFound(parent))?;
+    let next = conn.query_row(
+        "SELECT COALESCE(MAX(sort_order), 0) + 1 FROM todos
+         WHERE completed = 0 AND parent_id = ?1",
+        [parent],
+        |row| row.get(0),
+    )?;
+    Ok((day, next))
+}
+
+/// Completes the unfinished subtasks of `id` at any depth when the
+/// configured [`Completion`] asks for it.
+pub(super) fn complete_descendants(conn: &Connection, id: i64, at: &str) -> Result<usize> {
+    let completion = setting(conn, COMPLETION_SETTING)?
+        .and_then(|value| value.parse::<Completion>().ok())
+        .unwrap_or_default();
+    if completion == Completion::Independent {
+        return Ok(0);
+    }
+    Ok(conn.execute(
+        "WITH RECURSIVE subtree (id) AS (
+             SELECT id FROM todos WHERE parent_id = ?1
+             UNION ALL
+             SELECT todos.id FROM todos JOIN subtree ON todos.parent_id = subtree.id
+         )
+         UPDATE todos SET completed = 1, completed_at = ?2, updated_at = ?2
+         WHERE completed = 0 AND id IN subtree",
+        params![id, at],
+    )?)
+}
+
+/// Fills in the subtask progress of `todos` with a single query.
+pub(super) fn attach(conn: &Connection, todos: &mut [Todo]) -> Result<()> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<i64> = todos.iter().map(|t| t.id).collect();
+    let mut stmt = conn.prepare_cached(
+        "SELECT parent_id, SUM(completed), COUNT(*) FROM todos
+         WHERE parent_id IN (SELECT value FROM json_each(?1))
+         GROUP BY parent_id",
+    )?;
+    let mut progress: HashMap<i64, Progress> = HashMap::new();
+    let rows = stmt.query_map([json_list(&ids)], |row| {
+        let counts = Progress {
+            completed: row.get(1)?,
+            total: row.get(2)?,
+        };
+        Ok((row.get(0)?, counts))
+    })?;
+    for row in rows {
+        let (parent, counts) = row?;
+        progress.insert(parent, counts);
+    }
+    for todo in todos {
+        todo.progress = progress.remove(&todo.id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::NewTodo;
+
+    const DAY: &str = "2026-10-20";
+
+    fn under(parent: &Todo, text: &str) -> NewTodo {
+        NewTodo {
+            parent_id: Some(parent.id),
+            ..todo(text, DAY)
+        }
+    }
+
+    fn texts(nodes: &[TodoNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.todo.text.as_str()).collect()
+    }
+
+    /// "Plan trip" with "Book hotel" and "Book train", which has "Compare
+    /// fares" below it.
+    fn trip(store: &Store) -> (Todo, Todo, Todo, Todo) {
+        let trip = add(store, todo("Plan trip", DAY));
+        let hotel = add(store, under(&trip, "Book hotel"));
+        let train = add(store, under(&trip, "Book train"));
+        let fares = add(store, under(&train, "Compare fares"));
+        (trip, hotel, train, fares)
+    }
+
+    #[test]
+    fn nests_subtasks_with_progress() {
+        let store = store();
+        let (trip, hotel, ..) = trip(&store);
+        add(&store, todo("Call the bank", DAY));
+        store.set_completed(hotel.id, true).unwrap();
+
+        let tree = store.todo_tree(DAY).unwrap();
+        assert_eq!(texts(&tree), ["Plan trip", "Call the bank"]);
+        assert_eq!(texts(&tree[0].children), ["Book train", "Book hotel"]);
+        assert_eq!(texts(&tree[0].children[0].children), ["Compare fares"]);
+
+        let day: Vec<Todo> = store.todos_by_date(DAY).unwrap();
+        assert_eq!(day.len(), 2);
+        assert_eq!(
+            day[0].progress,
+            Some(Progress {
+                completed: 1,
+                total: 2
+            })
+        );
+        assert_eq!(day[1].progress, None);
+        assert_eq!(store.get_todo(trip.id).unwrap().progress, day[0].progress);
+    }
+
+    #[test]
+    fn moves_subtrees_onto_the_new_parents_day() {
+        let store = store();
+        let (trip, hotel, train, fares) = trip(&store);
+        let packing = add(&store, todo("Pack", "2026-10-21"));
+        let socks = add(&store, under(&packing, "Socks"));
+
+        let moved = store.move_todo(packing.id, Some(trip.id), Some(1)).unwrap();
+        assert_eq!(moved.parent_id, Some(trip.id));
+        assert_eq!(moved.scheduled_date, DAY);
+        assert_eq!(store.get_todo(socks.id).unwrap().scheduled_date, DAY);
+        let tree = store.todo_tree(DAY).unwrap();
+        assert_eq!(
+            texts(&tree[0].children),
+            ["Book hotel", "Pack", "Book train"]
+        );
+
+        let moved = store.move_todo(fares.id, None, Some(0)).unwrap();
+        assert_eq!(moved.parent_id, None);
+        assert_eq!(
+            texts(&store.todo_tree(DAY).unwrap()),
+            ["Compare fares", "Plan trip"]
+        );
+        assert_eq!(store.get_todo(train.id).unwrap().progress, None);
+        assert_eq!(store.get_todo(hotel.id).unwrap().parent_id, Some(trip.id));
+        assert!(store.todo_tree("2026-10-21").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let store = store();
+        let (trip, _, train, fares) = trip(&store);
+        for (id, parent) in [(trip.id, trip.id), (trip.id, train.id), (trip.id, fares.id)] {
+            assert!(matches!(
+                store.move_todo(id, Some(parent), None),
+                Err(Error::InvalidParent(_))
+            ));
+        }
+        assert!(matches!(
+            store.move_todo(trip.id, Some(999), None),
+            Err(Error::NotFound(999))
+        ));
+        assert_eq!(store.get_todo(trip.id).unwrap().parent_id, None);
+    }
+
+    #[test]
+    fn repeating_todos_stay_top_level() {
+        let store = store();
+        let (trip, ..) = trip(&store);
+        let gym = NewTodo {
+            recurrence: Some("every week".to_owned()),
+            ..todo("Gym", DAY)
+        };
+        let gym = add(&store, gym);
+        assert!(matches!(
+            store.move_todo(gym.id, Some(trip.id), None),
+            Err(Error::RecurringSubtask)
+        ));
+        assert_eq!(store.get_todo(gym.id).unwrap().parent_id, None);
+        assert!(matches!(
+            store.create_todo(&NewTodo {
+                recurrence: Some("every week".to_owned()),
+                ..under(&trip, "Stretch")
+            }),
+            Err(Error::RecurringSubtask)
+        ));
+        // Moving along the top level is fine.
+        store.move_todo(gym.id, None, Some(0)).unwrap();
+        assert_eq!(texts(&store.todo_tree(DAY).unwrap()), ["Gym", "Plan trip"]);
+    }
+
+    #[test]
+    fn completing_cascades_by_default() {
+        let store = store();
+        let (trip, hotel, train, fares) = trip(&store);
+        store.set_completed(trip.id, true).unwrap();
+        for todo in [&hotel, &train, &fares] {
+            assert!(store.get_todo(todo.id).unwrap().completed);
+        }
+        // Reopening the parent leaves the subtasks done.
+        store.set_completed(trip.id, false).unwrap();
+        assert!(store.get_todo(fares.id).unwrap().completed);
+    }
+
+    #[test]
+    fn completing_independently_leaves_subtasks() {
+        let store = store();
+        store
+            .set_setting(COMPLETION_SETTING, &Completion::Independent.to_string())
+            .unwrap();
+        let (trip, hotel, _, fares) = trip(&store);
+        store.set_completed(trip.id, true).unwrap();
+        assert!(!store.get_todo(hotel.id).unwrap().completed);
+        assert!(!store.get_todo(fares.id).unwrap().completed);
+        assert_eq!(
+            store.get_todo(trip.id).unwrap().progress,
+            Some(Progress {
+                completed: 0,
+                total: 2
+            })
+        );
+        assert!(matches!(
+            "sometimes".parse::<Completion>(),
+            Err(Error::InvalidSubtaskCompletion(_))
+        ));
+    }
+}
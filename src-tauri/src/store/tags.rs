@@ -0,0 +1,462 @@
+//! Tags: named, optionally colored labels attached to any number of todos.
+//!
+//! Names are case-insensitive and stored without a leading `#`, so `#Work`,
+//! `work` and `WORK` are the same tag. Other prefixes such as `@` are part of
+//! the name, which keeps contexts like `@phone` apart from `phone`.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::{get_todo, now, parse_date, priority, query_todos, Error, Result, Store, Todo};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// `#rrggbb`, or `None` for the default color.
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagUsage {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub todo_count: usize,
+}
+
+/// How [`Store::todos_by_tags`] combines several tags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Todos carrying every tag.
+    #[default]
+    All,
+    /// Todos carrying at least one of the tags.
+    Any,
+}
+
+impl Store {
+    /// Every tag with the number of todos carrying it, by name.
+    pub fn tags(&self) -> Result<Vec<TagUsage>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, name, color, (SELECT COUNT(*) FROM todo_tags WHERE tag_id = id)
+             FROM tags ORDER BY name",
+        )?;
+        let tags = stmt
+            .query_map([], |row| {
+                Ok(TagUsage {
+                    tag: Tag {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        color: row.get(2)?,
+                    },
+                    todo_count: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(tags)
+    }
+
+    /// Adds tags to a todo, creating tags that do not exist yet.
+    #[tracing::instrument(skip(self))]
+    pub fn tag_todo(&self, id: i64, names: &[String]) -> Result<Todo> {
+        let names = normalize_names(names)?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        touch(&tx, id)?;
+        add(&tx, id, &names)?;
+        let todo = get_todo(&tx, id)?;
+        tx.commit()?;
+        Ok(todo)
+    }
+
+    /// Removes tags from a todo. The tags themselves stay, even when no todo
+    /// carries them any more.
+    #[tracing::instrument(skip(self))]
+    pub fn untag_todo(&self, id: i64, names: &[String]) -> Result<Todo> {
+        let names = normalize_names(names)?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        touch(&tx, id)?;
+        tx.execute(
+            "DELETE FROM todo_tags WHERE todo_id = ?1 AND tag_id IN
+                 (SELECT id FROM tags WHERE name IN (SELECT value FROM json_each(?2)))",
+            params![id, json_list(&names)],
+        )?;
+        let todo = get_todo(&tx, id)?;
+        tx.commit()?;
+        Ok(todo)
+    }
+
+    /// Renames a tag. Renaming onto another existing tag is refused; use
+    /// [`Store::merge_tags`] for that.
+    #[tracing::instrument(skip(self))]
+    pub fn rename_tag(&self, name: &str, new_name: &str) -> Result<Tag> {
+        let new_name = normalize_name(new_name)?;
+        let conn = self.conn();
+        let tag = find_tag(&conn, &normalize_name(name)?)?;
+        match find_tag(&conn, &new_name) {
+            Ok(other) if other.id != tag.id => return Err(Error::TagExists(new_name)),
+            Ok(_) | Err(Error::TagNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        conn.execute(
+            "UPDATE tags SET name = ?2 WHERE id = ?1",
+            params![tag.id, new_name],
+        )?;
+        Ok(Tag {
+            name: new_name,
+            ..tag
+        })
+    }
+
+    /// Moves every todo of the `sources` tags onto `into`, creating it if
+    /// needed, and deletes the sources.
+    #[tracing::instrument(skip(self))]
+    pub fn merge_tags(&self, sources: &[String], into: &str) -> Result<Tag> {
+        let sources = normalize_names(sources)?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let target = ensure_tag(&tx, &normalize_name(into)?)?;
+        for name in &sources {
+            let source = find_tag(&tx, name)?;
+            if source.id == target.id {
+                continue;
+            }
+            tx.execute(
+                "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id)
+                 SELECT todo_id, ?2 FROM todo_tags WHERE tag_id = ?1",
+                params![source.id, target.id],
+            )?;
+            tx.execute("DELETE FROM tags WHERE id = ?1", [source.id])?;
+        }
+        tx.commit()?;
+        Ok(target)
+    }
+
+    /// Sets a tag's color, or resets it to the default with `None`.
+    pub fn set_tag_color(&self, name: &str, color: Option<&str>) -> Result<Tag> {
+        let color = color.map(parse_color).transpose()?;
+        let conn = self.conn();
+        let tag = find_tag(&conn, &normalize_name(name)?)?;
+        conn.execute(
+            "UPDATE tags SET color = ?2 WHERE id = ?1",
+            params![tag.id, color],
+        )?;
+        Ok(Tag { color, ..tag })
+    }
+
+    /// Deletes a tag and takes it off every todo. Returns whether it existed.
+    pub fn delete_tag(&self, name: &str) -> Result<bool> {
+        let name = normalize_name(name)?;
+        let conn = self.conn();
+        Ok(conn.execute("DELETE FROM tags WHERE name = ?1", [name])? > 0)
+    }
+
+    /// Todos carrying all or any of `names`, optionally limited to one day.
+    pub fn todos_by_tags(
+        &self,
+        names: &[String],
+        mode: TagMatch,
+        date: Option<&str>,
+    ) -> Result<Vec<Todo>> {
+        let names = normalize_names(names)?;
+        let date = date.map(parse_date).transpose()?.map(|d| d.to_string());
+        // A todo matches when it carries at least this many of the tags.
+        let required = match mode {
+            TagMatch::All => names.len(),
+            TagMatch::Any => 1,
+        };
+        let conn = self.conn();
+        let order_by = priority::sort_mode(&conn)?.order_by("sort_order");
+        query_todos(
+            &conn,
+            &format!(
+                "SELECT * FROM todos
+                 WHERE id IN (
+                     SELECT todo_id FROM todo_tags JOIN tags ON tags.id = tag_id
+                     WHERE tags.name IN (SELECT value FROM json_each(?1))
+                     GROUP BY todo_id HAVING COUNT(*) >= ?2)
+                   AND (?3 IS NULL OR scheduled_date = ?3)
+                 {order_by}"
+            ),
+            params![json_list(&names), required, date],
+        )
+    }
+}
+
+/// Adds already normalized tags to a todo, creating missing ones.
+pub(super) fn add(conn: &Connection, todo_id: i64, names: &[String]) -> Result<()> {
+    let mut stmt =
+        conn.prepare_cached("INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)")?;
+    for name in names {
+        stmt.execute(params![todo_id, ensure_tag(conn, name)?.id])?;
+    }
+    Ok(())
+}
+
+/// Fills in the tags of `todos` with a single query.
+pub(super) fn attach(conn: &Connection, todos: &mut [Todo]) -> Result<()> {
+    if todos.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<i64> = todos.iter().map(|t| t.id).collect();
+    let mut stmt = conn.prepare_cached(
+        "SELECT todo_id, id, name, color FROM todo_tags JOIN tags ON tags.id = tag_id
+         WHERE todo_id IN (SELECT value FROM json_each(?1))
+         ORDER BY name",
+    )?;
+    let mut by_todo: HashMap<i64, Vec<Tag>> = HashMap::new();
+    let rows = stmt.query_map([json_list(&ids)], |row| {
+        let tag = Tag {
+            id: row.get(1)?,
+            name: row.get(2)?,
+            color: row.get(3)?,
+        };
+        Ok((row.get(0)?, tag))
+    })?;
+    for row in rows {
+        let (todo_id, tag) = row?;
+        by_todo.entry(todo_id).or_default().push(tag);
+    }
+    for todo in todos {
+        todo.tags = by_todo.remove(&todo.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Strips the optional `#` and checks that a name is usable as a tag.
+pub fn normalize_name(name: &str) -> Result<String> {
+    let trimmed = name.trim();
+    let bare = trimmed.strip_prefix('#').unwrap_or(trimmed);
+    if bare.is_empty() || bare.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(Error::InvalidTagName(name.to_owned()));
+    }
+    Ok(bare.to_owned())
+}
+
+/// Normalizes names and drops case-insensitive duplicates.
+pub(super) fn normalize_names(names: &[String]) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let name = normalize_name(name)?;
+        if !normalized.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            normalized.push(name);
+        }
+    }
+    Ok(normalized)
+}
+
+pub(super) fn parse_color(color: &str) -> Result<String> {
+    let color = color.trim();
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(color.to_ascii_lowercase())
+        }
+        _ => Err(Error::InvalidColor(color.to_owned())),
+    }
+}
+
+fn find_tag(conn: &Connection, name: &str) -> Result<Tag> {
+    conn.query_row(
+        "SELECT id, name, color FROM tags WHERE name = ?1",
+        [name],
+        |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+            })
+        },
+    )
+    .optional()?
+    .ok_or_else(|| Error::TagNotFound(name.to_owned()))
+}
+
+fn ensure_tag(conn: &Connection, name: &str) -> Result<Tag> {
+    conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [name])?;
+    find_tag(conn, name)
+}
+
+/// Bumps `updated_at` of a todo whose tags are about to change, failing if
+/// it does not exist.
+fn touch(conn: &Connection, id: i64) -> Result<()> {
+    let changed = conn.execute(
+        "UPDATE todos SET updated_at = ?2 WHERE id = ?1",
+        params![id, now()],
+    )?;
+    if changed == 0 {
+        return Err(Error::NotFound(id));
+    }
+    Ok(())
+}
+
+/// Values for `json_each`, which stands in for binding a list.
+pub(super) fn json_list<T: Serialize>(values: &[T]) -> String {
+    serde_json::to_string(values).expect("lists of strings and integers serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::testing::{add, store, todo};
+    use crate::store::NewTodo;
+
+    const DAY: &str = "2026-10-20";
+
+    fn tagged(store: &Store, text: &str, date: &str, tags: &[&str]) -> Todo {
+        let todo = NewTodo {
+            tags: tags.iter().map(|&tag| tag.to_owned()).collect(),
+            ..todo(text, date)
+        };
+        add(store, todo)
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    fn tag_names(todo: &Todo) -> Vec<&str> {
+        todo.tags.iter().map(|tag| tag.name.as_str()).collect()
+    }
+
+    fn texts(todos: &[Todo]) -> Vec<&str> {
+        todos.iter().map(|todo| todo.text.as_str()).collect()
+    }
+
+    fn usage(store: &Store) -> Vec<(String, usize)> {
+        store
+            .tags()
+            .unwrap()
+            .into_iter()
+            .map(|usage| (usage.tag.name, usage.todo_count))
+            .collect()
+    }
+
+    #[test]
+    fn names_ignore_case_and_hashes() {
+        let store = store();
+        let todo = tagged(&store, "Plan trip", DAY, &["#Work", "@phone"]);
+        let todo = store
+            .tag_todo(todo.id, &names(&["work", "WORK", "#@Phone"]))
+            .unwrap();
+        assert_eq!(tag_names(&todo), ["@phone", "Work"]);
+        assert_eq!(
+            usage(&store),
+            [("@phone".to_owned(), 1), ("Work".to_owned(), 1)]
+        );
+        let todo = store.untag_todo(todo.id, &names(&["#work"])).unwrap();
+        assert_eq!(tag_names(&todo), ["@phone"]);
+        assert_eq!(usage(&store)[1], ("Work".to_owned(), 0));
+
+        for name in ["", "#", "two words", "a,b"] {
+            assert!(matches!(
+                normalize_name(name),
+                Err(Error::InvalidTagName(_))
+            ));
+        }
+        assert!(matches!(
+            store.tag_todo(999, &names(&["work"])),
+            Err(Error::NotFound(999))
+        ));
+    }
+
+    #[test]
+    fn finds_todos_by_all_or_any_tags() {
+        let store = store();
+        tagged(&store, "Fix roof", DAY, &["home", "urgent"]);
+        tagged(&store, "Water plants", DAY, &["Home"]);
+        tagged(&store, "File taxes", "2026-10-21", &["urgent"]);
+        tagged(&store, "Read", DAY, &[]);
+        let find =
+            |tags: &[&str], mode, date| store.todos_by_tags(&names(tags), mode, date).unwrap();
+
+        assert_eq!(
+            texts(&find(&["HOME", "#Urgent"], TagMatch::All, None)),
+            ["Fix roof"]
+        );
+        let mut any = find(&["home", "urgent"], TagMatch::Any, None);
+        any.sort_by(|a, b| a.text.cmp(&b.text));
+        assert_eq!(texts(&any), ["File taxes", "Fix roof", "Water plants"]);
+        assert_eq!(
+            texts(&find(&["home", "urgent"], TagMatch::Any, Some(DAY))),
+            ["Fix roof", "Water plants"]
+        );
+        // The same tag twice still only needs to be there once.
+        assert_eq!(
+            texts(&find(&["home", "HOME"], TagMatch::All, None)),
+            ["Fix roof", "Water plants"]
+        );
+        assert!(find(&["home", "garden"], TagMatch::All, None).is_empty());
+        assert!(find(&["garden"], TagMatch::Any, None).is_empty());
+    }
+
+    #[test]
+    fn merging_moves_todos_onto_the_target() {
+        let store = store();
+        let roof = tagged(&store, "Fix roof", DAY, &["home", "house"]);
+        let plants = tagged(&store, "Water plants", DAY, &["house"]);
+        let rent = tagged(&store, "Pay rent", DAY, &["flat"]);
+
+        let home = store
+            .merge_tags(&names(&["House", "flat"]), "#home")
+            .unwrap();
+        assert_eq!(home.name, "home");
+        for todo in [&roof, &plants, &rent] {
+            assert_eq!(tag_names(&store.get_todo(todo.id).unwrap()), ["home"]);
+        }
+        assert_eq!(usage(&store), [("home".to_owned(), 3)]);
+
+        // Merging into a tag that does not exist yet creates it.
+        let household = store.merge_tags(&names(&["home"]), "household").unwrap();
+        assert_eq!(usage(&store), [(household.name, 3)]);
+        assert!(matches!(
+            store.merge_tags(&names(&["garden"]), "household"),
+            Err(Error::TagNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn renames_tags() {
+        let store = store();
+        let roof = tagged(&store, "Fix roof", DAY, &["home"]);
+        tagged(&store, "File taxes", DAY, &["urgent"]);
+
+        let tag = store.rename_tag("#HOME", "house").unwrap();
+        assert_eq!(tag.name, "house");
+        assert_eq!(tag_names(&store.get_todo(roof.id).unwrap()), ["house"]);
+        assert_eq!(store.rename_tag("house", "House").unwrap().name, "House");
+        assert!(matches!(
+            store.rename_tag("house", "Urgent"),
+            Err(Error::TagExists(_))
+        ));
+        assert!(matches!(
+            store.rename_tag("garden", "yard"),
+            Err(Error::TagNotFound(_))
+        ));
+        assert_eq!(
+            usage(&store),
+            [("House".to_owned(), 1), ("urgent".to_owned(), 1)]
+        );
+    }
+
+    #[test]
+    fn colors_and_deletes_tags() {
+        let store = store();
+        let roof = tagged(&store, "Fix roof", DAY, &["home"]);
+        let tag = store.set_tag_color("home", Some("#A0B1C2")).unwrap();
+        assert_eq!(tag.color.as_deref(), Some("#a0b1c2"));
+        assert_eq!(store.get_todo(roof.id).unwrap().tags, [tag]);
+        assert!(matches!(
+            store.set_tag_color("home", Some("red")),
+            Err(Error::InvalidColor(_))
+        ));
+        assert!(store.delete_tag("HOME").unwrap());
+        assert!(!store.delete_tag("home").unwrap());
+        assert!(store.get_todo(roof.id).unwrap().tags.is_empty());
+    }
+}
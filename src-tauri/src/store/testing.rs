@@ -0,0 +1,28 @@
+//! Fixtures shared by the unit tests of the store modules.
+
+use std::path::Path;
+
+use super::{NewTodo, Store, Todo};
+
+/// A fresh in-memory database.
+pub fn store() -> Store {
+    Store::open(Path::new(":memory:")).unwrap()
+}
+
+/// A fresh database file in `dir`, with its attachments folder next to it.
+pub fn store_in(dir: &Path) -> Store {
+    Store::open(&dir.join(crate::DB_FILE)).unwrap()
+}
+
+/// A todo with `text` on `date`, to fill in further with `..todo(..)`.
+pub fn todo(text: &str, date: &str) -> NewTodo {
+    NewTodo {
+        text: text.to_owned(),
+        date: Some(date.to_owned()),
+        ..NewTodo::default()
+    }
+}
+
+pub fn add(store: &Store, todo: NewTodo) -> Todo {
+    store.create_todo(&todo).unwrap()
+}
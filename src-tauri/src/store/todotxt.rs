@@ -0,0 +1,416 @@
+//! Import and export in the [todo.txt] format, one todo per line:
+//!
+//! ```text
+//! x 2026-10-18 2026-10-12 Renew passport +Admin @errands due:2026-10-20 t:2026-10-17
+//! (B) 2026-10-18 Water plants #home rec:+1w t:2026-10-18
+//! ```
+//!
+//! Completion, priorities `(A)` to `(D)`, creation and completion dates,
+//! the first `+project`, `@contexts` (tags starting with `@`) and `#tags`
+//! map onto todos. So do the extensions `t:` for the day a todo is listed
+//! under, `due:` for its deadline, `rec:` or `rrule:` for how it repeats and
+//! `pri:` for the priority of a completed todo. Anything else, unknown
+//! `key:value` extensions included, stays in the text and is written back
+//! out as it came in. Notes, subtasks and attachments have no todo.txt form.
+//!
+//! [todo.txt]: https://github.com/todotxt/todo.txt
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+
+use super::export::{Document, ImportReport, ImportStrategy, TodoRecord, FORMAT, VERSION};
+use super::quick_add::{is_name, name_key};
+use super::recurrence::Frequency;
+use super::{now, parse_date, timestamp, today, Priority, Result, Rule, Store};
+
+/// Time of day a deadline given as a bare date falls on.
+pub(super) const DUE_TIME: (u32, u32) = (23, 59);
+
+/// Priority letters, most urgent first.
+const LETTERS: [(char, Priority); 4] = [
+    ('A', Priority::Urgent),
+    ('B', Priority::High),
+    ('C', Priority::Medium),
+    ('D', Priority::Low),
+];
+
+impl Store {
+    /// Every todo as todo.txt lines, day by day in list order.
+    #[tracing::instrument(skip(self))]
+    pub fn export_todo_txt(&self) -> Result<String> {
+        let conn = self.conn();
+        let todos = super::export::export_todos(&conn)?;
+        Ok(todos
+            .iter()
+            .map(|todo| format_line(todo, &Local) + "\n")
+            .collect())
+    }
+
+    /// Adds the todos of a todo.txt file, or replaces everything with them.
+    /// Lines carry no uid, so merging adds every todo like appending does.
+    /// Todos without a `t:` day go on today, or on their completion day.
+    #[tracing::instrument(skip(self, text))]
+    pub fn import_todo_txt(&self, text: &str, strategy: ImportStrategy) -> Result<ImportReport> {
+        let today = parse_date(&today(&self.conn())?)?;
+        let projects = self.projects(true)?;
+        let mut todos: Vec<TodoRecord> = text
+            .lines()
+            .filter_map(|line| parse_line(line, today, &Local))
+            .collect();
+        for (position, todo) in (0..).zip(&mut todos) {
+            todo.sort_order = position;
+            // `+side-project` goes into "Side Project" when there is one.
+            if let Some(name) = &mut todo.project {
+                if let Some(found) = projects
+                    .iter()
+                    .find(|p| name_key(&p.project.name) == name_key(name))
+                {
+                    name.clone_from(&found.project.name);
+                }
+            }
+        }
+        let document = Document {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            exported_at: now(),
+            projects: Vec::new(),
+            tags: Vec::new(),
+            todos,
+            attachments: Vec::new(),
+        };
+        self.import_json(&document, strategy)
+    }
+}
+
+/// Reads one todo.txt line typed on `today`, or `None` for a blank one.
+/// Dates are local to `zone`.
+pub fn parse_line<Tz: TimeZone>(line: &str, today: NaiveDate, zone: &Tz) -> Option<TodoRecord> {
+    let mut words = line.split_whitespace().peekable();
+    words.peek()?;
+    let mut todo = TodoRecord::default();
+    let date = |word: &&str| NaiveDate::parse_from_str(word, "%Y-%m-%d").ok();
+
+    let mut completed_on = None;
+    let mut created_on = None;
+    if words.next_if_eq(&"x").is_some() {
+        todo.completed = true;
+        completed_on = words.next_if(|w| date(w).is_some()).as_ref().and_then(date);
+        if completed_on.is_some() {
+            created_on = words.next_if(|w| date(w).is_some()).as_ref().and_then(date);
+        }
+    } else {
+        if let Some(priority) = words.peek().and_then(|w| priority_of(w)) {
+            todo.priority = priority;
+            words.next();
+        }
+        created_on = words.next_if(|w| date(w).is_some()).as_ref().and_then(date);
+    }
+
+    let description: Vec<&str> = words.collect();
+    let mut text = Vec::new();
+    let mut day = None;
+    for &word in &description {
+        if let Some(name) = word.strip_prefix('+').filter(|n| is_name(n)) {
+            if todo.project.is_none() {
+                todo.project = Some(name.to_owned());
+                continue;
+            }
+        } else if word.starts_with('@') && is_name(&word[1..]) {
+            todo.tags.push(word.to_owned());
+            continue;
+        } else if let Some(name) = word.strip_prefix('#').filter(|n| is_name(n)) {
+            todo.tags.push(name.to_owned());
+            continue;
+        } else if let Some((key, value)) = word.split_once(':') {
+            let known = match key {
+                "t" => date(&value).map(|d| day = Some(d)).is_some(),
+                "due" => due_at(value, zone).map(|d| todo.due_at = Some(d)).is_some(),
+                "rec" => rec_rule(value)
+                    .map(|r| todo.recurrence = Some(r.to_string()))
+                    .is_some(),
+                "rrule" => value
+                    .parse::<Rule>()
+                    .map(|r| todo.recurrence = Some(r.to_string()))
+                    .is_ok(),
+                "pri" => value
+                    .chars()
+                    .next()
+                    .and_then(|c| priority_of(&format!("({c})")))
+                    .filter(|_| value.len() == 1)
+                    .map(|p| todo.priority = p)
+                    .is_some(),
+                _ => false,
+            };
+            if known {
+                continue;
+            }
+        }
+        text.push(word);
+    }
+    if text.is_empty() {
+        // Nothing but projects, tags and extensions: keep it as written.
+        todo = TodoRecord {
+            completed: todo.completed,
+            priority: todo.priority,
+            ..TodoRecord::default()
+        };
+        day = None;
+        text = description;
+    }
+    todo.text = text.join(" ");
+
+    todo.scheduled_date = day.or(completed_on).unwrap_or(today).to_string();
+    if todo.recurrence.is_some() {
+        todo.recurrence_start = Some(todo.scheduled_date.clone());
+    }
+    todo.created_at = created_on
+        .and_then(|d| start_of(d, zone))
+        .unwrap_or_default();
+    todo.completed_at = completed_on.and_then(|d| start_of(d, zone));
+    Some(todo)
+}
+
+/// Writes a todo as a todo.txt line with dates local to `zone`.
+pub fn format_line<Tz: TimeZone>(todo: &TodoRecord, zone: &Tz) -> String {
+    let local = |at: &str| {
+        DateTime::parse_from_rfc3339(at)
+            .ok()
+            .map(|at| at.with_timezone(zone).naive_local())
+    };
+    let letter = LETTERS
+        .iter()
+        .find(|(_, p)| *p == todo.priority)
+        .map(|(letter, _)| letter);
+    let mut words = Vec::new();
+    let completed_at = todo.completed_at.as_deref().and_then(local);
+    if todo.completed {
+        words.push("x".to_owned());
+        if let Some(at) = completed_at {
+            words.push(at.date().to_string());
+        }
+    } else if let Some(letter) = letter {
+        words.push(format!("({letter})"));
+    }
+    // After `x`, a lone date is taken for the completion date.
+    if !todo.completed || completed_at.is_some() {
+        if let Some(at) = local(&todo.created_at) {
+            words.push(at.date().to_string());
+        }
+    }
+    words.push(todo.text.clone());
+    if let Some(project) = &todo.project {
+        words.push(format!(
+            "+{}",
+            project.split_whitespace().collect::<Vec<_>>().join("-")
+        ));
+    }
+    for tag in &todo.tags {
+        if tag.starts_with('@') {
+            words.push(tag.clone());
+        } else {
+            words.push(format!("#{tag}"));
+        }
+    }
+    if let Some(due) = todo.due_at.as_deref().and_then(local) {
+        if (due.hour(), due.minute()) == DUE_TIME {
+            words.push(format!("due:{}", due.date()));
+        } else {
+            words.push(format!("due:{}", due.format("%Y-%m-%dT%H:%M")));
+        }
+    }
+    words.push(format!("t:{}", todo.scheduled_date));
+    if let Some(rule) = todo.recurrence.as_deref().and_then(|r| r.parse().ok()) {
+        words.push(rec_value(&rule));
+    }
+    if let (true, Some(letter)) = (todo.completed, letter) {
+        words.push(format!("pri:{letter}"));
+    }
+    words.join(" ")
+}
+
+/// The priority of a `(A)` to `(Z)` marker. Letters after `D` count as
+/// low priority.
+fn priority_of(word: &str) -> Option<Priority> {
+    let letter = word.strip_prefix('(')?.strip_suffix(')')?;
+    let [letter] = letter.as_bytes() else {
+        return None;
+    };
+    match *letter {
+        b'A'..=b'D' => Some(LETTERS[usize::from(letter - b'A')].1),
+        b'E'..=b'Z' => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+/// A `due:` value, either a date or a local `YYYY-MM-DDTHH:MM`, as a UTC
+/// timestamp.
+fn due_at<Tz: TimeZone>(value: &str, zone: &Tz) -> Option<String> {
+    let (hour, minute) = DUE_TIME;
+    let local = match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => date.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?),
+        Err(_) => chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").ok()?,
+    };
+    let at = zone.from_local_datetime(&local).earliest()?;
+    Some(timestamp(at.with_timezone(&Utc)))
+}
+
+/// Local midnight starting `date`, as a UTC timestamp.
+fn start_of<Tz: TimeZone>(date: NaiveDate, zone: &Tz) -> Option<String> {
+    let at = zone
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()?;
+    Some(timestamp(at.with_timezone(&Utc)))
+}
+
+/// A `rec:` value such as `1w`, `+2m` or `d`. With `+` the todo repeats on
+/// a fixed schedule, otherwise that long after it is completed.
+fn rec_rule(value: &str) -> Option<Rule> {
+    let (fixed, value) = match value.strip_prefix('+') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let unit = value.chars().last()?;
+    let count = &value[..value.len() - unit.len_utf8()];
+    let interval = if count.is_empty() {
+        1
+    } else {
+        count.parse().ok()?
+    };
+    if interval == 0 {
+        return None;
+    }
+    let frequency = match unit {
+        'd' => Frequency::Daily,
+        'w' => Frequency::Weekly,
+        'm' => Frequency::Monthly,
+        'y' => Frequency::Yearly,
+        _ => return None,
+    };
+    Some(Rule {
+        frequency,
+        interval,
+        by_day: Vec::new(),
+        by_month_day: None,
+        count: None,
+        until: None,
+        from_completion: !fixed,
+    })
+}
+
+/// `rec:` for rules it can express, `rrule:` for the rest.
+fn rec_value(rule: &Rule) -> String {
+    let simple = rule.by_day.is_empty()
+        && rule.by_month_day.is_none()
+        && rule.count.is_none()
+        && rule.until.is_none();
+    if !simple {
+        return format!("rrule:{rule}");
+    }
+    let unit = match rule.frequency {
+        Frequency::Daily => 'd',
+        Frequency::Weekly => 'w',
+        Frequency::Monthly => 'm',
+        Frequency::Yearly => 'y',
+    };
+    let fixed = if rule.from_completion { "" } else { "+" };
+    format!("rec:{fixed}{}{unit}", rule.interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn parse(line: &str) -> TodoRecord {
+        parse_line(line, date("2026-10-18"), &Berlin).unwrap()
+    }
+
+    #[test]
+    fn fields_map_onto_todos() {
+        let todo = parse(
+            "(B) 2026-10-12 Renew passport +Admin @errands #paperwork due:2026-10-20 \
+             t:2026-10-19 rec:+1y",
+        );
+        assert_eq!(todo.text, "Renew passport");
+        assert_eq!(todo.priority, Priority::High);
+        assert_eq!(todo.created_at, "2026-10-11T22:00:00.000Z");
+        assert_eq!(todo.project.as_deref(), Some("Admin"));
+        assert_eq!(todo.tags, ["@errands", "paperwork"]);
+        assert_eq!(todo.due_at.as_deref(), Some("2026-10-20T21:59:00.000Z"));
+        assert_eq!(todo.scheduled_date, "2026-10-19");
+        assert_eq!(todo.recurrence.as_deref(), Some("FREQ=YEARLY"));
+        assert!(!todo.completed);
+    }
+
+    #[test]
+    fn completed_lines_keep_both_dates() {
+        let todo = parse("x 2026-10-18 2026-10-12 File taxes pri:A");
+        assert!(todo.completed);
+        assert_eq!(todo.priority, Priority::Urgent);
+        assert_eq!(
+            todo.completed_at.as_deref(),
+            Some("2026-10-17T22:00:00.000Z")
+        );
+        assert_eq!(todo.created_at, "2026-10-11T22:00:00.000Z");
+        // Listed on the day it was done when no `t:` says otherwise.
+        assert_eq!(todo.scheduled_date, "2026-10-18");
+
+        let todo = parse("x 2026-10-16 Call mom");
+        assert_eq!(
+            todo.completed_at.as_deref(),
+            Some("2026-10-15T22:00:00.000Z")
+        );
+        assert_eq!(todo.created_at, "");
+    }
+
+    #[test]
+    fn unknown_words_stay_in_the_text() {
+        let todo = parse("Read https://example.com +Reading +Later id:42 due:soon x (A)");
+        assert_eq!(
+            todo.text,
+            "Read https://example.com +Later id:42 due:soon x (A)"
+        );
+        assert_eq!(todo.project.as_deref(), Some("Reading"));
+        assert_eq!(todo.due_at, None);
+        assert_eq!(todo.priority, Priority::None);
+
+        let todo = parse("+Errands @phone");
+        assert_eq!(todo.text, "+Errands @phone");
+        assert_eq!(todo.project, None);
+        assert!(todo.tags.is_empty());
+        assert!(parse_line("   ", date("2026-10-18"), &Berlin).is_none());
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let lines = [
+            "(A) 2026-10-12 Renew passport +Admin @errands #paperwork due:2026-10-20 t:2026-10-19",
+            "x 2026-10-18 2026-10-12 File taxes id:42 t:2026-10-18 pri:C",
+            "2026-10-18 Stand-up due:2026-10-19T09:30 t:2026-10-19 rec:+1w",
+            "2026-10-18 Water plants t:2026-10-18 rec:3d",
+            "2026-10-18 Pay rent t:2026-10-31 rrule:FREQ=MONTHLY;BYMONTHDAY=-1",
+        ];
+        for line in lines {
+            assert_eq!(format_line(&parse(line), &Berlin), line);
+        }
+    }
+
+    #[test]
+    fn rec_values() {
+        assert_eq!(
+            rec_rule("d").unwrap().to_string(),
+            "FREQ=DAILY;X-FROM=COMPLETION"
+        );
+        assert_eq!(
+            rec_rule("+2w").unwrap().to_string(),
+            "FREQ=WEEKLY;INTERVAL=2"
+        );
+        assert_eq!(rec_rule("0m"), None);
+        assert_eq!(rec_rule("5b"), None);
+        assert_eq!(rec_value(&rec_rule("+1y").unwrap()), "rec:+1y");
+    }
+}
@@ -0,0 +1,46 @@
+//! Notices writes made to the database by other processes, such as the CLI,
+//! and the start of a new day, and tells the windows to reload.
+
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Runtime};
+use tracing::{info, warn};
+
+use crate::events;
+use crate::store::Store;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn spawn<R: Runtime>(app: AppHandle<R>, store: Store) {
+    thread::spawn(move || {
+        let mut last = None;
+        let mut today = store.today().ok();
+        loop {
+            match store.data_version() {
+                Ok(version) => {
+                    if last.is_some_and(|last| last != version) {
+                        events::todos_changed(&app);
+                    }
+                    last = Some(version);
+                }
+                Err(e) => warn!(error = %e, "failed to poll database version"),
+            }
+            match store.today() {
+                Ok(day) if today.as_ref() != Some(&day) => {
+                    info!(%day, "new day started");
+                    if let Err(e) = store.carry_over() {
+                        warn!(error = %e, "failed to carry over unfinished todos");
+                    }
+                    // Windows showing "today" have to switch days even when
+                    // nothing was carried over.
+                    events::todos_changed(&app);
+                    today = Some(day);
+                }
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "failed to determine the current day"),
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,188 @@
+//! System tray icon: today's remaining count and quick actions, so the app
+//! stays reachable while the main window is hidden.
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Listener, Manager, Runtime};
+use tracing::warn;
+
+use crate::store::{FirstTodo, Store};
+use crate::{events, windows};
+
+const TRAY_ID: &str = "main";
+
+const SHOW: &str = "show";
+const COMPACT: &str = "compact";
+const QUICK_ADD: &str = "quick-add";
+const COMPLETE_NEXT: &str = "complete-next";
+const QUIT: &str = "quit";
+
+/// Longest task text shown in the "Complete" menu item.
+const MAX_LABEL_CHARS: usize = 40;
+
+pub fn create<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let summary = summary(app);
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(&summary))
+        .menu(&menu(app, &summary)?)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    for event in [
+        events::TODO_ADDED,
+        events::TODO_UPDATED,
+        events::TODO_DELETED,
+        events::TODOS_REORDERED,
+        events::TODOS_CHANGED,
+    ] {
+        let handle = app.clone();
+        app.listen_any(event, move |_| refresh(&handle));
+    }
+    Ok(())
+}
+
+/// Rebuilds the menu and tooltip from the current counts.
+pub fn refresh<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let summary = summary(app);
+    let result = menu(app, &summary).and_then(|menu| {
+        tray.set_menu(Some(menu))?;
+        tray.set_tooltip(Some(tooltip(&summary)))
+    });
+    if let Err(e) = result {
+        warn!(error = %e, "failed to refresh tray");
+    }
+}
+
+fn summary<R: Runtime>(app: &AppHandle<R>) -> FirstTodo {
+    app.state::<Store>().first_todo().unwrap_or_else(|e| {
+        warn!(error = %e, "failed to count today's todos");
+        FirstTodo::default()
+    })
+}
+
+fn tooltip(summary: &FirstTodo) -> String {
+    match summary.active_count {
+        0 => "rtodo: all done for today".to_owned(),
+        1 => "rtodo: 1 task left today".to_owned(),
+        n => format!("rtodo: {n} tasks left today"),
+    }
+}
+
+fn menu<R: Runtime>(app: &AppHandle<R>, summary: &FirstTodo) -> tauri::Result<Menu<R>> {
+    let count = MenuItem::new(app, tooltip(summary), false, None::<&str>)?;
+    let complete_label = match &summary.todo {
+        Some(todo) => format!("Complete \"{}\"", truncate(&todo.text)),
+        None => "Complete next task".to_owned(),
+    };
+    let compact_label = if is_compact(app) {
+        "Leave compact mode"
+    } else {
+        "Compact mode"
+    };
+
+    Menu::with_items(
+        app,
+        &[
+            &count,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, SHOW, "Show rtodo", true, None::<&str>)?,
+            &MenuItem::with_id(app, COMPACT, compact_label, true, None::<&str>)?,
+            &MenuItem::with_id(app, QUICK_ADD, "Quick add…", true, None::<&str>)?,
+            &MenuItem::with_id(
+                app,
+                COMPLETE_NEXT,
+                complete_label,
+                summary.todo.is_some(),
+                None::<&str>,
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, QUIT, "Quit", true, None::<&str>)?,
+        ],
+    )
+}
+
+fn on_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let result = match event.id().as_ref() {
+        SHOW => windows::show_main(app),
+        COMPACT => {
+            let result = if is_compact(app) {
+                windows::hide_compact(app)
+            } else {
+                windows::show_compact(app)
+            };
+            refresh(app);
+            result
+        }
+        QUICK_ADD => windows::show_quick_add(app),
+        COMPLETE_NEXT => {
+            complete_next(app);
+            Ok(())
+        }
+        QUIT => {
+            app.exit(0);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        warn!(error = %e, item = ?event.id(), "tray action failed");
+    }
+}
+
+fn complete_next<R: Runtime>(app: &AppHandle<R>) {
+    let store = app.state::<Store>();
+    let result = store.first_todo().and_then(|first| match first.todo {
+        Some(todo) => store.set_completed(todo.id, true).map(Some),
+        None => Ok(None),
+    });
+    match result {
+        Ok(Some(todo)) => {
+            events::todo_updated(app, &todo, "toggled");
+            if todo.recurrence.is_some() {
+                // Its next instance was added too.
+                events::todos_changed(app);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!(error = %e, "failed to complete next todo"),
+    }
+}
+
+fn show_main<R: Runtime>(app: &AppHandle<R>) {
+    if let Err(e) = windows::show_main(app) {
+        warn!(error = %e, "failed to show main window");
+    }
+}
+
+fn is_compact<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.get_webview_window(windows::COMPACT)
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Shortens task text for a menu label; `&` would otherwise be taken as a
+/// mnemonic marker.
+fn truncate(text: &str) -> String {
+    let text = match text.char_indices().nth(MAX_LABEL_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_owned(),
+    };
+    text.replace('&', "&&")
+}
@@ -0,0 +1,100 @@
+//! Window management shared by everything that runs outside the webview:
+//! single-instance forwarding, deep links, the tray and global shortcuts.
+
+use tauri::window::Color;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+pub const MAIN: &str = "main";
+pub const COMPACT: &str = "compact";
+pub const QUICK_ADD: &str = "quick-add";
+
+const COMPACT_WIDTH: f64 = 350.0;
+const COMPACT_HEIGHT: f64 = 50.0;
+const QUICK_ADD_WIDTH: f64 = 480.0;
+const QUICK_ADD_HEIGHT: f64 = 56.0;
+const BACKGROUND: Color = Color(0x1e, 0x29, 0x3b, 0xff);
+
+/// Shows, restores and focuses the main window.
+pub fn show_main<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(MAIN) {
+        window.show()?;
+        window.unminimize()?;
+        window.set_focus()?;
+    }
+    Ok(())
+}
+
+/// Switches to compact mode: shows the compact window, creating it like
+/// `openCompactMode` in App.tsx does, and hides the main window.
+pub fn show_compact<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let compact = match app.get_webview_window(COMPACT) {
+        Some(window) => window,
+        None => build_compact(app)?,
+    };
+    compact.show()?;
+    compact.unminimize()?;
+    compact.set_focus()?;
+    if let Some(main) = app.get_webview_window(MAIN) {
+        main.hide()?;
+    }
+    Ok(())
+}
+
+/// Leaves compact mode, bringing the main window back.
+pub fn hide_compact<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    if let Some(compact) = app.get_webview_window(COMPACT) {
+        compact.hide()?;
+    }
+    show_main(app)
+}
+
+/// Opens the quick-add popup, or dismisses it when it is already open.
+pub fn toggle_quick_add<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(QUICK_ADD) {
+        return window.close();
+    }
+    show_quick_add(app)
+}
+
+/// Opens the quick-add popup over whatever has focus. The webview closes it
+/// again after submitting or on Escape.
+pub fn show_quick_add<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(QUICK_ADD) {
+        return window.set_focus();
+    }
+    WebviewWindowBuilder::new(app, QUICK_ADD, WebviewUrl::App("/quick-add".into()))
+        .title("Quick add")
+        .inner_size(QUICK_ADD_WIDTH, QUICK_ADD_HEIGHT)
+        .center()
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(true)
+        .background_color(BACKGROUND)
+        .build()?
+        .set_focus()
+}
+
+fn build_compact<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<WebviewWindow<R>> {
+    // Top right corner of the primary monitor, in logical pixels.
+    let (width, height) = match app.primary_monitor()? {
+        Some(monitor) => {
+            let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+            (size.width, size.height)
+        }
+        None => (1920.0, 1080.0),
+    };
+    let (x, y) = (width - width / 10.0 - COMPACT_WIDTH, height / 8.0);
+
+    WebviewWindowBuilder::new(app, COMPACT, WebviewUrl::App("/compact".into()))
+        .inner_size(COMPACT_WIDTH, COMPACT_HEIGHT)
+        .position(x, y)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        // macOS needs the dock entry to keep the window reachable.
+        .skip_taskbar(!cfg!(target_os = "macos"))
+        .background_color(BACKGROUND)
+        .build()
+}
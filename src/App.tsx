@@ -3,7 +3,7 @@ import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { currentMonitor, getCurrentWindow } from "@tauri-apps/api/window";
 import { listen } from "./utils/events";
 import { type } from "@tauri-apps/plugin-os";
-import { database } from "./utils/database";
+import { database, type QuickAdd } from "./utils/database";
 import { store } from "./utils/store";
 import {
   DndContext,
@@ -27,11 +27,13 @@ import SplashScreen from "./components/SplashScreen";
 function App() {
   const [state, setState] = useState(() => store.getState());
   const [inputValue, setInputValue] = useState("");
+  const [preview, setPreview] = useState<QuickAdd | null>(null);
   const [filter, setFilter] = useState<"all" | "active" | "completed">("all");
   const [showDatePicker, setShowDatePicker] = useState(false);
   const [historicalDates, setHistoricalDates] = useState<string[]>([]);
   const [futureDates, setFutureDates] = useState<string[]>([]);
   const [selectedDate, setSelectedDate] = useState<string | null>(null);
+  const [today, setToday] = useState("");
   const [isViewingHistorical, setIsViewingHistorical] = useState(false);
   const [isViewingFuture, setIsViewingFuture] = useState(false);
   const [isLoading, setIsLoading] = useState(true);
@@ -64,12 +66,14 @@ function App() {
       await store.loadTodos(date);
 
       // Also refresh historical and future dates
-      const [historical, future] = await Promise.all([
+      const [historical, future, currentDay] = await Promise.all([
         database.getHistoricalDates(),
         database.getFutureDates(),
+        database.getToday(),
       ]);
       setHistoricalDates(historical);
       setFutureDates(future);
+      setToday(currentDay);
     } catch (error) {
       console.error("Failed to sync data:", error);
     }
@@ -89,16 +93,23 @@ function App() {
     const initDatabase = async () => {
       try {
         await database.init();
-        // Load today's todos only
-        await store.loadTodos();
+        // Load today's todos only, unless launched with `--date`
+        const pendingDate = await database.takePendingDate();
+        if (pendingDate) {
+          await handleDateSelect(pendingDate);
+        } else {
+          await store.loadTodos();
+        }
 
         // Load historical and future dates
-        const [historical, future] = await Promise.all([
+        const [historical, future, currentDay] = await Promise.all([
           database.getHistoricalDates(),
           database.getFutureDates(),
+          database.getToday(),
         ]);
         setHistoricalDates(historical);
         setFutureDates(future);
+        setToday(currentDay);
       } catch (error) {
         console.error("Failed to initialize database:", error);
       } finally {
@@ -142,11 +153,39 @@ function App() {
           syncData();
         });
 
+        // Listen for tag renames, merges and color changes
+        const unlistenTags = await listen("tags-changed", (_event) => {
+          syncData();
+        });
+
+        // Listen for project edits and todos moving between projects
+        const unlistenProjects = await listen("projects-changed", (_event) => {
+          syncData();
+        });
+
+        // Listen for changes made outside the app, e.g. from the CLI
+        const unlistenExternal = await listen("todos-changed", (_event) => {
+          syncData();
+        });
+
+        // Listen for `rtodo --date` and `rtodo://open` links; null means today
+        const unlistenOpenDate = await listen<{ date: string | null }>("open-date", (event) => {
+          if (event.payload.date) {
+            handleDateSelect(event.payload.date);
+          } else {
+            returnToToday();
+          }
+        });
+
         unlistenFunctions = [
           unlistenToggle,
           unlistenAdd,
           unlistenDelete,
           unlistenReorder,
+          unlistenExternal,
+          unlistenOpenDate,
+          unlistenTags,
+          unlistenProjects,
         ];
       } catch (error) {
         console.error("App: Failed to setup event listeners:", error);
@@ -188,6 +227,33 @@ function App() {
     setupCloseListener();
   }, []);
 
+  // Attach files dropped onto a todo
+  useEffect(() => {
+    if (!("attachDroppedFiles" in database) || isViewingHistorical) return;
+    const service = database;
+    let unlisten: (() => void) | undefined;
+
+    listen<{ x: number; y: number }>("files-dropped", async (event) => {
+      // The position is in physical pixels.
+      const scale = window.devicePixelRatio || 1;
+      const target = document
+        .elementFromPoint(event.payload.x / scale, event.payload.y / scale)
+        ?.closest<HTMLElement>("[data-todo-id]");
+      if (!target) return;
+      try {
+        await service.attachDroppedFiles(Number(target.dataset.todoId));
+      } catch (error) {
+        console.error("Failed to attach files:", error);
+      }
+    })
+      .then((fn) => {
+        unlisten = fn;
+      })
+      .catch((error) => console.error("Failed to setup file drop listener:", error));
+
+    return () => unlisten?.();
+  }, [isViewingHistorical]);
+
   // Disable context menu in production
   useEffect(() => {
     const handleContextMenu = (e: MouseEvent) => {
@@ -264,12 +330,14 @@ function App() {
 
   const openDatePicker = async () => {
     try {
-      const [historical, future] = await Promise.all([
+      const [historical, future, currentDay] = await Promise.all([
         database.getHistoricalDates(),
         database.getFutureDates(),
+        database.getToday(),
       ]);
       setHistoricalDates(historical);
       setFutureDates(future);
+      setToday(currentDay);
       setShowDatePicker(true);
     } catch (error) {
       console.error("Failed to get dates:", error);
@@ -278,7 +346,8 @@ function App() {
 
   const handleDateSelect = async (date: string) => {
     try {
-      const today = new Date().toISOString().split("T")[0];
+      const today = await database.getToday();
+      setToday(today);
 
       if (date === today) {
         // If selecting today, return to today view
@@ -317,6 +386,29 @@ function App() {
     }
   };
 
+  // Show what quick-add will make of the input while typing.
+  useEffect(() => {
+    const text = inputValue.trim();
+    if (!text || isViewingHistorical) {
+      setPreview(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      try {
+        const targetDate = isViewingFuture && selectedDate ? selectedDate : undefined;
+        const parsed = await database.parseQuickAdd(inputValue, targetDate);
+        if (!cancelled) setPreview(parsed.spans.length > 0 ? parsed : null);
+      } catch (error) {
+        console.error("Failed to parse quick-add input:", error);
+      }
+    }, 150);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [inputValue, isViewingHistorical, isViewingFuture, selectedDate]);
+
   // Override addTodo to prevent adding todos when viewing historical data
   const handleAddTodo = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -491,6 +583,9 @@ function App() {
               +
             </button>
           </div>
+          {preview && (
+            <QuickAddPreview input={inputValue} parsed={preview} />
+          )}
         </form>
 
         {/* Stats */}
@@ -596,11 +691,42 @@ function App() {
           selectedDate={selectedDate}
           onDateSelect={handleDateSelect}
           onClose={() => setShowDatePicker(false)}
-          currentDate={new Date().toISOString().split("T")[0]}
+          currentDate={today}
         />
       )}
     </div>
   );
 }
 
+const SPAN_COLORS: Record<QuickAdd["spans"][number]["kind"], string> = {
+  date: "text-blue-300",
+  time: "text-blue-300",
+  tag: "text-green-300",
+  priority: "text-red-300",
+  recurrence: "text-purple-300",
+  project: "text-yellow-300",
+};
+
+// The add box input with the parts quick-add recognized highlighted.
+function QuickAddPreview({ input, parsed }: { input: string; parsed: QuickAdd }) {
+  const parts: React.ReactNode[] = [];
+  let pos = 0;
+  for (const span of parsed.spans) {
+    if (span.start < pos || span.end > input.length) continue;
+    parts.push(input.slice(pos, span.start));
+    parts.push(
+      <span key={span.start} className={`font-medium ${SPAN_COLORS[span.kind]}`}>
+        {input.slice(span.start, span.end)}
+      </span>,
+    );
+    pos = span.end;
+  }
+  parts.push(input.slice(pos));
+  return (
+    <div className="mt-1.5 px-1 text-xs text-gray-400 truncate" data-testid="quick-add-preview">
+      {parts}
+    </div>
+  );
+}
+
 export default App;
@@ -2,36 +2,34 @@ import { useState, useEffect } from "react";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { listen } from "./utils/events";
-import { Todo, database } from "./utils/database";
-import { store } from "./utils/store";
+import { FirstTodo, database } from "./utils/database";
 
 function CompactApp() {
-  const [state, setState] = useState(() => store.getState());
-  const [firstTodo, setFirstTodo] = useState<Todo | null>(() =>
-    store.getFirstIncompleteTodo()
-  );
+  const [first, setFirst] = useState<FirstTodo>({ todo: null, activeCount: 0 });
+  const firstTodo = first.todo;
+
+  // The compact capability only grants `first_todo` and `toggle_todo`, so
+  // this window reads its summary directly instead of going through the store.
+  const refresh = async () => {
+    try {
+      setFirst(await database.getFirstTodo());
+    } catch (error) {
+      console.error("Failed to load first todo in CompactApp:", error);
+    }
+  };
 
-  // Initialize database and load todos on mount
+  // Initialize database and load the first todo on mount
   useEffect(() => {
     const initDatabase = async () => {
       try {
         await database.init();
-        await store.loadTodos();
+        await refresh();
       } catch (error) {
         console.error("Failed to initialize database in CompactApp:", error);
       }
     };
 
     initDatabase();
-
-    // Subscribe to store changes
-    const unsubscribe = store.subscribe(() => {
-      const newState = store.getState();
-      setState(newState);
-      setFirstTodo(store.getFirstIncompleteTodo());
-    });
-
-    return unsubscribe;
   }, []);
 
   // Set up event listeners for real-time updates
@@ -42,22 +40,27 @@ function CompactApp() {
       try {
         // Listen for todo updates
         const unlistenToggle = await listen("todo-updated", (_event) => {
-          store.loadTodos();
+          refresh();
         });
 
         // Listen for new todos
         const unlistenAdd = await listen("todo-added", (_event) => {
-          store.loadTodos();
+          refresh();
         });
 
         // Listen for todo deletions
         const unlistenDelete = await listen("todo-deleted", (_event) => {
-          store.loadTodos();
+          refresh();
         });
 
         // Listen for todo reordering
         const unlistenReorder = await listen("todos-reordered", (_event) => {
-          store.loadTodos();
+          refresh();
+        });
+
+        // Listen for changes made outside the app, e.g. from the CLI
+        const unlistenExternal = await listen("todos-changed", (_event) => {
+          refresh();
         });
 
         unlistenFunctions = [
@@ -65,6 +68,7 @@ function CompactApp() {
           unlistenAdd,
           unlistenDelete,
           unlistenReorder,
+          unlistenExternal,
         ];
       } catch (error) {
         console.error("CompactApp: Failed to setup event listeners:", error);
@@ -82,7 +86,8 @@ function CompactApp() {
   const toggleTodo = async (id: number, event: React.MouseEvent) => {
     event.stopPropagation(); // Prevent opening main window when clicking checkbox
     try {
-      await store.toggleTodo(id);
+      await database.toggleTodo(id);
+      await refresh();
     } catch (error) {
       console.error("Failed to toggle todo in CompactApp:", error);
     }
@@ -165,13 +170,13 @@ function CompactApp() {
 
         {/* 右侧控制区域 */}
         <div className="flex items-center gap-2" data-tauri-drag-region>
-          {state.todos.filter((todo) => !todo.completed).length > 1 && (
+          {first.activeCount > 1 && (
             <span
               data-tauri-drag-region
               className="bg-blue-500 text-white text-xs px-2 py-1 rounded-full"
               data-testid="compact-extra-count"
             >
-              +{state.todos.filter((todo) => !todo.completed).length - 1}
+              +{first.activeCount - 1}
             </span>
           )}
         </div>
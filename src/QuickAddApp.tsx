@@ -0,0 +1,72 @@
+import { useState, useEffect, useRef } from "react";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { database } from "./utils/database";
+
+// Popup opened by the global quick-add shortcut. Its capability only grants
+// `quick_add` and closing itself; the main window and compact window pick up
+// the new todo through the `todo-added` event.
+function QuickAddApp() {
+  const [text, setText] = useState("");
+  const [submitting, setSubmitting] = useState(false);
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  const dismiss = async () => {
+    try {
+      await getCurrentWindow().close();
+    } catch (error) {
+      console.error("Failed to close quick-add window:", error);
+    }
+  };
+
+  useEffect(() => {
+    database.init().catch((error) => {
+      console.error("Failed to initialize database in QuickAddApp:", error);
+    });
+    inputRef.current?.focus();
+
+    const handleKeyDown = (event: KeyboardEvent) => {
+      if (event.key === "Escape") {
+        dismiss();
+      }
+    };
+    window.addEventListener("keydown", handleKeyDown);
+    return () => window.removeEventListener("keydown", handleKeyDown);
+  }, []);
+
+  const handleSubmit = async (event: React.FormEvent) => {
+    event.preventDefault();
+    const value = text.trim();
+    if (!value || submitting) return;
+
+    setSubmitting(true);
+    try {
+      // No date: the backend files it under today unless the text names a day.
+      await database.quickAdd(value);
+      await dismiss();
+    } catch (error) {
+      console.error("Failed to add todo from quick-add:", error);
+      setSubmitting(false);
+    }
+  };
+
+  return (
+    <form
+      onSubmit={handleSubmit}
+      className="h-screen bg-gradient-to-r from-gray-900 to-slate-800 flex items-center px-4"
+      data-tauri-drag-region
+    >
+      <input
+        ref={inputRef}
+        type="text"
+        value={text}
+        onChange={(e) => setText(e.target.value)}
+        placeholder="Add a task for today…"
+        disabled={submitting}
+        className="flex-1 bg-transparent text-white text-base placeholder-gray-400 outline-none"
+        data-testid="quick-add-input"
+      />
+    </form>
+  );
+}
+
+export default QuickAddApp;
@@ -50,8 +50,7 @@ export default function DatePicker({ historicalDates, futureDates, selectedDate,
 
   const isFutureDate = (date: Date) => {
     const dateStr = formatDate(date);
-    const todayStr = formatDate(new Date());
-    return dateStr > todayStr;
+    return dateStr > currentDate;
   };
 
   const hasFutureTodos = (date: Date) => {
@@ -117,7 +116,7 @@ export default function DatePicker({ historicalDates, futureDates, selectedDate,
             const isHistorical = isHistoricalDate(day);
             const isCurrentDay = isToday(day);
             const isSelected = selectedDate === dateStr;
-            const isPast = day < today && !isCurrentDay;
+            const isPast = dateStr < currentDate;
             const isFuture = isFutureDate(day);
 
             return (
@@ -1,7 +1,14 @@
 import React, { memo } from 'react';
 import { useSortable } from '@dnd-kit/sortable';
 import { CSS } from '@dnd-kit/utilities';
-import { Todo } from '../utils/database';
+import { Priority, Todo } from '../utils/database';
+
+const PRIORITY_STYLES: Record<Exclude<Priority, 'none'>, string> = {
+  low: 'bg-slate-700 text-gray-300',
+  medium: 'bg-sky-800 text-sky-100',
+  high: 'bg-amber-700 text-amber-50',
+  urgent: 'bg-red-700 text-red-50',
+};
 
 interface DraggableTodoProps {
   todo: Todo;
@@ -33,6 +40,7 @@ function DraggableTodo({ todo, onToggle, onDelete, readonly = false, disableComp
       style={style}
       className="bg-gray-800 rounded-lg p-3 shadow-sm hover:shadow-md transition-shadow"
       data-testid="todo-item"
+      data-todo-id={todo.id}
     >
       <div className="flex items-center gap-3">
         {/* Drag Handle */}
@@ -81,9 +89,31 @@ function DraggableTodo({ todo, onToggle, onDelete, readonly = false, disableComp
             } text-sm`}
           >
             {todo.text}
+            {todo.progress && (
+              <span className="ml-2 text-xs text-gray-400" data-testid="todo-progress">
+                {todo.progress.completed}/{todo.progress.total}
+              </span>
+            )}
+            {todo.priority && todo.priority !== 'none' && (
+              <span
+                className={`ml-2 px-1.5 py-0.5 rounded text-xs ${PRIORITY_STYLES[todo.priority]}`}
+              >
+                !{todo.priority}
+              </span>
+            )}
+            {todo.tags?.map((tag) => (
+              <span
+                key={tag.id}
+                className="ml-2 px-1.5 py-0.5 rounded text-xs bg-slate-700 text-gray-200"
+                style={tag.color ? { backgroundColor: tag.color } : undefined}
+              >
+                #{tag.name}
+              </span>
+            ))}
           </p>
           <p className="text-xs text-gray-500 mt-1">
             {todo.createdAt?.toLocaleString()}
+            {todo.original_date && ` · carried over from ${todo.original_date}`}
           </p>
         </div>
 
@@ -6,6 +6,7 @@ import SplashScreen from "./components/SplashScreen";
 
 const App = lazy(() => import("./App"));
 const CompactApp = lazy(() => import("./CompactApp"));
+const QuickAddApp = lazy(() => import("./QuickAddApp"));
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
@@ -14,6 +15,7 @@ ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
         <Routes>
           <Route path="/" element={<App />} />
           <Route path="/compact" element={<CompactApp />} />
+          <Route path="/quick-add" element={<QuickAddApp />} />
         </Routes>
       </Suspense>
     </BrowserRouter>
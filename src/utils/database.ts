@@ -1,299 +1,437 @@
 import { emit } from './events';
 import { measure } from './telemetry';
 
+export interface Tag {
+  id: number;
+  name: string; // without the leading '#'
+  color: string | null; // #rrggbb
+}
+
+export interface TagUsage extends Tag {
+  todoCount: number;
+}
+
+export type Priority = 'none' | 'low' | 'medium' | 'high' | 'urgent';
+
+// 'priority' sorts by priority first and by drag order within a priority.
+export type SortMode = 'manual' | 'priority';
+
+export interface Project {
+  id: number;
+  name: string;
+  color: string | null; // #rrggbb
+  icon: string | null;
+  sort_order: number;
+  archived: boolean;
+  created_at: string;
+}
+
+export interface ProjectSummary extends Project {
+  activeCount: number;
+  completedCount: number;
+}
+
 export interface Todo {
   id: number;
+  uid: string; // random UUID that survives export and import
   text: string;
+  notes: string | null; // Markdown; show it through renderNotes
   completed: boolean;
+  scheduled_date: string; // YYYY-MM-DD the todo is listed under
+  due_at: string | null; // UTC RFC 3339 deadline
+  remind_at: string | null; // UTC RFC 3339 time a notification is shown
   created_at: string;
+  updated_at: string;
+  completed_at: string | null;
+  original_date: string | null; // day it was planned for before carry-over
+  carried_from: number | null; // todo it was copied from by carry-over
+  project_id: number | null;
+  priority: Priority;
+  parent_id: number | null; // todo this one is a subtask of
+  recurrence: string | null; // RRULE text, e.g. FREQ=WEEKLY;BYDAY=MO
+  recurs_from: number | null; // previous instance of a repeating todo
   sort_order: number;
+  tags: Tag[];
+  progress: { completed: number; total: number } | null; // direct subtasks
   createdAt?: Date;
 }
 
-function isTauri(): boolean {
-  return typeof window !== 'undefined' && !!(window as any).__TAURI__;
+// A file copied into the app and attached to a todo.
+export interface Attachment {
+  id: number;
+  todo_id: number;
+  name: string; // file name it was attached with
+  size: number; // bytes
+  blob: string; // content-addressed file name in the attachments folder
+  created_at: string;
 }
 
-// ---------------- Tauri (SQLite) implementation ----------------
-class TauriDatabaseService {
-  private db: any | null = null;
-  private initialized = false;
-  // Ensure single-writer semantics for write operations
-  private writeChain: Promise<any> = Promise.resolve();
+// Sanitized HTML for a todo's notes and the links it may open.
+export interface RenderedNotes {
+  html: string;
+  links: string[];
+}
 
-  private serialize<T>(fn: () => Promise<T>): Promise<T> {
-    const next = this.writeChain.then(fn);
-    // prevent unhandled rejection from breaking the chain
-    this.writeChain = next.then(() => undefined).catch(() => undefined);
-    return next;
-  }
+// A `search_todos` result. `title` and `snippet` are escaped HTML with the
+// matches in <mark>; `snippet` is an excerpt of the notes when they matched.
+export interface SearchHit {
+  todo: Todo;
+  title: string;
+  snippet: string | null;
+}
 
-  private async begin(): Promise<void> {
-    await this.db!.execute('BEGIN IMMEDIATE');
-  }
+// What quick-add input parses to. `text` is the input without the parts
+// that were recognized; `spans` locate those parts in the input, in
+// JavaScript string offsets.
+export interface QuickAddSpan {
+  start: number;
+  end: number;
+  kind: 'date' | 'time' | 'tag' | 'priority' | 'recurrence' | 'project';
+}
 
-  private async commit(): Promise<void> {
-    await this.db!.execute('COMMIT');
-  }
+export interface QuickAdd {
+  text: string;
+  date: string | null;
+  due_at: string | null;
+  tags: string[];
+  priority: Priority | null;
+  recurrence: string | null;
+  project_id: number | null;
+  project: string | null;
+  spans: QuickAddSpan[];
+}
 
-  private async rollback(): Promise<void> {
-    try { await this.db!.execute('ROLLBACK'); } catch {}
-  }
+export type ImportStrategy = 'replace' | 'merge' | 'append';
+export type ReportFormat = 'csv' | 'markdown';
+
+// What `import_json` did with the todos of the document.
+export interface ImportReport {
+  created: number;
+  updated: number;
+  skipped: number;
+}
+
+// A top-level todo of a day with its subtasks, as returned by `todo_tree`.
+export interface TodoNode extends Todo {
+  children: TodoNode[];
+}
+
+// What the compact window shows: the next task for today and how many remain.
+export interface FirstTodo {
+  todo: Todo | null;
+  activeCount: number;
+}
+
+function isTauri(): boolean {
+  return typeof window !== 'undefined' && !!(window as any).__TAURI__;
+}
+
+// ---------------- Tauri (native store) implementation ----------------
+// All SQL lives in the Rust `store` module; the backend emits the change
+// events itself, so this class only forwards calls.
+class TauriDatabaseService {
+  private invoke: (<T>(cmd: string, args?: Record<string, unknown>) => Promise<T>) | null = null;
 
   async init(): Promise<void> {
     try {
-      const [{ appDataDir }, { default: Database }] = await Promise.all([
-        import('@tauri-apps/api/path'),
-        import('@tauri-apps/plugin-sql'),
-      ]);
-
-      const dataDir = await appDataDir();
-      const dbPath = `sqlite:${dataDir.replace(/\\/g, '/')}/rtodo.db`;
-      // @ts-ignore dynamic module
-      this.db = await (Database as any).load(dbPath);
-
-      // SQLite tuning pragmas
-      try {
-        await this.db.execute(`PRAGMA journal_mode=WAL`);
-        await this.db.execute(`PRAGMA synchronous=NORMAL`);
-        await this.db.execute(`PRAGMA busy_timeout=5000`);
-        await this.db.execute(`PRAGMA foreign_keys=ON`);
-        await this.db.execute(`PRAGMA cache_size=-20000`); // ~20MB cache
-        await this.db.execute(`PRAGMA temp_store=MEMORY`);
-      } catch (e) {
-        console.warn('SQLite PRAGMA setup failed or partially applied:', e);
-      }
-
-      await this.db.execute(`
-        CREATE TABLE IF NOT EXISTS todos (
-          id INTEGER PRIMARY KEY AUTOINCREMENT,
-          text TEXT NOT NULL,
-          completed BOOLEAN NOT NULL DEFAULT 0,
-          created_at TEXT NOT NULL,
-          sort_order INTEGER NOT NULL DEFAULT 0
-        )
-      `);
-
-      // Helpful indexes
-      try {
-        await this.db.execute(`CREATE INDEX IF NOT EXISTS idx_todos_completed_sort_created ON todos (completed, sort_order, created_at DESC)`);
-      } catch {}
-      try {
-        // Expression index to accelerate date-based queries if supported by SQLite version
-        await this.db.execute(`CREATE INDEX IF NOT EXISTS idx_todos_date_completed_sort ON todos (date(created_at), completed, sort_order)`);
-      } catch {}
-
-      // Backfill sort_order for legacy rows
-      await this.db.execute(`
-        UPDATE todos SET sort_order = id WHERE sort_order = 0
-      `);
-
-      this.initialized = true;
+      const { invoke } = await import('@tauri-apps/api/core');
+      this.invoke = invoke;
     } catch (error) {
       console.error('Failed to initialize database:', error);
       throw error;
     }
   }
 
-  private checkInitialized(): void {
-    if (!this.initialized || !this.db) {
+  private call<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
+    if (!this.invoke) {
       throw new Error('Database not initialized');
     }
+    return this.invoke<T>(cmd, args);
+  }
+
+  private toPublic(todo: Todo): Todo {
+    return { ...todo, createdAt: new Date(todo.created_at) };
   }
 
   async getTodos(): Promise<Todo[]> {
-    this.checkInitialized();
-    const result = await this.db.select('SELECT * FROM todos ORDER BY completed ASC, sort_order ASC, created_at DESC');
-    return (result as any[]).map((todo: any) => ({
-      ...todo,
-      completed: Boolean(todo.completed),
-      createdAt: new Date(todo.created_at),
-    }));
+    const todos = await this.call<Todo[]>('list_todos');
+    return todos.map((t) => this.toPublic(t));
   }
 
-  async addTodo(text: string, date?: string): Promise<Todo> {
-    this.checkInitialized();
-    return measure('db.addTodo', async () => this.serialize(async () => {
-      const now = date ? new Date(date).toISOString() : new Date().toISOString();
-      try {
-        await this.begin();
-        const maxSortResult = (await this.db!.select(
-          `SELECT MAX(sort_order) as max_sort FROM todos WHERE completed = 0 AND date(created_at) = date(?)`,
-          [now]
-        )) as any[];
-        const nextSort = (maxSortResult[0]?.max_sort || 0) + 1;
-
-        const result = (await this.db!.select(
-          'INSERT INTO todos (text, completed, created_at, sort_order) VALUES (?, ?, ?, ?) RETURNING *',
-          [text, 0, now, nextSort]
-        )) as any[];
-        await this.commit();
-
-        const newTodo: Todo = {
-          ...result[0],
-          completed: Boolean(result[0].completed),
-          createdAt: new Date(result[0].created_at),
-        };
-
-        await emit('todo-added', { todo: newTodo });
-        return newTodo;
-      } catch (e) {
-        await this.rollback();
-        throw e;
-      }
-    }));
+  // `dueAt` takes the same formats as `setTodoDue`.
+  async addTodo(
+    text: string,
+    date?: string,
+    projectId?: number,
+    priority?: Priority,
+    dueAt?: string,
+  ): Promise<Todo> {
+    return measure('db.addTodo', async () =>
+      this.toPublic(await this.call<Todo>('add_todo', { text, date, dueAt, projectId, priority })),
+    );
+  }
+
+  // Adds a todo from quick-add input such as "Pay rent tomorrow 9am #home".
+  // `date` is used when the text names no day.
+  async quickAdd(text: string, date?: string): Promise<Todo> {
+    return measure('db.quickAdd', async () =>
+      this.toPublic(await this.call<Todo>('quick_add', { text, date })),
+    );
+  }
+
+  async parseQuickAdd(text: string, date?: string): Promise<QuickAdd> {
+    return this.call<QuickAdd>('parse_quick_add', { text, date });
   }
 
   async updateTodo(id: number, updates: Partial<Pick<Todo, 'text' | 'completed'>>): Promise<Todo> {
-    this.checkInitialized();
-    return this.serialize(async () => {
-      const setClause = Object.keys(updates)
-        .map((key) => `${key} = ?`)
-        .join(', ');
-      const values = Object.values(updates).map((value) => (typeof value === 'boolean' ? (value ? 1 : 0) : value));
-      values.push(String(id));
-
-      const result = (await this.db!.select(`UPDATE todos SET ${setClause} WHERE id = ? RETURNING *`, values as any[])) as any[];
-      return {
-        ...result[0],
-        completed: Boolean(result[0].completed),
-        createdAt: new Date(result[0].created_at),
-      };
+    return this.toPublic(await this.call<Todo>('update_todo', { id, ...updates }));
+  }
+
+  async setTodoDue(id: number, dueAt: string | null): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('set_todo_due', { id, dueAt }));
+  }
+
+  // Null removes the reminder. A new time fires even if an earlier one did.
+  async setTodoReminder(id: number, remindAt: string | null): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('set_todo_reminder', { id, remindAt }));
+  }
+
+  async snoozeReminder(id: number, minutes: number): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('snooze_reminder', { id, minutes }));
+  }
+
+  async addSubtask(parentId: number, text: string): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('add_todo', { text, parentId }));
+  }
+
+  async getTodoTree(date: string): Promise<TodoNode[]> {
+    const toNode = (node: TodoNode): TodoNode => ({
+      ...this.toPublic(node),
+      children: node.children.map(toNode),
     });
+    const nodes = await this.call<TodoNode[]>('todo_tree', { date });
+    return nodes.map(toNode);
+  }
+
+  // Moves a todo and its subtasks under `parentId` (or to the top level with
+  // null) at `position` among the new siblings, or at the end.
+  async moveTodo(id: number, parentId: number | null, position?: number): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('move_todo', { id, parentId, position }));
+  }
+
+  async setTodoPriority(id: number, priority: Priority): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('set_todo_priority', { id, priority }));
+  }
+
+  // Accepts RRULE text or a phrase such as "every 2 weeks on monday"; null
+  // stops the todo repeating.
+  async setTodoRecurrence(id: number, rule: string | null): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('set_todo_recurrence', { id, rule }));
+  }
+
+  // Null or blank text removes the notes.
+  async setTodoNotes(id: number, notes: string | null): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('set_todo_notes', { id, notes }));
+  }
+
+  async renderNotes(id: number): Promise<RenderedNotes> {
+    return this.call<RenderedNotes>('render_notes', { id });
+  }
+
+  // Opens one of the links from a todo's notes outside the app.
+  async openLink(id: number, url: string): Promise<void> {
+    await this.call<void>('open_link', { id, url });
+  }
+
+  async listAttachments(todoId: number): Promise<Attachment[]> {
+    return this.call<Attachment[]>('list_attachments', { todoId });
+  }
+
+  // Copies the files last dropped onto the window into the app and attaches
+  // them to the todo. The backend keeps the dropped paths itself.
+  async attachDroppedFiles(todoId: number): Promise<Attachment[]> {
+    return this.call<Attachment[]>('attach_dropped_files', { todoId });
+  }
+
+  async openAttachment(id: number): Promise<void> {
+    await this.call<void>('open_attachment', { id });
+  }
+
+  async removeAttachment(id: number): Promise<boolean> {
+    return this.call<boolean>('remove_attachment', { id });
+  }
+
+  async getOverdueTodos(): Promise<Todo[]> {
+    const todos = await this.call<Todo[]>('overdue_todos');
+    return todos.map((t) => this.toPublic(t));
+  }
+
+  // Searches every day. Words match as prefixes, "quoted phrases" exactly.
+  async searchTodos(query: string, limit?: number): Promise<SearchHit[]> {
+    const hits = await this.call<SearchHit[]>('search_todos', { query, limit });
+    return hits.map((hit) => ({ ...hit, todo: this.toPublic(hit.todo) }));
   }
 
   async deleteTodo(id: number): Promise<boolean> {
-    this.checkInitialized();
-    return this.serialize(async () => {
-      await this.db!.execute('DELETE FROM todos WHERE id = ?', [String(id)]);
-      await emit('todo-deleted', { id });
-      return true;
-    });
+    return this.call<boolean>('delete_todo', { id });
   }
 
-  async clearCompleted(): Promise<number> {
-    this.checkInitialized();
-    const result = await this.db.execute('DELETE FROM todos WHERE completed = 1');
-    return result.rowsAffected || 0;
+  // The whole database, attached files included, as a versioned JSON document.
+  async exportJson(): Promise<string> {
+    return this.call<string>('export_json');
   }
 
-  async toggleTodo(id: number): Promise<Todo> {
-    this.checkInitialized();
-    return measure('db.toggle', async () => this.serialize(async () => {
-      try {
-        await this.begin();
-        const result = (await this.db!.select('UPDATE todos SET completed = NOT completed WHERE id = ? RETURNING *', [String(id)])) as any[];
-        const updatedTodo: Todo = {
-          ...result[0],
-          completed: Boolean(result[0].completed),
-          createdAt: new Date(result[0].created_at),
-        };
-
-        await this.reorderAfterStatusChange(true);
-        await this.commit();
-
-        await emit('todo-updated', { todo: updatedTodo, action: 'toggled' });
-        return updatedTodo;
-      } catch (e) {
-        await this.rollback();
-        throw e;
-      }
-    }));
+  async importJson(json: string, strategy: ImportStrategy): Promise<ImportReport> {
+    return this.call<ImportReport>('import_json', { json, strategy });
   }
 
-  private async reorderAfterStatusChange(inTransaction = false): Promise<void> {
-    const todos = (await this.db!.select('SELECT id, completed FROM todos ORDER BY completed ASC, sort_order ASC, created_at DESC')) as any[];
-    const incompleteTodos = todos.filter((t) => !Boolean(t.completed));
-    const completedTodos = todos.filter((t) => Boolean(t.completed));
+  // Every todo as todo.txt lines.
+  async exportTodoTxt(): Promise<string> {
+    return this.call<string>('export_todo_txt');
+  }
 
-    const exec = async () => {
-      for (let i = 0; i < incompleteTodos.length; i++) {
-        await this.db!.execute('UPDATE todos SET sort_order = ? WHERE id = ?', [String(i), String(incompleteTodos[i].id)]);
-      }
-      for (let i = 0; i < completedTodos.length; i++) {
-        await this.db!.execute('UPDATE todos SET sort_order = ? WHERE id = ?', [String(incompleteTodos.length + i), String(completedTodos[i].id)]);
-      }
-    };
+  async importTodoTxt(text: string, strategy: ImportStrategy): Promise<ImportReport> {
+    return this.call<ImportReport>('import_todo_txt', { text, strategy });
+  }
 
-    if (inTransaction) {
-      await exec();
-    } else {
-      await this.serialize(async () => {
-        try { await this.begin(); await exec(); await this.commit(); } catch (e) { await this.rollback(); throw e; }
-      });
-    }
+  // Every todo as an iCalendar document of VTODOs.
+  async exportIcal(): Promise<string> {
+    return this.call<string>('export_ical');
   }
 
-  async getFirstTodo(): Promise<Todo | null> {
-    this.checkInitialized();
-    const result = (await this.db.select('SELECT * FROM todos ORDER BY sort_order ASC, created_at DESC LIMIT 1')) as any[];
-    if (result.length > 0) {
-      return { ...result[0], completed: Boolean(result[0].completed), createdAt: new Date(result[0].created_at) };
-    }
-    return null;
+  async importIcal(text: string, strategy: ImportStrategy): Promise<ImportReport> {
+    return this.call<ImportReport>('import_ical', { text, strategy });
+  }
+
+  // The todos of the days from `from` to `to`, both included, as a report.
+  async report(from: string, to: string, format: ReportFormat): Promise<string> {
+    return this.call<string>('report', { from, to, format });
+  }
+
+  async getTags(): Promise<TagUsage[]> {
+    return this.call<TagUsage[]>('list_tags');
+  }
+
+  async tagTodo(id: number, tags: string[]): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('tag_todo', { id, tags }));
+  }
+
+  async untagTodo(id: number, tags: string[]): Promise<Todo> {
+    return this.toPublic(await this.call<Todo>('untag_todo', { id, tags }));
+  }
+
+  async renameTag(name: string, newName: string): Promise<Tag> {
+    return this.call<Tag>('rename_tag', { name, newName });
+  }
+
+  async mergeTags(sources: string[], into: string): Promise<Tag> {
+    return this.call<Tag>('merge_tags', { sources, into });
+  }
+
+  async setTagColor(name: string, color: string | null): Promise<Tag> {
+    return this.call<Tag>('set_tag_color', { name, color });
+  }
+
+  async deleteTag(name: string): Promise<boolean> {
+    return this.call<boolean>('delete_tag', { name });
+  }
+
+  // 'all' matches todos carrying every tag, 'any' at least one of them.
+  async getTodosByTags(tags: string[], mode: 'all' | 'any' = 'all', date?: string): Promise<Todo[]> {
+    const todos = await this.call<Todo[]>('todos_by_tags', { tags, mode, date });
+    return todos.map((t) => this.toPublic(t));
+  }
+
+  async getProjects(includeArchived = false): Promise<ProjectSummary[]> {
+    return this.call<ProjectSummary[]>('list_projects', { includeArchived });
+  }
+
+  async createProject(name: string, color?: string, icon?: string): Promise<Project> {
+    return this.call<Project>('create_project', { name, color, icon });
+  }
+
+  async renameProject(id: number, name: string): Promise<Project> {
+    return this.call<Project>('rename_project', { id, name });
+  }
+
+  async setProjectStyle(id: number, color: string | null, icon: string | null): Promise<Project> {
+    return this.call<Project>('set_project_style', { id, color, icon });
+  }
+
+  async archiveProject(id: number, archived: boolean): Promise<Project> {
+    return this.call<Project>('archive_project', { id, archived });
+  }
+
+  async reorderProjects(projectIds: number[]): Promise<void> {
+    await this.call<void>('reorder_projects', { projectIds });
+  }
+
+  // Deletes the project's todos too when `cascade` is set; otherwise they move
+  // to `reassignTo`, or out of any project. Resolves to the number affected.
+  async deleteProject(id: number, cascade: boolean, reassignTo?: number): Promise<number> {
+    return this.call<number>('delete_project', { id, cascade, reassignTo });
+  }
+
+  async getProjectTodos(id: number): Promise<Todo[]> {
+    const todos = await this.call<Todo[]>('project_todos', { id });
+    return todos.map((t) => this.toPublic(t));
+  }
+
+  async reorderProjectTodos(id: number, todoIds: number[]): Promise<void> {
+    await this.call<void>('reorder_project_todos', { id, todoIds });
+  }
+
+  async moveTodosToProject(todoIds: number[], projectId: number | null): Promise<Todo[]> {
+    const todos = await this.call<Todo[]>('move_todos_to_project', { todoIds, projectId });
+    return todos.map((t) => this.toPublic(t));
+  }
+
+  async clearCompleted(): Promise<number> {
+    return this.call<number>('clear_completed');
+  }
+
+  async toggleTodo(id: number): Promise<Todo> {
+    return measure('db.toggle', async () => this.toPublic(await this.call<Todo>('toggle_todo', { id })));
+  }
+
+  async getFirstTodo(): Promise<FirstTodo> {
+    const first = await this.call<FirstTodo>('first_todo');
+    return { ...first, todo: first.todo ? this.toPublic(first.todo) : null };
   }
 
   async reorderTodos(todoIds: number[]): Promise<void> {
-    this.checkInitialized();
-    await measure('db.reorder', async () => this.serialize(async () => {
-      const todos = await this.getTodos();
-      const incomplete: number[] = [];
-      const completed: number[] = [];
-      todoIds.forEach((id) => {
-        const t = todos.find((x) => x.id === id);
-        if (!t) return;
-        if (t.completed) completed.push(id);
-        else incomplete.push(id);
-      });
+    await measure('db.reorder', async () => this.call<void>('reorder_todos', { todoIds }));
+  }
 
-      try {
-        await this.begin();
-        for (let i = 0; i < incomplete.length; i++) {
-          await this.db!.execute('UPDATE todos SET sort_order = ? WHERE id = ?', [String(i), String(incomplete[i])]);
-        }
-        for (let i = 0; i < completed.length; i++) {
-          await this.db!.execute('UPDATE todos SET sort_order = ? WHERE id = ?', [String(incomplete.length + i), String(completed[i])]);
-        }
-        await this.commit();
-      } catch (e) {
-        await this.rollback();
-        throw e;
-      }
+  // The backend decides which day "today" is (local zone, day start hour).
+  async getToday(): Promise<string> {
+    return this.call<string>('today');
+  }
+
+  async getSortMode(): Promise<SortMode> {
+    return this.call<SortMode>('sort_mode');
+  }
 
-      await emit('todos-reordered', { todoIds });
-    }));
+  async setSortMode(mode: SortMode): Promise<void> {
+    await this.call<void>('set_sort_mode', { mode });
   }
 
   async getHistoricalDates(): Promise<string[]> {
-    this.checkInitialized();
-    const result = (await this.db.select(`
-      SELECT DISTINCT DATE(created_at) as date
-      FROM todos
-      WHERE DATE(created_at) <= DATE('now', 'localtime')
-      ORDER BY date DESC
-    `)) as any[];
-    return result.map((row: any) => row.date);
+    return this.call<string[]>('historical_dates');
   }
 
   async getTodosByDate(date: string): Promise<Todo[]> {
-    this.checkInitialized();
-    const result = (await this.db.select('SELECT * FROM todos WHERE DATE(created_at) = ? ORDER BY completed ASC, sort_order ASC, created_at DESC', [date])) as any[];
-    return result.map((todo: any) => ({
-      ...todo,
-      completed: Boolean(todo.completed),
-      createdAt: new Date(todo.created_at),
-    }));
+    const todos = await this.call<Todo[]>('todos_by_date', { date });
+    return todos.map((t) => this.toPublic(t));
   }
 
   async getFutureDates(): Promise<string[]> {
-    this.checkInitialized();
-    const result = (await this.db.select(`
-      SELECT DISTINCT DATE(created_at) as date
-      FROM todos
-      WHERE DATE(created_at) > DATE('now', 'localtime')
-      ORDER BY date ASC
-    `)) as any[];
-    return result.map((row: any) => row.date);
+    return this.call<string[]>('future_dates');
+  }
+
+  // Day passed with `rtodo --date` when the app was launched
+  async takePendingDate(): Promise<string | null> {
+    return this.call<string | null>('take_pending_date');
   }
 }
 
@@ -314,7 +452,10 @@ function getTestId(): string {
 }
 
 function todayStr(): string {
-  return new Date().toISOString().split('T')[0];
+  const now = new Date();
+  const month = String(now.getMonth() + 1).padStart(2, '0');
+  const day = String(now.getDate()).padStart(2, '0');
+  return `${now.getFullYear()}-${month}-${day}`;
 }
 
 class WebDatabaseService {
@@ -368,19 +509,60 @@ class WebDatabaseService {
   async addTodo(text: string, date?: string): Promise<Todo> {
     this.checkInitialized();
     const data = this.read();
-    const dateKey = date ? new Date(date).toISOString().split('T')[0] : todayStr();
+    const dateKey = date ?? todayStr();
     this.ensureDate(data, dateKey);
 
     const nextId = ++data.seq;
     const nextSort = (data.todos[dateKey].filter((t) => !t.completed).reduce((m, t) => Math.max(m, t.sort_order), 0) || 0) + 1;
-    const nowIso = date ? new Date(date).toISOString() : new Date().toISOString();
-    const newTodo: Todo = { id: nextId, text, completed: false, created_at: nowIso, sort_order: nextSort };
+    const nowIso = new Date().toISOString();
+    const newTodo: Todo = {
+      id: nextId,
+      uid: crypto.randomUUID(),
+      text,
+      notes: null,
+      completed: false,
+      scheduled_date: dateKey,
+      due_at: null,
+      remind_at: null,
+      created_at: nowIso,
+      updated_at: nowIso,
+      completed_at: null,
+      original_date: null,
+      carried_from: null,
+      project_id: null,
+      priority: 'none',
+      parent_id: null,
+      recurrence: null,
+      recurs_from: null,
+      sort_order: nextSort,
+      tags: [],
+      progress: null,
+    };
     data.todos[dateKey].push(newTodo);
     this.write(data);
     await emit('todo-added', { todo: this.toPublic(newTodo) });
     return this.toPublic(newTodo);
   }
 
+  // The browser mock has no parser; input is taken literally.
+  async quickAdd(text: string, date?: string): Promise<Todo> {
+    return this.addTodo(text, date);
+  }
+
+  async parseQuickAdd(text: string, date?: string): Promise<QuickAdd> {
+    return {
+      text,
+      date: date ?? null,
+      due_at: null,
+      tags: [],
+      priority: null,
+      recurrence: null,
+      project_id: null,
+      project: null,
+      spans: [],
+    };
+  }
+
   async updateTodo(id: number, updates: Partial<Pick<Todo, 'text' | 'completed'>>): Promise<Todo> {
     this.checkInitialized();
     const data = this.read();
@@ -388,7 +570,7 @@ class WebDatabaseService {
       const arr = data.todos[date];
       const idx = arr.findIndex((t) => t.id === id);
       if (idx >= 0) {
-        const updated = { ...arr[idx], ...updates } as Todo;
+        const updated = { ...arr[idx], ...updates, updated_at: new Date().toISOString() } as Todo;
         arr[idx] = updated;
         this.write(data);
         return this.toPublic(updated);
@@ -397,6 +579,31 @@ class WebDatabaseService {
     throw new Error('Todo not found');
   }
 
+  async setTodoDue(id: number, dueAt: string | null): Promise<Todo> {
+    this.checkInitialized();
+    const data = this.read();
+    for (const date of Object.keys(data.todos)) {
+      const todo = data.todos[date].find((t) => t.id === id);
+      if (todo) {
+        todo.due_at = dueAt ? new Date(dueAt).toISOString() : null;
+        todo.updated_at = new Date().toISOString();
+        this.write(data);
+        const updated = this.toPublic(todo);
+        await emit('todo-updated', { todo: updated, action: 'updated' });
+        return updated;
+      }
+    }
+    throw new Error('Todo not found');
+  }
+
+  async getOverdueTodos(): Promise<Todo[]> {
+    this.checkInitialized();
+    const now = new Date().toISOString();
+    return (await this.getTodos())
+      .filter((t) => !t.completed && t.due_at !== null && t.due_at < now)
+      .sort((a, b) => (a.due_at! < b.due_at! ? -1 : 1));
+  }
+
   async deleteTodo(id: number): Promise<boolean> {
     this.checkInitialized();
     const data = this.read();
@@ -434,6 +641,8 @@ class WebDatabaseService {
       const idx = arr.findIndex((t) => t.id === id);
       if (idx >= 0) {
         arr[idx].completed = !arr[idx].completed;
+        arr[idx].completed_at = arr[idx].completed ? new Date().toISOString() : null;
+        arr[idx].updated_at = new Date().toISOString();
         await this.reorderAfterStatusChange(data, date);
         const updated = this.toPublic(arr[idx]);
         this.write(data);
@@ -452,16 +661,10 @@ class WebDatabaseService {
     completed.forEach((t, i) => (t.sort_order = incomplete.length + i));
   }
 
-  async getFirstTodo(): Promise<Todo | null> {
+  async getFirstTodo(): Promise<FirstTodo> {
     this.checkInitialized();
-    const data = this.read();
-    const all = Object.values(data.todos).flat();
-    if (all.length === 0) return null;
-    const sorted = all.slice().sort((a, b) => {
-      if (a.sort_order !== b.sort_order) return a.sort_order - b.sort_order;
-      return new Date(b.created_at).getTime() - new Date(a.created_at).getTime();
-    });
-    return this.toPublic(sorted[0]);
+    const active = (await this.getTodosByDate(todayStr())).filter((t) => !t.completed);
+    return { todo: active[0] ?? null, activeCount: active.length };
   }
 
   async reorderTodos(todoIds: number[]): Promise<void> {
@@ -512,6 +715,10 @@ class WebDatabaseService {
     return Object.keys(data.todos).sort();
   }
 
+  async getToday(): Promise<string> {
+    return todayStr();
+  }
+
   async getHistoricalDates(): Promise<string[]> {
     this.checkInitialized();
     const today = todayStr();
@@ -537,6 +744,10 @@ class WebDatabaseService {
     const today = todayStr();
     return this.listDates().filter((d) => d > today).sort();
   }
+
+  async takePendingDate(): Promise<string | null> {
+    return null;
+  }
 }
 
 export const database = isTauri() ? new TauriDatabaseService() : new WebDatabaseService();
@@ -42,7 +42,7 @@ class Store {
         todos = await database.getTodosByDate(date);
       } else {
         // Get today's date
-        const today = new Date().toISOString().split('T')[0];
+        const today = await database.getToday();
         todos = await database.getTodosByDate(today);
       }
       this.setState({ todos, loading: false });
@@ -51,10 +51,10 @@ class Store {
     }
   }
 
-  // Add new todo
+  // Add new todo from quick-add input
   async addTodo(text: string, date?: string): Promise<void> {
     try {
-      await database.addTodo(text, date);
+      await database.quickAdd(text, date);
       await this.loadTodos(date); // Reload todos for the specific date to maintain proper order
     } catch (error) {
       this.setState({ error: String(error) });